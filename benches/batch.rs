@@ -0,0 +1,61 @@
+//! Columnar decoding with `seq2dec_offsets` against the naive per-row loop.
+//!
+//! Run with `cargo bench --bench batch`.
+extern crate numsys;
+
+use std::str;
+use std::time::Instant;
+
+use numsys::{decode, seq2dec_offsets, OverflowMode, DIGITS_UPPER_AZ};
+
+
+/// Buffer and offsets of `count` short base-36 rows, every 100th one invalid.
+fn column(count: usize) -> (Vec<u8>, Vec<usize>) {
+    let mut state: u64 = 243;
+    let mut data = Vec::new();
+    let mut offsets = vec![0];
+    for row in 0..count {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        for index in 0..1 + state as usize % 8 {
+            let digit = (state >> (index * 5)) as usize % 36;
+            data.push(if row % 100 == 99 && index == 0 { b'!' } else { DIGITS_UPPER_AZ[digit] as u8 });
+        }
+        offsets.push(data.len());
+    }
+    (data, offsets)
+}
+
+
+fn main() {
+    let alphabet = &DIGITS_UPPER_AZ[..36];
+    let (data, offsets) = column(1_000_000);
+    let rows = offsets.len() - 1;
+
+    let start = Instant::now();
+    let mut naive = Vec::with_capacity(rows);
+    let mut naive_errors = 0;
+    for pair in offsets.windows(2) {
+        let row = str::from_utf8(&data[pair[0]..pair[1]]).unwrap().to_string();
+        match decode(&row, alphabet, OverflowMode::Error) {
+            Ok(value) => naive.push(value as u64),
+            Err(_) => {
+                naive_errors += 1;
+                naive.push(0);
+            }
+        }
+    }
+    let naive_time = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    let mut out = Vec::with_capacity(rows);
+    let report = seq2dec_offsets(&data, &offsets, alphabet, &mut out).unwrap();
+    let batch_time = start.elapsed().as_secs_f64();
+
+    assert_eq!(out, naive);
+    assert_eq!(report.errors.len(), naive_errors);
+    println!("{} rows, {} invalid", rows, naive_errors);
+    println!("per row  {:>8.3} s", naive_time);
+    println!("offsets  {:>8.3} s", batch_time);
+}
@@ -0,0 +1,89 @@
+//! Interned against plain encoding of a skewed distribution of values.
+//!
+//! Run with `cargo bench --bench intern`, it reports time and allocations of both paths.
+extern crate numsys;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use numsys::{dec2seq, InterningEncoder, DIGITS_UPPER_AZ};
+
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+
+/// Values of a log pipeline: 9 in 10 are a few hundred ones, the rest anything below 100_000.
+fn skewed(count: usize) -> Vec<usize> {
+    let mut state: u64 = 227;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            if state % 10 != 0 { (state >> 8) as usize % 200 } else { (state >> 8) as usize % 100_000 }
+        })
+        .collect()
+}
+
+
+/// Runs `encode` over `values`, returning seconds taken and allocations made.
+fn measure<F: FnMut(usize) -> usize>(values: &[usize], mut encode: F) -> (f64, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut total = 0;
+    for value in values {
+        total += encode(*value);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    assert!(total > 0);
+    (elapsed, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+
+fn main() {
+    let alphabet = &DIGITS_UPPER_AZ[..36];
+    let values = skewed(1_000_000);
+    let encoder = InterningEncoder::new(alphabet, 1_000).unwrap();
+    let mut kept = Vec::with_capacity(values.len());
+    let (plain_time, plain_allocations) = measure(&values, |value| {
+        let sequence = dec2seq(value, alphabet).unwrap();
+        let len = sequence.len();
+        kept.push(sequence);
+        len
+    });
+    drop(kept);
+    let mut kept = Vec::with_capacity(values.len());
+    let (interned_time, interned_allocations) = measure(&values, |value| {
+        let sequence = encoder.encode(value);
+        let len = sequence.len();
+        kept.push(sequence);
+        len
+    });
+    drop(kept);
+    let stats = encoder.stats();
+    println!("{} encodings, all kept as a pipeline would", values.len());
+    println!("plain    {:>8.3} s {:>10} allocations", plain_time, plain_allocations);
+    println!(
+        "interned {:>8.3} s {:>10} allocations, hit rate {:.3}",
+        interned_time,
+        interned_allocations,
+        stats.hit_rate()
+    );
+}
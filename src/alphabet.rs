@@ -0,0 +1,816 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use {index_chars, Error};
+
+
+/// Validated set of unique chars, each char's value is its index.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::Alphabet;
+///
+/// let alphabet = Alphabet::new(&['a', 'b', 'c']).unwrap();
+/// assert_eq!(alphabet.len(), 3);
+/// assert_eq!(alphabet.value_of('c'), Some(2));
+/// assert_eq!(alphabet.glyph(1), Some('b'));
+/// assert_eq!(alphabet.chars(), &['a', 'b', 'c']);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alphabet {
+    chars: Vec<char>,
+    char2val: HashMap<char, usize>,
+}
+
+impl Alphabet {
+    /// Creates alphabet from `chars`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when `chars` length is 0
+    ///
+    /// * Returns `Error::MultipleChar` when `chars` includes duplicated chars
+    ///
+    /// * Returns `Error::ForbiddenCodePoint` when `chars` includes a noncharacter or a private use
+    ///   code point, `AlphabetBuilder` can allow them
+    ///
+    /// ```
+    /// use numsys::{Alphabet, Error};
+    ///
+    /// let msg = "Char \'\\u{fdd0}\' (U+FDD0) at index 1 MUST NOT be a noncharacter".to_string();
+    /// assert_eq!(Alphabet::new(&['0', '\u{FDD0}']), Err(Error::ForbiddenCodePoint(msg)));
+    /// ```
+    pub fn new(chars: &[char]) -> Result<Alphabet, Error> {
+        Alphabet::with_code_points(chars, false, false)
+    }
+
+    fn with_code_points(chars: &[char], allow_private_use: bool, allow_noncharacters: bool) -> Result<Alphabet, Error> {
+        let char2val = index_chars(chars)?;
+        check_code_points(chars, allow_private_use, allow_noncharacters)?;
+        Ok(Alphabet {
+            char2val,
+            chars: chars.to_vec(),
+        })
+    }
+
+    /// Creates alphabet from `chars` whose `value_of` ignores case.
+    ///
+    /// Both cases of each char get its value. It MUST NOT make two chars
+    /// one digit, see `case_fold_safety`.
+    ///
+    /// ```
+    /// use numsys::{Alphabet, Error};
+    ///
+    /// let hex = Alphabet::new_case_insensitive(&['0', '1', 'A', 'B']).unwrap();
+    /// assert_eq!(hex.value_of('b'), Some(3));
+    /// let msg = "Chars \'A\' and \'a\' MUST NOT fold to the same digit in: [\'A\', \'a\']".to_string();
+    /// assert_eq!(Alphabet::new_case_insensitive(&['A', 'a']), Err(Error::CaseFoldCollision(msg)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when `chars` length is 0
+    ///
+    /// * Returns `Error::MultipleChar` when `chars` includes duplicated chars
+    ///
+    /// * Returns `Error::CaseFoldCollision` when chars differ only by case
+    ///
+    /// * Returns `Error::ForbiddenCodePoint` when `chars` includes a noncharacter or a private use code point
+    pub fn new_case_insensitive(chars: &[char]) -> Result<Alphabet, Error> {
+        let char2val = case_folded_chars(chars)?;
+        check_code_points(chars, false, false)?;
+        Ok(Alphabet {
+            char2val,
+            chars: chars.to_vec(),
+        })
+    }
+
+    /// Tells whether case insensitive lookup keeps chars distinct digits.
+    ///
+    /// ```
+    /// use numsys::{Alphabet, CaseFoldSafety};
+    ///
+    /// let base36: Vec<char> = "0123456789abcdefghijklmnopqrstuvwxyz".chars().collect();
+    /// assert_eq!(Alphabet::new(&base36).unwrap().case_fold_safety(), CaseFoldSafety::Safe);
+    /// let mixed = Alphabet::new(&['x', 'X', 'y']).unwrap();
+    /// assert_eq!(mixed.case_fold_safety(), CaseFoldSafety::Unsafe(vec![('x', 'X')]));
+    /// ```
+    pub fn case_fold_safety(&self) -> CaseFoldSafety {
+        match fold_table(&self.chars) {
+            Ok(_) => CaseFoldSafety::Safe,
+            Err(collisions) => CaseFoldSafety::Unsafe(collisions),
+        }
+    }
+
+    /// Returns chars ordered by their values.
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+
+    /// Returns count of chars, the base.
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Always `false`, an alphabet has at least one char.
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Returns value of `glyph`, if it's included.
+    pub fn value_of(&self, glyph: char) -> Option<usize> {
+        self.char2val.get(&glyph).cloned()
+    }
+
+    /// Returns glyph of `value`, if it's less than `len()`.
+    pub fn glyph(&self, value: usize) -> Option<char> {
+        self.chars.get(value).cloned()
+    }
+
+    /// Compares this (old) alphabet with `other` (new) one, for a migration.
+    ///
+    /// Migration is safe when every sequence valid with the old alphabet is
+    /// either invalid or decodes to the same value with the new one. Values
+    /// are positional, so it requires common chars to keep their values and,
+    /// when the base changes, the only common char to be the zero glyph (with
+    /// another base `"10"` means another value). So even a superset alphabet
+    /// isn't a safe migration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use numsys::Alphabet;
+    ///
+    /// let old = Alphabet::new(&['0', '1', '2', 'I']).unwrap();
+    /// // 'I' is confused with '1', replace it
+    /// let new = Alphabet::new(&['0', '1', '2', 'J']).unwrap();
+    /// let report = old.compatibility(&new);
+    /// assert!(report.safe);
+    /// assert_eq!(report.missing, vec!['I']);
+    /// assert_eq!(report.added, vec!['J']);
+    ///
+    /// let reordered = Alphabet::new(&['1', '0', '2', 'I']).unwrap();
+    /// let report = old.compatibility(&reordered);
+    /// assert!(!report.safe);
+    /// assert_eq!(report.remapped, vec![('0', 0, 1), ('1', 1, 0)]);
+    /// ```
+    pub fn compatibility(&self, other: &Alphabet) -> CompatibilityReport {
+        let mut remapped = Vec::new();
+        let mut missing = Vec::new();
+        let mut common_nonzero = false;
+        for (value, glyph) in self.chars.iter().enumerate() {
+            match other.value_of(*glyph) {
+                Some(new_value) => {
+                    if new_value != value {
+                        remapped.push((*glyph, value, new_value));
+                    }
+                    common_nonzero = common_nonzero || value != 0;
+                }
+                None => missing.push(*glyph),
+            }
+        }
+        let added = other
+            .chars
+            .iter()
+            .filter(|glyph| !self.char2val.contains_key(glyph))
+            .cloned()
+            .collect();
+        let safe = remapped.is_empty() && (self.len() == other.len() || !common_nonzero);
+        CompatibilityReport { remapped, missing, added, safe }
+    }
+}
+
+
+/// Result of `Alphabet::compatibility`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityReport {
+    /// Chars of both alphabets with different values: char, old and new value.
+    pub remapped: Vec<(char, usize, usize)>,
+    /// Chars of the old alphabet missing in the new one.
+    pub missing: Vec<char>,
+    /// Chars of the new alphabet missing in the old one.
+    pub added: Vec<char>,
+    /// Whether every sequence of the old alphabet is invalid or keeps its value with the new one.
+    pub safe: bool,
+}
+
+impl AsRef<[char]> for Alphabet {
+    fn as_ref(&self) -> &[char] {
+        &self.chars
+    }
+}
+
+
+/// Result of `Alphabet::case_fold_safety`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseFoldSafety {
+    /// Each char stays a distinct digit, chars without case included.
+    Safe,
+    /// Pairs of chars which would become one digit.
+    Unsafe(Vec<(char, char)>),
+}
+
+
+/// Returns `glyph` and its single char lower and upper case forms.
+fn case_forms(glyph: char) -> Vec<char> {
+    let mut forms = vec![glyph];
+    let mut lower = glyph.to_lowercase();
+    let mut upper = glyph.to_uppercase();
+    for form in [lower.next().filter(|_| lower.next().is_none()), upper.next().filter(|_| upper.next().is_none())]
+        .iter()
+        .flatten()
+    {
+        if !forms.contains(form) {
+            forms.push(*form);
+        }
+    }
+    forms
+}
+
+/// Builds lookup of both cases of `chars`, or returns pairs of chars colliding.
+fn fold_table(chars: &[char]) -> Result<HashMap<char, usize>, Vec<(char, char)>> {
+    let mut table: HashMap<char, usize> = HashMap::new();
+    let mut collisions = Vec::new();
+    for (value, glyph) in chars.iter().enumerate() {
+        for form in case_forms(*glyph) {
+            match table.get(&form).cloned() {
+                Some(other) if other != value => {
+                    let pair = (chars[other], *glyph);
+                    if !collisions.contains(&pair) {
+                        collisions.push(pair);
+                    }
+                }
+                Some(_) => (),
+                None => {
+                    table.insert(form, value);
+                }
+            }
+        }
+    }
+    if collisions.is_empty() { Ok(table) } else { Err(collisions) }
+}
+
+/// Validates `chars` like `index_chars`, and indexes both cases of each of them.
+pub(crate) fn case_folded_chars(chars: &[char]) -> Result<HashMap<char, usize>, Error> {
+    index_chars(chars)?;
+    fold_table(chars).map_err(|collisions| {
+        let (first, second) = collisions[0];
+        Error::CaseFoldCollision(format!(
+            "Chars {:?} and {:?} MUST NOT fold to the same digit in: {:?}",
+            first,
+            second,
+            chars
+        ))
+    })
+}
+
+
+/// Tells whether `glyph` is one of the 66 Unicode noncharacters.
+fn is_noncharacter(glyph: char) -> bool {
+    let code = glyph as u32;
+    (0xFDD0..=0xFDEF).contains(&code) || code & 0xFFFE == 0xFFFE
+}
+
+/// Tells whether `glyph` is in one of the private use areas.
+fn is_private_use(glyph: char) -> bool {
+    matches!(glyph as u32, 0xE000..=0xF8FF | 0xF_0000..=0xF_FFFD | 0x10_0000..=0x10_FFFD)
+}
+
+/// Rejects code points downstream text sanitizers commonly strip or replace.
+fn check_code_points(chars: &[char], allow_private_use: bool, allow_noncharacters: bool) -> Result<(), Error> {
+    for (idx, glyph) in chars.iter().enumerate() {
+        let rule = if !allow_noncharacters && is_noncharacter(*glyph) {
+            "a noncharacter"
+        } else if !allow_private_use && is_private_use(*glyph) {
+            "a private use code point"
+        } else {
+            continue;
+        };
+        return Err(Error::ForbiddenCodePoint(format!(
+            "Char {:?} (U+{:04X}) at index {} MUST NOT be {}",
+            glyph,
+            *glyph as u32,
+            idx,
+            rule
+        )));
+    }
+    Ok(())
+}
+
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Range(char, char),
+    Chars(String),
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Piece::Range(start, end) => write!(f, "{:?}..={:?}", start, end),
+            Piece::Chars(ref chars) => write!(f, "{:?}", chars),
+        }
+    }
+}
+
+
+/// Builds an `Alphabet` from char ranges and explicit chars.
+///
+/// Values follow declaration order: chars of the first piece get the lowest
+/// values, each range is taken from its start to its end. Excluded chars are
+/// removed from all pieces, no matter where `exclude` was called, and the
+/// remaining chars keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::AlphabetBuilder;
+///
+/// let hex = AlphabetBuilder::new().range('0'..='9').range('a'..='f').chars("-_").build().unwrap();
+/// assert_eq!(hex.len(), 18);
+/// assert_eq!(hex.value_of('a'), Some(10));
+/// assert_eq!(hex.value_of('_'), Some(17));
+///
+/// let no_vowels = AlphabetBuilder::new().range('a'..='z').exclude("aeiou").build().unwrap();
+/// assert_eq!(no_vowels.len(), 21);
+/// assert_eq!(no_vowels.glyph(0), Some('b'));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InvalidRange` when a range starts after its end
+///
+/// ```
+/// use numsys::{AlphabetBuilder, Error};
+///
+/// let msg = "Range \'z\'..=\'a\' MUST start before its end".to_string();
+/// assert_eq!(AlphabetBuilder::new().range('z'..='a').build(), Err(Error::InvalidRange(msg)));
+/// ```
+///
+/// * Returns `Error::MultipleChar` when pieces overlap
+///
+/// ```
+/// use numsys::{AlphabetBuilder, Error};
+///
+/// let msg = "Chars MUST be unique, duplicated: \'5\' in \'0\'..=\'9\' (piece 0) and \"53\" (piece 1)";
+/// let result = AlphabetBuilder::new().range('0'..='9').chars("53").build();
+/// assert_eq!(result, Err(Error::MultipleChar(msg.to_string())));
+/// ```
+///
+/// * Returns `Error::DictEmpty` when no chars remain
+///
+/// * Returns `Error::ForbiddenCodePoint` when a noncharacter or a private use code point remains and
+///   it isn't allowed
+///
+/// ```
+/// use numsys::{AlphabetBuilder, Error};
+///
+/// let msg = "Char \'\\u{e000}\' (U+E000) at index 0 MUST NOT be a private use code point".to_string();
+/// let builder = AlphabetBuilder::new().range('\u{E000}'..='\u{E0FF}');
+/// assert_eq!(builder.build(), Err(Error::ForbiddenCodePoint(msg)));
+/// assert_eq!(builder.allow_private_use().build().unwrap().len(), 256);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AlphabetBuilder {
+    pieces: Vec<Piece>,
+    excluded: Vec<char>,
+    private_use: bool,
+    noncharacters: bool,
+}
+
+impl AlphabetBuilder {
+    /// Creates builder without any chars.
+    pub fn new() -> AlphabetBuilder {
+        AlphabetBuilder::default()
+    }
+
+    /// Appends chars from `range` start to its end (included).
+    pub fn range(mut self, range: RangeInclusive<char>) -> AlphabetBuilder {
+        self.pieces.push(Piece::Range(*range.start(), *range.end()));
+        self
+    }
+
+    /// Appends `chars` in their order.
+    pub fn chars(mut self, chars: &str) -> AlphabetBuilder {
+        self.pieces.push(Piece::Chars(chars.to_string()));
+        self
+    }
+
+    /// Removes `chars` from the alphabet.
+    pub fn exclude(mut self, chars: &str) -> AlphabetBuilder {
+        self.excluded.extend(chars.chars());
+        self
+    }
+
+    /// Accepts private use code points, which mean nothing outside of an agreement between parties.
+    pub fn allow_private_use(mut self) -> AlphabetBuilder {
+        self.private_use = true;
+        self
+    }
+
+    /// Accepts noncharacters (e.g. U+FDD0 or U+FFFE), which are meant for internal use only.
+    pub fn allow_noncharacters(mut self) -> AlphabetBuilder {
+        self.noncharacters = true;
+        self
+    }
+
+    /// Validates pieces and creates the alphabet.
+    pub fn build(&self) -> Result<Alphabet, Error> {
+        let mut origin: HashMap<char, usize> = HashMap::new();
+        let mut chars = Vec::new();
+        for (idx, piece) in self.pieces.iter().enumerate() {
+            let glyphs: Vec<char> = match *piece {
+                Piece::Range(start, end) => {
+                    if start > end {
+                        return Err(Error::InvalidRange(
+                            format!("Range {} MUST start before its end", piece),
+                        ));
+                    }
+                    (start..=end).collect()
+                }
+                Piece::Chars(ref glyphs) => glyphs.chars().collect(),
+            };
+            for glyph in glyphs {
+                if let Some(first) = origin.insert(glyph, idx) {
+                    return Err(Error::MultipleChar(format!(
+                        "Chars MUST be unique, duplicated: {:?} in {} (piece {}) and {} (piece {})",
+                        glyph,
+                        self.pieces[first],
+                        first,
+                        piece,
+                        idx
+                    )));
+                }
+                chars.push(glyph);
+            }
+        }
+        chars.retain(|glyph| !self.excluded.contains(glyph));
+        Alphabet::with_code_points(&chars, self.private_use, self.noncharacters)
+    }
+}
+
+
+/// Validates `alphabet` chars are unique.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::validate_alphabet;
+/// use numsys::Error;
+///
+/// assert_eq!(validate_alphabet(&['0', '1']), Ok(()));
+/// assert_eq!(validate_alphabet(&[]), Err(Error::DictEmpty));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+pub fn validate_alphabet(alphabet: &[char]) -> Result<(), Error> {
+    index_chars(alphabet).map(|_| ())
+}
+
+
+/// Validates `alphabet` like `validate_alphabet` and rejects whitespace chars.
+///
+/// Whitespace digits would be lost when sequences get trimmed.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::validate_alphabet_no_whitespace;
+/// use numsys::Error;
+///
+/// assert_eq!(validate_alphabet_no_whitespace(&['0', '1']), Ok(()));
+///
+/// let msg = "Char \' \' at index 1 is whitespace in: [\'0\', \' \']".to_string();
+/// assert_eq!(validate_alphabet_no_whitespace(&['0', ' ']), Err(Error::WhitespaceChar(msg)));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::WhitespaceChar` when `alphabet` includes a whitespace char
+pub fn validate_alphabet_no_whitespace(alphabet: &[char]) -> Result<(), Error> {
+    validate_alphabet(alphabet)?;
+    match alphabet.iter().position(|glyph| glyph.is_whitespace()) {
+        Some(idx) => Err(Error::WhitespaceChar(format!(
+            "Char {:?} at index {} is whitespace in: {:?}",
+            alphabet[idx],
+            idx,
+            alphabet
+        ))),
+        None => Ok(()),
+    }
+}
+
+
+/// Tells whether alphabets `a` and `b` define the same numbering, i.e. they are identical char by char.
+///
+/// An invalid alphabet (empty, or with duplicated chars) defines no
+/// numbering, so it's equivalent to none.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::alphabets_equivalent;
+///
+/// assert!(alphabets_equivalent(&['0', '1'], &['0', '1']));
+/// assert!(!alphabets_equivalent(&['0', '1'], &['1', '0']));
+/// assert!(!alphabets_equivalent(&['0', '0'], &['0', '0']));
+/// ```
+pub fn alphabets_equivalent(a: &[char], b: &[char]) -> bool {
+    validate_alphabet(a).is_ok() && a == b
+}
+
+
+/// Tells whether alphabets `a` and `b` have the same chars, in any order.
+///
+/// Like `alphabets_equivalent` an invalid alphabet has the same set as none.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::alphabets_same_set;
+///
+/// assert!(alphabets_same_set(&['0', '1'], &['1', '0']));
+/// assert!(!alphabets_same_set(&['0', '1'], &['0', '1', '2']));
+/// assert!(!alphabets_same_set(&[], &[]));
+/// ```
+pub fn alphabets_same_set(a: &[char], b: &[char]) -> bool {
+    match (validate_alphabet(a), index_chars(b)) {
+        // chars are unique, so equal lengths and inclusion mean the same set
+        (Ok(()), Ok(b_chars)) => a.len() == b.len() && a.iter().all(|glyph| b_chars.contains_key(glyph)),
+        _ => false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {recode, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn reordered_alphabets_are_not_equivalent() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let mut reversed = hex.to_vec();
+        reversed.reverse();
+        assert!(alphabets_equivalent(hex, hex));
+        assert!(!alphabets_equivalent(hex, &reversed));
+        assert!(alphabets_same_set(hex, &reversed));
+        assert!(!alphabets_equivalent(hex, &DIGITS_UPPER_AZ[..17]));
+        assert!(!alphabets_same_set(hex, &DIGITS_UPPER_AZ[..17]));
+        assert!(!alphabets_same_set(&DIGITS_UPPER_AZ[1..17], hex));
+        // invalid alphabets are equivalent to none, themselves included
+        assert!(!alphabets_equivalent(&['a', 'a'], &['a', 'a']));
+        assert!(!alphabets_same_set(&['a', 'a'], &['a', 'a']));
+        assert!(!alphabets_same_set(&['a', 'b'], &['a', 'b', 'a']));
+        assert!(!alphabets_same_set(hex, &[]));
+        assert!(!alphabets_equivalent(&[], &[]));
+    }
+
+    #[test]
+    fn values_follow_declaration_order() {
+        let alphabet = AlphabetBuilder::new()
+            .chars("xy")
+            .range('0'..='2')
+            .range('A'..='B')
+            .build()
+            .unwrap();
+        assert_eq!(alphabet.chars(), &['x', 'y', '0', '1', '2', 'A', 'B']);
+        let reversed = AlphabetBuilder::new().range('A'..='B').range('0'..='2').chars("xy").build();
+        assert_eq!(reversed.unwrap().chars(), &['A', 'B', '0', '1', '2', 'x', 'y']);
+    }
+
+    #[test]
+    fn exclude_keeps_relative_order() {
+        let alphabet = AlphabetBuilder::new()
+            .exclude("0O")
+            .range('0'..='9')
+            .range('A'..='Z')
+            .exclude("1IL")
+            .build()
+            .unwrap();
+        assert_eq!(alphabet.len(), 36 - 5);
+        assert_eq!(alphabet.glyph(0), Some('2'));
+        assert_eq!(alphabet.value_of('A'), Some(8));
+        assert_eq!(alphabet.value_of('O'), None);
+    }
+
+    #[test]
+    fn overlapping_ranges_name_both_pieces() {
+        let result = AlphabetBuilder::new().range('a'..='f').range('0'..='9').range('d'..='k').build();
+        let msg = "Chars MUST be unique, duplicated: 'd' in 'a'..='f' (piece 0) and 'd'..='k' (piece 2)";
+        assert_eq!(result, Err(Error::MultipleChar(msg.to_string())));
+    }
+
+    #[test]
+    fn single_char_range_and_empty_result() {
+        let alphabet = AlphabetBuilder::new().range('q'..='q').build().unwrap();
+        assert_eq!(alphabet.chars(), &['q']);
+        assert_eq!(AlphabetBuilder::new().build(), Err(Error::DictEmpty));
+        assert_eq!(AlphabetBuilder::new().chars("ab").exclude("ba").build(), Err(Error::DictEmpty));
+    }
+
+    #[test]
+    fn presets_are_built_declaratively() {
+        assert_eq!(&::DIGITS_UPPER_AZ[..], AlphabetBuilder::new().range('0'..='9').range('A'..='Z')
+            .build().unwrap().chars());
+        assert_eq!(::DIGITS_UPPER_LOWER_AZ.len(), 62);
+    }
+
+    #[test]
+    fn noncharacters_are_rejected_unless_allowed() {
+        for glyph in ['\u{FDD0}', '\u{FDEF}', '\u{FFFE}', '\u{FFFF}', '\u{1FFFE}', '\u{10FFFF}'].iter() {
+            match Alphabet::new(&['a', *glyph]) {
+                Err(Error::ForbiddenCodePoint(msg)) => assert!(msg.ends_with("MUST NOT be a noncharacter"), "{}", msg),
+                other => panic!("unexpected {:?} for {:?}", other, glyph),
+            }
+        }
+        let result = AlphabetBuilder::new().range('\u{FDC0}'..='\u{FDFF}').build();
+        let msg = "Char '\\u{fdd0}' (U+FDD0) at index 16 MUST NOT be a noncharacter".to_string();
+        assert_eq!(result, Err(Error::ForbiddenCodePoint(msg)));
+        let allowed = AlphabetBuilder::new().range('\u{FDC0}'..='\u{FDFF}').allow_noncharacters().build();
+        assert_eq!(allowed.unwrap().len(), 64);
+        // excluded chars aren't checked
+        let excluded = AlphabetBuilder::new().range('\u{FDC0}'..='\u{FDFF}').exclude(&('\u{FDD0}'..='\u{FDEF}')
+            .collect::<String>()).build();
+        assert_eq!(excluded.unwrap().len(), 32);
+        assert!(AlphabetBuilder::new().chars("\u{FFFF}").allow_private_use().build().is_err());
+        assert!(Alphabet::new_case_insensitive(&['a', '\u{FFFE}']).is_err());
+    }
+
+    #[test]
+    fn private_use_is_rejected_unless_allowed() {
+        for glyph in ['\u{E000}', '\u{F8FF}', '\u{F0000}', '\u{FFFFD}', '\u{100000}', '\u{10FFFD}'].iter() {
+            match Alphabet::new(&['a', *glyph]) {
+                Err(Error::ForbiddenCodePoint(msg)) => assert!(msg.ends_with("private use code point"), "{}", msg),
+                other => panic!("unexpected {:?} for {:?}", other, glyph),
+            }
+            assert!(AlphabetBuilder::new().chars("a").chars(&glyph.to_string()).allow_private_use().build().is_ok());
+        }
+        // planes 15 and 16 end with noncharacters, not private use
+        let plane15 = AlphabetBuilder::new().range('\u{FFFF0}'..='\u{FFFFF}').allow_private_use().build();
+        match plane15 {
+            Err(Error::ForbiddenCodePoint(msg)) => assert!(msg.contains("U+FFFFE"), "{}", msg),
+            other => panic!("unexpected {:?}", other),
+        }
+        let both = AlphabetBuilder::new().range('\u{FFFF0}'..='\u{FFFFF}').allow_private_use().allow_noncharacters();
+        assert_eq!(both.build().unwrap().len(), 16);
+        // neighbours of the forbidden ranges
+        assert!(Alphabet::new(&['\u{D7FF}', '\u{F900}', '\u{FDCF}', '\u{FDF0}', '\u{FFFD}']).is_ok());
+    }
+
+    #[test]
+    fn presets_have_no_forbidden_code_points() {
+        for preset in [&::DIGITS[..], &::DIGITS_UPPER_AZ, &::DIGITS_UPPER_LOWER_AZ, &::LOWER_AZ, &::UPPER_AZ].iter() {
+            assert!(Alphabet::new(preset).is_ok());
+        }
+        assert!(Alphabet::new(&['★', '☆', 'ż', '\u{1F005}', '\u{FFFD}']).is_ok());
+    }
+
+    #[test]
+    fn whitespace_alphabets_are_rejected() {
+        assert_eq!(validate_alphabet_no_whitespace(&::DIGITS_UPPER_LOWER_AZ), Ok(()));
+        assert_eq!(validate_alphabet_no_whitespace(&['★', '☆']), Ok(()));
+        for glyph in [' ', '\t', '\n', '\u{a0}', '\u{3000}'].iter() {
+            match validate_alphabet_no_whitespace(&['a', *glyph]) {
+                Err(Error::WhitespaceChar(_)) => (),
+                other => panic!("unexpected {:?} for {:?}", other, glyph),
+            }
+        }
+        assert_eq!(validate_alphabet_no_whitespace(&[]), Err(Error::DictEmpty));
+    }
+
+    #[test]
+    fn superset_keeps_chars_but_not_values() {
+        let old = Alphabet::new(&::DIGITS[..]).unwrap();
+        let new = Alphabet::new(&::DIGITS_UPPER_AZ[..]).unwrap();
+        let report = old.compatibility(&new);
+        assert_eq!(report.remapped, vec![]);
+        assert_eq!(report.missing, vec![]);
+        assert_eq!(report.added.len(), 26);
+        // "10" is 10 with the old alphabet, 36 with the new one
+        assert!(!report.safe);
+        // same base, a glyph just retires
+        let old = Alphabet::new(&['0', '1', '2', 'O']).unwrap();
+        let new = Alphabet::new(&['0', '1', '2', '3']).unwrap();
+        assert!(old.compatibility(&new).safe);
+        assert!(old.compatibility(&old).safe);
+    }
+
+    #[test]
+    fn glyph_swap_migration() {
+        // retiring 'O' for '3' is safe: codes of the old alphabet keep their value or fail
+        let old = Alphabet::new(&['0', '1', '2', 'O']).unwrap();
+        let new = Alphabet::new(&['0', '1', '2', '3']).unwrap();
+        assert!(old.compatibility(&new).safe);
+        for value in 0..256 {
+            let code = ::dec2seq(value, old.chars()).unwrap();
+            match ::seq2dec(&code, new.chars()) {
+                Ok(decoded) => assert_eq!(decoded, value, "{}", code),
+                Err(Error::MissingChar(_)) => assert!(code.contains('O'), "{}", code),
+                Err(other) => panic!("{} gave {:?}", code, other),
+            }
+            assert_eq!(::seq2dec(recode(&code, &old, &new).unwrap(), new.chars()), Ok(value));
+        }
+    }
+
+    #[test]
+    fn prefix_superset_migration() {
+        // the old alphabet is a strict prefix of the new one, every char keeps its value
+        let old = Alphabet::new(&['0', '1']).unwrap();
+        let new = Alphabet::new(&['0', '1', '2', '3']).unwrap();
+        let report = old.compatibility(&new);
+        assert_eq!(report.remapped, vec![]);
+        assert_eq!(report.missing, vec![]);
+        assert_eq!(report.added, vec!['2', '3']);
+        // the base changes, so old codes of several digits need recoding
+        assert!(!report.safe);
+        assert_eq!(::seq2dec("1", new.chars()), Ok(1));
+        assert_eq!(::seq2dec("10", new.chars()), Ok(4));
+        for value in 0..256 {
+            let code = ::dec2seq(value, old.chars()).unwrap();
+            let migrated = recode(&code, &old, &new).unwrap();
+            assert_eq!(::seq2dec(&migrated, new.chars()), Ok(value), "{}", code);
+            assert_eq!(recode(&migrated, &new, &old), Ok(code));
+        }
+        // decimal digits are a prefix of base 36 ones too
+        let old = Alphabet::new(&::DIGITS[..]).unwrap();
+        let new = Alphabet::new(&::DIGITS_UPPER_AZ[..]).unwrap();
+        let report = old.compatibility(&new);
+        assert_eq!((report.remapped, report.missing), (vec![], vec![]));
+        assert!(!report.safe);
+        assert_eq!(::seq2dec("10", new.chars()), Ok(36));
+        for value in [0, 9, 10, 35, 36, 1_000_000, usize::MAX].iter() {
+            let code = ::dec2seq(*value, old.chars()).unwrap();
+            let migrated = recode(&code, &old, &new).unwrap();
+            assert_eq!(::seq2dec(&migrated, new.chars()), Ok(*value), "{}", code);
+            assert_eq!(recode(&migrated, &new, &old), Ok(code));
+        }
+    }
+
+    #[test]
+    fn value_remapping_is_unsafe() {
+        let old = Alphabet::new(&['a', 'b', 'c', 'd']).unwrap();
+        let new = Alphabet::new(&['a', 'c', 'b', 'd']).unwrap();
+        let report = old.compatibility(&new);
+        assert_eq!(report.remapped, vec![('b', 1, 2), ('c', 2, 1)]);
+        assert!(!report.safe);
+    }
+
+    #[test]
+    fn disjoint_alphabets_are_safe() {
+        let old = Alphabet::new(&['0', '1']).unwrap();
+        let new = Alphabet::new(&['a', 'b', 'c']).unwrap();
+        let report = old.compatibility(&new);
+        assert_eq!(report.missing, vec!['0', '1']);
+        assert_eq!(report.added, vec!['a', 'b', 'c']);
+        assert!(report.safe);
+        // sharing only the zero glyph is still safe, zeros are zero in any base
+        let new = Alphabet::new(&['0', 'x', 'y']).unwrap();
+        assert!(old.compatibility(&new).safe);
+    }
+
+    #[test]
+    fn case_folding_of_base36_and_base62() {
+        let base36 = Alphabet::new(&::DIGITS_UPPER_AZ[..]).unwrap();
+        assert_eq!(base36.case_fold_safety(), CaseFoldSafety::Safe);
+        let folded = Alphabet::new_case_insensitive(&::DIGITS_UPPER_AZ[..]).unwrap();
+        assert_eq!(folded.value_of('z'), Some(35));
+        assert_eq!(folded.value_of('Z'), Some(35));
+        let base62 = Alphabet::new(&::DIGITS_UPPER_LOWER_AZ[..]).unwrap();
+        match base62.case_fold_safety() {
+            CaseFoldSafety::Unsafe(pairs) => {
+                assert_eq!(pairs.len(), 26);
+                assert_eq!(pairs[0], ('A', 'a'));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match Alphabet::new_case_insensitive(&::DIGITS_UPPER_LOWER_AZ[..]) {
+            Err(Error::CaseFoldCollision(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn case_folding_of_mixed_unicode() {
+        // only some glyphs have case
+        let alphabet = Alphabet::new_case_insensitive(&['★', 'ż', '7', 'Σ', '中']).unwrap();
+        assert_eq!(alphabet.value_of('Ż'), Some(1));
+        assert_eq!(alphabet.value_of('σ'), Some(3));
+        assert_eq!(alphabet.value_of('★'), Some(0));
+        // 'ſ' (long s) upper cases to 'S', like 's'
+        let alphabet = Alphabet::new(&['s', 'ſ']).unwrap();
+        assert_eq!(alphabet.case_fold_safety(), CaseFoldSafety::Unsafe(vec![('s', 'ſ')]));
+        // Kelvin sign lower cases to 'k'
+        assert!(Alphabet::new_case_insensitive(&['k', '\u{212A}']).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use digits::missing_char;
+use {checked_pow_base, check_base, digit_count, index_chars, switch_dec_base, Error, DIGITS_UPPER_AZ};
+
+
+/// Counts how many times each glyph appears in `decimal` converted to `base`.
+///
+/// Only glyphs present in the representation are included.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::char_distribution;
+///
+/// let dist = char_distribution(10, 2).unwrap();
+/// assert_eq!(dist[&'1'], 2);
+/// assert_eq!(dist[&'0'], 2);
+/// assert_eq!(char_distribution(255, 16).unwrap().get(&'0'), None);
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+pub fn char_distribution(decimal: usize, base: usize) -> Result<HashMap<char, usize>, Error> {
+    let mut distribution = HashMap::new();
+    for glyph in switch_dec_base(decimal, base)?.chars() {
+        *distribution.entry(glyph).or_insert(0) += 1;
+    }
+    Ok(distribution)
+}
+
+
+/// Returns most significant first positions of `glyph` in `decimal` converted to `base`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::glyph_positions;
+///
+/// // 0b1011_0010
+/// assert_eq!(glyph_positions(0xB2, 2, '0'), Ok(vec![1, 4, 5, 7]));
+/// assert_eq!(glyph_positions(255, 16, '0'), Ok(vec![]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::MissingChar` when `glyph` isn't a digit of `base`
+///
+/// ```
+/// use numsys::glyph_positions;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(glyph_positions(5, 2, '2'), Err(Error::MissingChar(msg)));
+/// ```
+pub fn glyph_positions(decimal: usize, base: usize, glyph: char) -> Result<Vec<usize>, Error> {
+    let sequence = switch_dec_base(decimal, base)?;
+    let alphabet = &DIGITS_UPPER_AZ[..base];
+    if !alphabet.contains(&glyph) {
+        return Err(missing_char(glyph, None, alphabet));
+    }
+    Ok(sequence
+        .chars()
+        .enumerate()
+        .filter(|(_, g)| *g == glyph)
+        .map(|(position, _)| position)
+        .collect())
+}
+
+
+/// Counts occurrences of each digit value of `sequence` using `char2val` translation.
+///
+/// Element at index `i` counts digit of value `i`, so the result length is
+/// always the base (`char2val` length).
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digit_histogram;
+///
+/// assert_eq!(digit_histogram("CABBA", &['A', 'B', 'C', 'D']), Ok(vec![2, 2, 1, 0]));
+/// assert_eq!(digit_histogram("", &['0', '1']), Ok(vec![0, 0]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// ```
+/// use numsys::digit_histogram;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' at position 1 not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(digit_histogram("12", &['0', '1']), Err(Error::MissingChar(msg)));
+/// ```
+pub fn digit_histogram(sequence: &str, char2val: &[char]) -> Result<Vec<usize>, Error> {
+    digit_histogram_all(&[sequence], char2val)
+}
+
+
+/// Counts occurrences of each digit value of all `sequences` using `char2val` translation.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digit_histogram_all;
+///
+/// assert_eq!(digit_histogram_all(&["10", "11", "0"], &['0', '1']), Ok(vec![2, 3]));
+/// ```
+///
+/// # Errors
+///
+/// Same as `digit_histogram`, `Error::MissingChar` also names the sequence index.
+pub fn digit_histogram_all<S: AsRef<str>>(sequences: &[S], char2val: &[char]) -> Result<Vec<usize>, Error> {
+    let map = index_chars(char2val)?;
+    let mut histogram = vec![0; char2val.len()];
+    for (index, sequence) in sequences.iter().enumerate() {
+        for (position, glyph) in sequence.as_ref().chars().enumerate() {
+            let digit = map.get(&glyph).ok_or_else(|| {
+                let at = if sequences.len() == 1 {
+                    format!("position {}", position)
+                } else {
+                    format!("position {} of sequence {}", position, index)
+                };
+                Error::MissingChar(format!("Char {:?} at {} not found in: {:?}", glyph, at, char2val))
+            })?;
+            histogram[*digit] += 1;
+        }
+    }
+    Ok(histogram)
+}
+
+
+/// Counts occurrences of each digit value of `decimal` converted to `base`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digit_histogram_of;
+///
+/// assert_eq!(digit_histogram_of(10, 2), Ok(vec![2, 2]));
+/// assert_eq!(digit_histogram_of(255, 16).unwrap()[15], 2);
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+pub fn digit_histogram_of(decimal: usize, base: usize) -> Result<Vec<usize>, Error> {
+    digit_histogram(&switch_dec_base(decimal, base)?, &DIGITS_UPPER_AZ[..base])
+}
+
+
+/// Counts leading (most significant) digit values of `values` converted to `base`.
+///
+/// Useful for Benford's law screening. Leading digit of `0` is `0`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::leading_digit_distribution;
+///
+/// let dist = leading_digit_distribution(&[1, 12, 150, 2, 9, 0], 10).unwrap();
+/// assert_eq!(dist, vec![1, 3, 1, 0, 0, 0, 0, 0, 0, 1]);
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+pub fn leading_digit_distribution(values: &[usize], base: usize) -> Result<Vec<usize>, Error> {
+    check_base(base)?;
+    let mut distribution = vec![0; base];
+    for value in values {
+        let mut leading = *value;
+        while leading >= base {
+            leading /= base;
+        }
+        distribution[leading] += 1;
+    }
+    Ok(distribution)
+}
+
+
+/// Counts how many values of `range` have each representation length in `base`.
+///
+/// Computed from `digit_count` boundaries, values aren't converted one by
+/// one, so huge ranges are fine.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::length_histogram;
+///
+/// let histogram = length_histogram(0..=255, 16).unwrap();
+/// assert_eq!(histogram[&1], 16);
+/// assert_eq!(histogram[&2], 240);
+/// assert_eq!(histogram.len(), 2);
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn length_histogram(range: RangeInclusive<usize>, base: usize) -> Result<HashMap<usize, usize>, Error> {
+    let (start, end) = (*range.start(), *range.end());
+    let first = digit_count(start, base)?;
+    let mut histogram = HashMap::new();
+    if start > end {
+        return Ok(histogram);
+    }
+    let last = digit_count(end, base)?;
+    let mut low = start;
+    for length in first..=last {
+        // the biggest value of `length` digits, or `end` if it's sooner
+        let high = checked_pow_base(base, length as u32).map_or(end, |power| end.min(power - 1));
+        histogram.insert(length, high - low + 1);
+        low = high.wrapping_add(1);
+    }
+    Ok(histogram)
+}
+
+
+/// Detects the smallest base of `DIGITS_UPPER_AZ` consistent with all `samples`.
+///
+/// The base is the highest glyph value across `samples` plus one, but at
+/// least 2. Letters are case insensitive (as `from_str_radix`), so `ff` and
+/// `FF` both need base 16. Returns `None` when a glyph isn't from `0-9`, `A-Z`
+/// or `a-z`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::detect_corpus_base;
+///
+/// assert_eq!(detect_corpus_base(&["101", "11"]), Some(2));
+/// assert_eq!(detect_corpus_base(&["1f", "C0", "7"]), Some(16));
+/// assert_eq!(detect_corpus_base(&["12", "3-4"]), None);
+/// ```
+pub fn detect_corpus_base(samples: &[&str]) -> Option<usize> {
+    let mut base = 2;
+    for sample in samples {
+        for glyph in sample.chars() {
+            base = base.max(glyph.to_digit(36)? as usize + 1);
+        }
+    }
+    Some(base)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_positions_of_binary_zeros() {
+        // 0b1000_0110_0001
+        assert_eq!(glyph_positions(0x861, 2, '0'), Ok(vec![1, 2, 3, 4, 7, 8, 9, 10]));
+        assert_eq!(glyph_positions(0x861, 2, '1'), Ok(vec![0, 5, 6, 11]));
+        assert_eq!(glyph_positions(0, 2, '0'), Ok(vec![0]));
+        assert!(glyph_positions(0x861, 2, 'A').is_err());
+        assert!(glyph_positions(0x861, 16, 'a').is_err());
+    }
+
+    #[test]
+    fn char_distribution_counts_binary_digits() {
+        // 0b1011_0111
+        let dist = char_distribution(183, 2).unwrap();
+        assert_eq!(dist.len(), 2);
+        assert_eq!(dist[&'1'], 6);
+        assert_eq!(dist[&'0'], 2);
+    }
+
+    #[test]
+    fn char_distribution_of_zero() {
+        let dist = char_distribution(0, 36).unwrap();
+        assert_eq!(dist.len(), 1);
+        assert_eq!(dist[&'0'], 1);
+    }
+
+    #[test]
+    fn digit_histogram_hand_counted() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let histogram = digit_histogram("DEADBEEF", hex).unwrap();
+        assert_eq!(histogram.len(), 16);
+        assert_eq!(histogram[0xD], 2);
+        assert_eq!(histogram[0xE], 3);
+        assert_eq!(histogram[0xA], 1);
+        assert_eq!(histogram[0xB], 1);
+        assert_eq!(histogram[0xF], 1);
+        assert_eq!(histogram[0], 0);
+    }
+
+    #[test]
+    fn digit_histogram_sum_is_digit_count() {
+        for base in 2..37 {
+            for value in (0..100_000).step_by(997) {
+                let seq = switch_dec_base(value, base).unwrap();
+                let histogram = digit_histogram_of(value, base).unwrap();
+                assert_eq!(histogram.len(), base);
+                assert_eq!(histogram.iter().sum::<usize>(), seq.chars().count());
+            }
+        }
+    }
+
+    #[test]
+    fn digit_histogram_all_reports_sequence_index() {
+        let msg = "Char 'x' at position 0 of sequence 1 not found in: ['0', '1']".to_string();
+        let result = digit_histogram_all(&["01", "x"], &['0', '1']);
+        assert_eq!(result, Err(Error::MissingChar(msg)));
+    }
+
+    #[test]
+    fn leading_digit_distribution_in_base_2() {
+        // every nonzero value starts with 1 in binary
+        let values: Vec<usize> = (1..100).collect();
+        assert_eq!(leading_digit_distribution(&values, 2), Ok(vec![0, 99]));
+    }
+
+    #[test]
+    fn length_histogram_matches_conversion() {
+        for base in 2..37 {
+            for &(start, end) in [(0, 0), (0, 255), (7, 1000), (base, base * base), (100, 99)].iter() {
+                let mut expected = HashMap::new();
+                for value in start..=end {
+                    *expected.entry(switch_dec_base(value, base).unwrap().len()).or_insert(0) += 1;
+                }
+                assert_eq!(length_histogram(start..=end, base), Ok(expected), "{}..={} base {}", start, end, base);
+            }
+        }
+        let histogram = length_histogram(0..=usize::MAX, 2).unwrap();
+        assert_eq!(histogram.len(), 64);
+        assert_eq!(histogram[&1], 2);
+        assert_eq!(histogram[&64], 1 << 63);
+    }
+
+    #[test]
+    fn detect_corpus_base_of_hex_dump() {
+        let samples = ["deadbeef", "0", "19af", "c0ffee", "42"];
+        assert_eq!(detect_corpus_base(&samples), Some(16));
+        // one sample drives the whole corpus
+        assert_eq!(detect_corpus_base(&["12", "9", "z"]), Some(36));
+        assert_eq!(detect_corpus_base(&["0", ""]), Some(2));
+        assert_eq!(detect_corpus_base(&["ff", "f f"]), None);
+        for base in 2..37 {
+            let samples: Vec<String> = (0..base * 3).map(|value| switch_dec_base(value, base).unwrap()).collect();
+            let samples: Vec<&str> = samples.iter().map(|sample| sample.as_str()).collect();
+            assert_eq!(detect_corpus_base(&samples), Some(base));
+        }
+    }
+}
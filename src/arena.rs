@@ -0,0 +1,241 @@
+use {positional_alphabet, Error};
+
+
+/// Bytes of the first chunk of `EncodeArena::new`.
+const DEFAULT_CHUNK: usize = 1024;
+
+
+/// Growable arena keeping encodings of `encode_in` together, until `reset`.
+///
+/// Bytes live in chunks which never reallocate: once a chunk can't fit an
+/// encoding, a new one at least twice as big is added. So encodings never
+/// move, and `reset` keeps all the chunks for the next frame, which then
+/// encodes without allocating as long as it fits the capacity.
+///
+/// The `&str` of `encode_in` borrows the arena mutably, all encodings are
+/// available together afterwards with `get` and `iter`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{encode_in, EncodeArena};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let mut arena = EncodeArena::with_capacity(64);
+/// assert_eq!(encode_in(&mut arena, 255, &hex), Ok("FF"));
+/// assert_eq!(encode_in(&mut arena, 4096, &hex), Ok("1000"));
+/// assert_eq!(arena.iter().collect::<Vec<_>>(), vec!["FF", "1000"]);
+/// assert_eq!(arena.len(), 6);
+///
+/// arena.reset();
+/// assert!(arena.is_empty());
+/// assert_eq!(arena.capacity(), 64);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncodeArena {
+    chunks: Vec<String>,
+    // chunk in use, the ones before it are full
+    current: usize,
+    // chunk, start and end of each encoding
+    spans: Vec<(usize, usize, usize)>,
+    // the last alphabet validated by `encode_in`
+    alphabet: Vec<char>,
+}
+
+impl Default for EncodeArena {
+    fn default() -> EncodeArena {
+        EncodeArena::new()
+    }
+}
+
+impl EncodeArena {
+    /// Creates arena with a first chunk of 1024 bytes.
+    pub fn new() -> EncodeArena {
+        EncodeArena::with_capacity(DEFAULT_CHUNK)
+    }
+
+    /// Creates arena with a first chunk of `bytes` (at least 1).
+    pub fn with_capacity(bytes: usize) -> EncodeArena {
+        EncodeArena {
+            chunks: vec![String::with_capacity(bytes.max(1))],
+            current: 0,
+            spans: Vec::new(),
+            alphabet: Vec::new(),
+        }
+    }
+
+    /// Forgets all encodings, keeping the chunks for reuse.
+    pub fn reset(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.clear();
+        }
+        self.current = 0;
+        self.spans.clear();
+    }
+
+    /// Returns count of bytes of all encodings.
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    /// Tells whether there are no encodings with a byte or more.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns count of bytes the arena holds without allocating.
+    pub fn capacity(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.capacity()).sum()
+    }
+
+    /// Returns count of encodings since the last `reset`.
+    pub fn count(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns encoding number `index` (in order of `encode_in` calls).
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.spans.get(index).map(|&(chunk, start, end)| &self.chunks[chunk][start..end])
+    }
+
+    /// Returns iterator over all encodings, in order of `encode_in` calls.
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.spans.iter().map(move |&(chunk, start, end)| &self.chunks[chunk][start..end])
+    }
+
+    /// Returns index of a chunk which fits `bytes` more, adding one if needed.
+    fn chunk_for(&mut self, bytes: usize) -> usize {
+        while self.current < self.chunks.len() {
+            let chunk = &self.chunks[self.current];
+            if chunk.capacity() - chunk.len() >= bytes {
+                return self.current;
+            }
+            self.current += 1;
+        }
+        let last = self.chunks.last().map_or(DEFAULT_CHUNK, |chunk| chunk.capacity());
+        self.chunks.push(String::with_capacity((last * 2).max(bytes)));
+        self.current
+    }
+}
+
+
+/// Converts `decimal` using `char2val` translation into `arena`, see `EncodeArena`.
+///
+/// Sequences are the same as `dec2seq` ones, so `0` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{encode_in, EncodeArena};
+///
+/// let mut arena = EncodeArena::new();
+/// assert_eq!(encode_in(&mut arena, 10, &['★', '☆']), Ok("☆★☆★"));
+/// assert_eq!(arena.len(), 12);
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+pub fn encode_in<'a>(arena: &'a mut EncodeArena, decimal: usize, char2val: &[char]) -> Result<&'a str, Error> {
+    if arena.alphabet[..] != *char2val {
+        positional_alphabet(char2val)?;
+        arena.alphabet.clear();
+        arena.alphabet.extend_from_slice(char2val);
+    }
+    let base = char2val.len();
+    // digits of usize in base 2 or higher, least significant first
+    let mut digits = [0; 64];
+    let mut count = 0;
+    let mut rest = decimal;
+    while rest != 0 {
+        digits[count] = rest % base;
+        rest /= base;
+        count += 1;
+    }
+    let bytes = digits[..count].iter().map(|digit| char2val[*digit].len_utf8()).sum();
+    let chunk = arena.chunk_for(bytes);
+    let start = arena.chunks[chunk].len();
+    arena.chunks[chunk].extend(digits[..count].iter().rev().map(|digit| char2val[*digit]));
+    arena.spans.push((chunk, start, start + bytes));
+    Ok(&arena.chunks[chunk][start..])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::allocations;
+    use {dec2seq, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn encodings_never_move() {
+        let wide = ['★', '☆', 'ż', '\u{1F005}', 'a'];
+        let mut arena = EncodeArena::with_capacity(16);
+        let mut addresses = Vec::new();
+        for value in 0..10_000 {
+            let alphabet = if value % 3 == 0 { &wide[..] } else { &DIGITS_UPPER_AZ[..] };
+            let encoded = encode_in(&mut arena, value * 7919, alphabet).unwrap();
+            assert_eq!(encoded, dec2seq(value * 7919, alphabet).unwrap());
+            addresses.push(encoded.as_ptr() as usize);
+        }
+        assert!(arena.chunks.len() > 1);
+        assert_eq!(arena.count(), 10_000);
+        for (index, address) in addresses.iter().enumerate() {
+            let alphabet = if index % 3 == 0 { &wide[..] } else { &DIGITS_UPPER_AZ[..] };
+            let encoded = arena.get(index).unwrap();
+            assert_eq!(encoded.as_ptr() as usize, *address);
+            assert_eq!(encoded, dec2seq(index * 7919, alphabet).unwrap());
+        }
+        assert_eq!(arena.iter().count(), 10_000);
+        assert_eq!(arena.get(10_000), None);
+    }
+
+    #[test]
+    fn encoding_bigger_than_chunk_grows_arena() {
+        let mut arena = EncodeArena::with_capacity(4);
+        let first = encode_in(&mut arena, 3, &['0', '1']).unwrap().as_ptr() as usize;
+        // 64 glyphs of 4 bytes
+        let big = encode_in(&mut arena, usize::MAX, &['\u{1F005}', '\u{1F006}']).unwrap();
+        assert_eq!(big.len(), 256);
+        let big = big.as_ptr() as usize;
+        assert!(arena.capacity() >= 4 + 256);
+        assert_eq!(arena.get(0).unwrap().as_ptr() as usize, first);
+        assert_eq!(arena.get(0), Some("11"));
+        // the big encoding filled a chunk of its own, the next one gets a new chunk
+        assert_eq!(encode_in(&mut arena, 1, &['0', '1']), Ok("1"));
+        assert_eq!(arena.chunks.len(), 3);
+        assert_eq!(arena.get(1).unwrap().as_ptr() as usize, big);
+        assert_eq!(arena.get(0).unwrap().as_ptr() as usize, first);
+        assert_eq!(arena.len(), 2 + 256 + 1);
+    }
+
+    #[test]
+    fn reset_reuses_capacity() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let mut arena = EncodeArena::with_capacity(8);
+        for value in 0..1000 {
+            encode_in(&mut arena, value, hex).unwrap();
+        }
+        let capacity = arena.capacity();
+        arena.reset();
+        assert_eq!((arena.len(), arena.count(), arena.capacity()), (0, 0, capacity));
+        let before = allocations();
+        for value in (0..1000).rev() {
+            encode_in(&mut arena, value, hex).unwrap();
+        }
+        assert_eq!(allocations(), before);
+        for (index, encoded) in arena.iter().enumerate() {
+            assert_eq!(encoded, dec2seq(999 - index, hex).unwrap());
+        }
+        assert_eq!(encode_in(&mut arena, 0, hex), Ok(""));
+        assert_eq!(encode_in(&mut arena, 1, &['a', 'a']).map(|_| ()), Err(::Error::MultipleChar(
+            "Chars MUST be unique, duplicated: 'a' in ['a', 'a']".to_string(),
+        )));
+        assert!(encode_in(&mut arena, 1, &[]).is_err());
+        assert!(encode_in(&mut arena, 1, &['a']).is_err());
+    }
+}
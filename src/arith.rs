@@ -0,0 +1,1007 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use digits::{missing_char, value_at};
+use {positional_alphabet, Alphabet, Error};
+
+
+/// Digit values of `sequence`, least significant first.
+fn digits_lsb(sequence: &str, char2val: &HashMap<char, usize>, alphabet: &[char]) -> Result<Vec<usize>, Error> {
+    let mut digits = sequence
+        .chars()
+        .enumerate()
+        .map(|(position, glyph)| value_at(glyph, Some(position), char2val, alphabet))
+        .collect::<Result<Vec<_>, _>>()?;
+    digits.reverse();
+    Ok(digits)
+}
+
+/// Renders least significant first `digits` skipping leading zeros.
+fn render_lsb(digits: &[usize], alphabet: &[char]) -> String {
+    match digits.iter().rposition(|d| *d != 0) {
+        Some(idx) => digits[..idx + 1].iter().rev().map(|d| alphabet[*d]).collect(),
+        None => alphabet[0].to_string(),
+    }
+}
+
+
+/// Adds sequences `a` and `b` digit by digit, using `alphabet` translation.
+///
+/// Returns the sum and the carry flags. Flag at index `i` tells whether adding
+/// the `i`-th digit (counted from the least significant one) carried over to
+/// the next position. No conversion to `usize` is made, so sequences of any
+/// length are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::add_in_base;
+///
+/// let dec: Vec<char> = "0123456789".chars().collect();
+/// assert_eq!(add_in_base("99", "1", &dec), Ok(("100".to_string(), vec![true, true])));
+/// assert_eq!(add_in_base("15", "21", &dec), Ok(("36".to_string(), vec![false, false])));
+/// assert_eq!(add_in_base("BB", "B", &['A', 'B']), Ok(("BAA".to_string(), vec![true, true])));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `alphabet` missing a char of `a` or `b`
+///
+/// ```
+/// use numsys::add_in_base;
+/// use numsys::Error;
+///
+/// let detailed_msg = "Char \'2\' at position 0 not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(add_in_base("1", "2", &['0', '1']), Err(Error::MissingChar(detailed_msg)));
+/// ```
+pub fn add_in_base(a: &str, b: &str, alphabet: &[char]) -> Result<(String, Vec<bool>), Error> {
+    let char2val = positional_alphabet(alphabet)?;
+    let base = alphabet.len();
+    let a = digits_lsb(a, &char2val, alphabet)?;
+    let b = digits_lsb(b, &char2val, alphabet)?;
+    let length = a.len().max(b.len());
+    let mut sum = Vec::with_capacity(length + 1);
+    let mut carries = Vec::with_capacity(length);
+    let mut carry = 0;
+    for idx in 0..length {
+        let total = a.get(idx).unwrap_or(&0) + b.get(idx).unwrap_or(&0) + carry;
+        sum.push(total % base);
+        carry = total / base;
+        carries.push(carry != 0);
+    }
+    if carry != 0 {
+        sum.push(carry);
+    }
+    Ok((render_lsb(&sum, alphabet), carries))
+}
+
+
+/// Subtracts sequence `b` from `a` digit by digit, using `alphabet` translation.
+///
+/// No conversion to `usize` is made, so sequences of any length are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::sub_in_base;
+///
+/// let dec: Vec<char> = "0123456789".chars().collect();
+/// assert_eq!(sub_in_base("100", "1", &dec), Ok("99".to_string()));
+/// assert_eq!(sub_in_base("42", "42", &dec), Ok("0".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `alphabet` missing a char of `a` or `b`
+///
+/// * Returns `Error::OutOfRange` when `b` is greater than `a`
+///
+/// ```
+/// use numsys::sub_in_base;
+/// use numsys::Error;
+///
+/// let msg = "Subtrahend \"11\" MUST NOT be greater than \"10\"".to_string();
+/// assert_eq!(sub_in_base("10", "11", &['0', '1']), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn sub_in_base(a: &str, b: &str, alphabet: &[char]) -> Result<String, Error> {
+    let char2val = positional_alphabet(alphabet)?;
+    let base = alphabet.len() as isize;
+    let minuend = digits_lsb(a, &char2val, alphabet)?;
+    let subtrahend = digits_lsb(b, &char2val, alphabet)?;
+    let length = minuend.len().max(subtrahend.len());
+    let mut difference = Vec::with_capacity(length);
+    let mut borrow = 0;
+    for idx in 0..length {
+        let mut digit = *minuend.get(idx).unwrap_or(&0) as isize
+            - *subtrahend.get(idx).unwrap_or(&0) as isize - borrow;
+        borrow = 0;
+        if digit < 0 {
+            digit += base;
+            borrow = 1;
+        }
+        difference.push(digit as usize);
+    }
+    if borrow != 0 {
+        return Err(Error::OutOfRange(
+            format!("Subtrahend {:?} MUST NOT be greater than {:?}", b, a),
+        ));
+    }
+    Ok(render_lsb(&difference, alphabet))
+}
+
+
+/// Multiplies sequences `a` and `b` digit by digit, using `alphabet` translation.
+///
+/// No conversion to `usize` is made, so sequences of any length are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::mul_in_base;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(mul_in_base("FF", "FF", &hex), Ok("FE01".to_string()));
+/// assert_eq!(mul_in_base("FF", "0", &hex), Ok("0".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `alphabet` missing a char of `a` or `b`
+pub fn mul_in_base(a: &str, b: &str, alphabet: &[char]) -> Result<String, Error> {
+    let char2val = positional_alphabet(alphabet)?;
+    let base = alphabet.len();
+    let a = digits_lsb(a, &char2val, alphabet)?;
+    let b = digits_lsb(b, &char2val, alphabet)?;
+    let mut product = vec![0; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let total = product[i + j] + x * y + carry;
+            product[i + j] = total % base;
+            carry = total / base;
+        }
+        let mut idx = i + b.len();
+        while carry != 0 {
+            let total = product[idx] + carry;
+            product[idx] = total % base;
+            carry = total / base;
+            idx += 1;
+        }
+    }
+    Ok(render_lsb(&product, alphabet))
+}
+
+
+/// Digit values of `operand` with `alphabet`, least significant first, validating the alphabet.
+fn operand_digits(operand: &str, alphabet: &[char]) -> Result<Vec<usize>, Error> {
+    digits_lsb(operand, &positional_alphabet(alphabet)?, alphabet)
+}
+
+/// Drops leading (most significant) zeros of least significant first `digits`.
+fn trim_lsb(digits: &mut Vec<usize>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+/// Converts least significant first `digits` of base `from` to base `to`.
+fn rebase_lsb(digits: &[usize], from: usize, to: usize) -> Vec<usize> {
+    let mut rebased: Vec<usize> = Vec::new();
+    for digit in digits.iter().rev() {
+        let mut carry = *digit;
+        for target in rebased.iter_mut() {
+            let total = *target * from + carry;
+            *target = total % to;
+            carry = total / to;
+        }
+        while carry != 0 {
+            rebased.push(carry % to);
+            carry /= to;
+        }
+    }
+    rebased
+}
+
+
+/// Tells whether sequence `a` of `alpha_a` and `b` of `alpha_b` denote the same number.
+///
+/// Leading zeros are ignored. No conversion to `usize` is made, when bases
+/// differ `a` is converted digit by digit to the base of `b`, so sequences of
+/// any length are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::eq_seq;
+///
+/// let dec: Vec<char> = "0123456789".chars().collect();
+/// let hex: Vec<char> = "0123456789abcdef".chars().collect();
+/// assert_eq!(eq_seq("255", &dec, "ff", &hex), Ok(true));
+/// assert_eq!(eq_seq("0255", &dec, "255", &dec), Ok(true));
+/// assert_eq!(eq_seq("256", &dec, "ff", &hex), Ok(false));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when an alphabet length is 0
+///
+/// * Returns `Error::BaseTooSmall` when an alphabet length is 1
+///
+/// * Returns `Error::MultipleChar` when an alphabet includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when an alphabet missing a char of its operand
+///
+/// ```
+/// use numsys::eq_seq;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' at position 1 not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(eq_seq("1", &['0', '1'], "12", &['0', '1']), Err(Error::MissingChar(msg)));
+/// ```
+pub fn eq_seq(a: &str, alpha_a: &[char], b: &str, alpha_b: &[char]) -> Result<bool, Error> {
+    let mut a = operand_digits(a, alpha_a)?;
+    let mut b = operand_digits(b, alpha_b)?;
+    if alpha_a.len() != alpha_b.len() {
+        a = rebase_lsb(&a, alpha_a.len(), alpha_b.len());
+    }
+    trim_lsb(&mut a);
+    trim_lsb(&mut b);
+    Ok(a == b)
+}
+
+
+/// Compares sequences `a` and `b` of `alphabet` by the numbers they denote.
+///
+/// Leading zeros are ignored and no conversion to `usize` is made, so
+/// sequences of any length are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::cmp_seq;
+/// use std::cmp::Ordering;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(cmp_seq("FF", "100", &hex), Ok(Ordering::Less));
+/// assert_eq!(cmp_seq("00FF", "FF", &hex), Ok(Ordering::Equal));
+/// let huge = "F".repeat(100);
+/// assert_eq!(cmp_seq(&huge, "1", &hex), Ok(Ordering::Greater));
+/// ```
+///
+/// # Errors
+///
+/// Same as `eq_seq`.
+pub fn cmp_seq(a: &str, b: &str, alphabet: &[char]) -> Result<Ordering, Error> {
+    let mut a = operand_digits(a, alphabet)?;
+    let mut b = operand_digits(b, alphabet)?;
+    trim_lsb(&mut a);
+    trim_lsb(&mut b);
+    Ok(a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev())))
+}
+
+
+/// Converts fixed-width `sequence` of `from` to the fixed width of `to` covering all inputs.
+///
+/// Target width is the minimal one whose capacity covers capacity of
+/// `from_width` digits of `from`, and the result is zero padded to it. So the
+/// mapping is injective (and order preserving) across the whole fixed-width
+/// domain, each input gets a distinct output of the same width. Digit
+/// string arithmetic is used, so widths of any size are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::convert_fixed_width;
+///
+/// let bin = ['0', '1'];
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// // 9 bits need 3 hex digits
+/// assert_eq!(convert_fixed_width("000000001", &bin, &hex, 9), Ok("001".to_string()));
+/// assert_eq!(convert_fixed_width("111111111", &bin, &hex, 9), Ok("1FF".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when an alphabet length is 0
+///
+/// * Returns `Error::BaseTooSmall` when an alphabet length is 1
+///
+/// * Returns `Error::MultipleChar` when an alphabet includes duplicated chars
+///
+/// * Returns `Error::LengthMismatch` when `sequence` hasn't exactly `from_width` chars
+///
+/// ```
+/// use numsys::convert_fixed_width;
+/// use numsys::Error;
+///
+/// let msg = "Sequence MUST have 4 chars, given 3".to_string();
+/// let result = convert_fixed_width("101", &['0', '1'], &['0', '1', '2'], 4);
+/// assert_eq!(result, Err(Error::LengthMismatch(msg)));
+/// ```
+///
+/// * Returns `Error::MissingChar` when `from` missing a char of `sequence`
+pub fn convert_fixed_width(sequence: &str, from: &[char], to: &[char], from_width: usize) -> Result<String, Error> {
+    positional_alphabet(to)?;
+    let digits = operand_digits(sequence, from)?;
+    if digits.len() != from_width {
+        return Err(Error::LengthMismatch(
+            format!("Sequence MUST have {} chars, given {}", from_width, digits.len()),
+        ));
+    }
+    // width of the biggest input in the target base
+    let mut max = rebase_lsb(&vec![from.len() - 1; from_width], from.len(), to.len());
+    trim_lsb(&mut max);
+    let mut converted = rebase_lsb(&digits, from.len(), to.len());
+    trim_lsb(&mut converted);
+    converted.resize(max.len(), 0);
+    Ok(converted.iter().rev().map(|digit| to[*digit]).collect())
+}
+
+
+/// Re-encodes `sequence` of alphabet `from` with alphabet `to`, preserving its value.
+///
+/// Alphabets of the same length only swap glyphs digit by digit, so the
+/// length (and leading zeros) are kept. Otherwise the value is converted
+/// with digit string arithmetic and leading zeros are skipped. Sequences of
+/// any length are supported.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{recode, Alphabet};
+///
+/// let dec = Alphabet::new(&['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']).unwrap();
+/// let letters = Alphabet::new(&['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j']).unwrap();
+/// let bin = Alphabet::new(&['0', '1']).unwrap();
+/// assert_eq!(recode("0042", &dec, &letters), Ok("aaec".to_string()));
+/// assert_eq!(recode("0042", &dec, &bin), Ok("101010".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when an alphabet length is 1
+///
+/// * Returns `Error::MissingChar` when `from` missing a char of `sequence`
+pub fn recode(sequence: &str, from: &Alphabet, to: &Alphabet) -> Result<String, Error> {
+    positional_alphabet(to.chars())?;
+    let digits = operand_digits(sequence, from.chars())?;
+    if from.len() == to.len() {
+        return Ok(digits.iter().rev().map(|digit| to.chars()[*digit]).collect());
+    }
+    if digits.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(render_lsb(&rebase_lsb(&digits, from.len(), to.len()), to.chars()))
+}
+
+
+/// Outcome of `BigConversion::step`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepResult {
+    /// Budget ran out, more steps are needed.
+    Progress,
+    /// Conversion finished, with the result of `recode`.
+    Done(String),
+    /// Conversion failed, with the error of `recode`.
+    Error(Error),
+}
+
+
+/// Resumable `recode`, converting huge sequences a bounded amount of work at a time.
+///
+/// Each `step` does at most `budget` units of work, so conversions can be
+/// interleaved with other tasks, e.g. yielding to an async executor between
+/// steps. A unit is one digit processed:
+///
+/// * a char of the input checked against `from`
+/// * a digit of the partial result of `to` updated (or appended) while an
+///   input digit is folded in, so input digits get costlier as the result grows
+///
+/// Rendering of the result isn't budgeted, it's done by the last step in
+/// linear time. The result (or the error) is the same `recode` gives.
+/// Dropping an unfinished conversion just frees its buffers.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{recode, Alphabet, BigConversion, StepResult};
+///
+/// let dec = Alphabet::new(&['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']).unwrap();
+/// let hex = Alphabet::new(&"0123456789ABCDEF".chars().collect::<Vec<_>>()).unwrap();
+/// let input = "9".repeat(200);
+/// let mut conversion = BigConversion::new(&input, &dec, &hex);
+/// let mut steps = 0;
+/// let result = loop {
+///     steps += 1;
+///     match conversion.step(1000) {
+///         // a good place for `yield_now().await`
+///         StepResult::Progress => continue,
+///         StepResult::Done(result) => break result,
+///         StepResult::Error(error) => panic!("{}", error),
+///     }
+/// };
+/// assert_eq!(Ok(result), recode(&input, &dec, &hex));
+/// assert!(steps > 1);
+/// assert!(conversion.work_done() <= steps * 1000);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BigConversion {
+    input: Vec<char>,
+    from: Alphabet,
+    to: Alphabet,
+    // digits of the input checked so far, most significant first
+    digits: Vec<usize>,
+    // the partial result, least significant first
+    rebased: Vec<usize>,
+    // index of the input digit being folded in
+    next: usize,
+    // digit of `rebased` and carry to continue folding the input digit with
+    folding: Option<(usize, usize)>,
+    work_done: usize,
+    outcome: Option<StepResult>,
+}
+
+impl BigConversion {
+    /// Prepares conversion of `input` of alphabet `from` to alphabet `to`, no work is done yet.
+    pub fn new(input: &str, from: &Alphabet, to: &Alphabet) -> BigConversion {
+        BigConversion {
+            input: input.chars().collect(),
+            from: from.clone(),
+            to: to.clone(),
+            digits: Vec::new(),
+            rebased: Vec::new(),
+            next: 0,
+            folding: None,
+            work_done: 0,
+            outcome: None,
+        }
+    }
+
+    /// Returns units of work done so far.
+    pub fn work_done(&self) -> usize {
+        self.work_done
+    }
+
+    /// Does at most `budget` units of work, see `BigConversion`.
+    ///
+    /// Once `Done` or `Error` is returned, further steps return it again.
+    pub fn step(&mut self, budget: usize) -> StepResult {
+        if self.outcome.is_none() {
+            let mut left = budget;
+            let outcome = self.run(&mut left);
+            self.work_done += budget - left;
+            self.outcome = outcome;
+        }
+        self.outcome.clone().unwrap_or(StepResult::Progress)
+    }
+
+    /// Continues the conversion spending `left` units, returns its outcome when it ends.
+    fn run(&mut self, left: &mut usize) -> Option<StepResult> {
+        if self.digits.is_empty() && self.work_done == 0 {
+            let alphabets = positional_alphabet(self.to.chars()).and_then(|_| positional_alphabet(self.from.chars()));
+            if let Err(error) = alphabets {
+                return Some(StepResult::Error(error));
+            }
+        }
+        while self.digits.len() < self.input.len() {
+            if *left == 0 {
+                return None;
+            }
+            let position = self.digits.len();
+            let glyph = self.input[position];
+            match self.from.value_of(glyph) {
+                Some(digit) => self.digits.push(digit),
+                None => return Some(StepResult::Error(missing_char(glyph, Some(position), self.from.chars()))),
+            }
+            *left -= 1;
+        }
+        let (from, to) = (self.from.len(), self.to.len());
+        if from == to {
+            return Some(StepResult::Done(self.digits.iter().map(|digit| self.to.chars()[*digit]).collect()));
+        }
+        if self.digits.is_empty() {
+            return Some(StepResult::Done(String::new()));
+        }
+        // `rebase_lsb`, resumable at any digit of the partial result
+        while self.next < self.digits.len() {
+            let (mut position, mut carry) = self.folding.unwrap_or((0, self.digits[self.next]));
+            while position < self.rebased.len() || carry != 0 {
+                if *left == 0 {
+                    self.folding = Some((position, carry));
+                    return None;
+                }
+                if position < self.rebased.len() {
+                    let total = self.rebased[position] * from + carry;
+                    self.rebased[position] = total % to;
+                    carry = total / to;
+                } else {
+                    self.rebased.push(carry % to);
+                    carry /= to;
+                }
+                position += 1;
+                *left -= 1;
+            }
+            self.folding = None;
+            self.next += 1;
+        }
+        Some(StepResult::Done(render_lsb(&self.rebased, self.to.chars())))
+    }
+}
+
+
+/// Value decoded by `seq2dec_auto`, promoted to big only when it exceeds `usize`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    /// Value fitting `usize`.
+    Small(usize),
+    /// Value too big for `usize`, as big-endian bytes without leading zeros.
+    Big(Vec<u8>),
+}
+
+
+/// Converts `sequence` to a `Number` using `alphabet` translation, never overflowing.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{seq2dec_auto, Number};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(seq2dec_auto("FF", &hex), Ok(Number::Small(255)));
+/// let big = seq2dec_auto("10000000000000000", &hex).unwrap();
+/// assert_eq!(big, Number::Big(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `alphabet` missing a char of `sequence`
+pub fn seq2dec_auto(sequence: &str, alphabet: &[char]) -> Result<Number, Error> {
+    let digits = operand_digits(sequence, alphabet)?;
+    let base = alphabet.len();
+    let small = digits
+        .iter()
+        .rev()
+        .try_fold(0usize, |dec, digit| dec.checked_mul(base).and_then(|d| d.checked_add(*digit)));
+    if let Some(value) = small {
+        return Ok(Number::Small(value));
+    }
+    let mut bytes = rebase_lsb(&digits, base, 256);
+    trim_lsb(&mut bytes);
+    Ok(Number::Big(bytes.iter().rev().map(|byte| *byte as u8).collect()))
+}
+
+
+/// Order of flags packed by `flags2seq`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitOrder {
+    /// The first flag is the most significant bit.
+    MsbFirst,
+    /// The first flag is the least significant bit.
+    LsbFirst,
+}
+
+/// Width (in digits of `base`) covering every set of `flag_count` flags.
+fn flags_width(flag_count: usize, base: usize) -> usize {
+    let mut max = rebase_lsb(&vec![1; flag_count], 2, base);
+    trim_lsb(&mut max);
+    max.len()
+}
+
+
+/// Packs `flags` as a binary number into a sequence of `char2val` glyphs.
+///
+/// `order` tells which flag is the most significant bit. The sequence is
+/// zero padded to the width covering any `flags.len()` flags, so equal
+/// counts give equal widths. Digit string arithmetic is used, so there is
+/// no limit on the count of flags.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{flags2seq, BitOrder};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let flags = [true, false, false, false, false, false, false, false];
+/// assert_eq!(flags2seq(&flags, &hex, BitOrder::MsbFirst), Ok("80".to_string()));
+/// assert_eq!(flags2seq(&flags, &hex, BitOrder::LsbFirst), Ok("01".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+pub fn flags2seq(flags: &[bool], char2val: &[char], order: BitOrder) -> Result<String, Error> {
+    positional_alphabet(char2val)?;
+    let mut bits: Vec<usize> = flags.iter().map(|flag| *flag as usize).collect();
+    if order == BitOrder::MsbFirst {
+        bits.reverse();
+    }
+    let mut digits = rebase_lsb(&bits, 2, char2val.len());
+    digits.resize(flags_width(flags.len(), char2val.len()), 0);
+    Ok(digits.iter().rev().map(|digit| char2val[*digit]).collect())
+}
+
+
+/// Unpacks `flag_count` flags from `sequence` packed by `flags2seq` with the same `order`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{seq2flags, BitOrder};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(seq2flags("5", &hex, 4, BitOrder::MsbFirst), Ok(vec![false, true, false, true]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char of `sequence`
+///
+/// * Returns `Error::OutOfRange` when value of `sequence` needs more than `flag_count` bits
+///
+/// ```
+/// use numsys::{seq2flags, BitOrder, Error};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let msg = "Value of \"1F\" MUST fit in 4 flags".to_string();
+/// assert_eq!(seq2flags("1F", &hex, 4, BitOrder::MsbFirst), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn seq2flags(sequence: &str, char2val: &[char], flag_count: usize, order: BitOrder) -> Result<Vec<bool>, Error> {
+    let digits = operand_digits(sequence, char2val)?;
+    let mut bits = rebase_lsb(&digits, char2val.len(), 2);
+    trim_lsb(&mut bits);
+    if bits.len() > flag_count {
+        return Err(Error::OutOfRange(
+            format!("Value of {:?} MUST fit in {} flags", sequence, flag_count),
+        ));
+    }
+    bits.resize(flag_count, 0);
+    if order == BitOrder::MsbFirst {
+        bits.reverse();
+    }
+    Ok(bits.iter().map(|bit| *bit == 1).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use {dec2seq, seq2dec, DIGITS, DIGITS_UPPER_AZ, DIGITS_UPPER_LOWER_AZ};
+
+    fn drive(conversion: &mut BigConversion, budget: usize) -> (StepResult, usize) {
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            match conversion.step(budget) {
+                StepResult::Progress => (),
+                outcome => return (outcome, steps),
+            }
+        }
+    }
+
+    #[test]
+    fn big_conversion_matches_recode() {
+        let mut rng = XorShift::new(2422);
+        for _ in 0..30 {
+            let (from, to) = (2 + rng.below(61), 2 + rng.below(61));
+            let (from, to) = (Alphabet::new(&DIGITS_UPPER_LOWER_AZ[..from]).unwrap(),
+                              Alphabet::new(&DIGITS_UPPER_LOWER_AZ[62 - to..]).unwrap());
+            let length = rng.below(1500);
+            let input: String = (0..length).map(|_| from.chars()[rng.below(from.len())]).collect();
+            let mut conversion = BigConversion::new(&input, &from, &to);
+            let (outcome, _) = drive(&mut conversion, 1 + rng.below(5000));
+            assert_eq!(outcome, StepResult::Done(recode(&input, &from, &to).unwrap()));
+            // finished conversions keep their outcome
+            assert_eq!(conversion.step(1), outcome);
+        }
+        let dec = Alphabet::new(&DIGITS).unwrap();
+        let bin = Alphabet::new(&['0', '1']).unwrap();
+        for input in ["", "0", "000", "0042", "12x4"].iter() {
+            let expected = match recode(input, &dec, &bin) {
+                Ok(result) => StepResult::Done(result),
+                Err(error) => StepResult::Error(error),
+            };
+            assert_eq!(drive(&mut BigConversion::new(input, &dec, &bin), 1).0, expected);
+        }
+        let unary = Alphabet::new(&['0']).unwrap();
+        assert_eq!(drive(&mut BigConversion::new("1", &dec, &unary), 7).0,
+                   StepResult::Error(recode("1", &dec, &unary).unwrap_err()));
+    }
+
+    #[test]
+    fn big_conversion_steps_scale_with_budget() {
+        let dec = Alphabet::new(&DIGITS).unwrap();
+        let hex = Alphabet::new(&DIGITS_UPPER_AZ[..16]).unwrap();
+        let input = "7".repeat(2000);
+        let mut counts = Vec::new();
+        for budget in [100, 1000, 10_000].iter() {
+            let mut conversion = BigConversion::new(&input, &dec, &hex);
+            let (outcome, steps) = drive(&mut conversion, *budget);
+            assert_eq!(outcome, StepResult::Done(recode(&input, &dec, &hex).unwrap()));
+            let work = conversion.work_done();
+            // every step but the last one spends the whole budget
+            assert_eq!(steps, work.div_ceil(*budget).max(1));
+            counts.push((steps, work));
+        }
+        // the work doesn't depend on the budget, so 10 times the budget is a tenth of the steps
+        assert!(counts.iter().all(|&(_, work)| work == counts[0].1));
+        assert!(counts[0].0 >= 99 * counts[1].0 / 10 && counts[1].0 >= 99 * counts[2].0 / 10);
+        // no work at all without a budget
+        let mut idle = BigConversion::new(&input, &dec, &hex);
+        assert_eq!(idle.step(0), StepResult::Progress);
+        assert_eq!(idle.work_done(), 0);
+    }
+
+    #[test]
+    fn big_conversion_dropped_half_way() {
+        let dec = Alphabet::new(&DIGITS).unwrap();
+        let bin = Alphabet::new(&['0', '1']).unwrap();
+        let input = "9".repeat(2000);
+        for steps in 0..10 {
+            let mut conversion = BigConversion::new(&input, &dec, &bin);
+            for _ in 0..steps {
+                assert_eq!(conversion.step(777), StepResult::Progress);
+            }
+            // a copy finishes as if it wasn't interrupted
+            let mut copy = conversion.clone();
+            drop(conversion);
+            assert_eq!(drive(&mut copy, 100_000).0, StepResult::Done(recode(&input, &dec, &bin).unwrap()));
+        }
+    }
+
+    #[test]
+    fn add_in_base_carries_through_all_positions() {
+        let (sum, carries) = add_in_base("999", "1", &DIGITS).unwrap();
+        assert_eq!(sum, "1000");
+        assert_eq!(carries, vec![true, true, true]);
+    }
+
+    #[test]
+    fn add_in_base_handles_numbers_beyond_usize() {
+        let a = "9".repeat(40);
+        let (sum, carries) = add_in_base(&a, &a, &DIGITS).unwrap();
+        assert_eq!(sum, format!("1{}8", "9".repeat(39)));
+        assert!(carries.iter().all(|c| *c));
+    }
+
+    #[test]
+    fn add_in_base_skips_leading_zeros() {
+        let (sum, carries) = add_in_base("000", "0", &DIGITS).unwrap();
+        assert_eq!(sum, "0");
+        assert_eq!(carries, vec![false, false, false]);
+    }
+
+    #[test]
+    fn sub_in_base_borrows_through_all_positions() {
+        assert_eq!(sub_in_base("1000", "1", &DIGITS), Ok("999".to_string()));
+        let a = format!("1{}", "0".repeat(40));
+        assert_eq!(sub_in_base(&a, "1", &DIGITS), Ok("9".repeat(40)));
+    }
+
+    #[test]
+    fn mul_in_base_matches_decoded_multiplication() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let samples = [0, 1, 15, 16, 255, 4096, 48879, 65535, 3_054_198_966, 0xFFFF_FFFF];
+        for x in samples.iter() {
+            for y in samples.iter() {
+                let a = dec2seq(*x, hex).unwrap();
+                let b = dec2seq(*y, hex).unwrap();
+                let product = mul_in_base(&a, &b, hex).unwrap();
+                assert_eq!(seq2dec(&product, hex), Ok(x * y), "{} * {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn mul_and_sub_agree_with_add() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let a = "DEADBEEF".repeat(5);
+        let doubled = mul_in_base(&a, "2", hex).unwrap();
+        let (sum, _) = add_in_base(&a, &a, hex).unwrap();
+        assert_eq!(doubled, sum);
+        assert_eq!(sub_in_base(&doubled, &a, hex), Ok(a));
+    }
+
+    #[test]
+    fn eq_seq_agrees_with_integer_comparison() {
+        let mut rng = XorShift::new(213);
+        for _ in 0..3000 {
+            let base_a = 2 + rng.below(61);
+            let base_b = 2 + rng.below(61);
+            let alpha_a = &DIGITS_UPPER_LOWER_AZ[..base_a];
+            let alpha_b = &DIGITS_UPPER_LOWER_AZ[..base_b];
+            let x = rng.next_u64() as usize >> rng.below(64);
+            let y = if rng.below(2) == 0 { x } else { rng.next_u64() as usize >> rng.below(64) };
+            let a = format!("{}{}", "0".repeat(rng.below(3)), dec2seq(x, alpha_a).unwrap());
+            let b = dec2seq(y, alpha_b).unwrap();
+            assert_eq!(eq_seq(&a, alpha_a, &b, alpha_b), Ok(x == y), "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn eq_seq_hundreds_of_digits() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        // 10^300 computed in base 16 by repeated multiplication
+        let mut power = "1".to_string();
+        for _ in 0..300 {
+            power = mul_in_base(&power, "A", hex).unwrap();
+        }
+        let decimal = format!("1{}", "0".repeat(300));
+        assert_eq!(eq_seq(&decimal, &DIGITS, &power, hex), Ok(true));
+        assert_eq!(eq_seq(&power, hex, &decimal, &DIGITS), Ok(true));
+        let (next, _) = add_in_base(&power, "1", hex).unwrap();
+        assert_eq!(eq_seq(&decimal, &DIGITS, &next, hex), Ok(false));
+        let lower: Vec<char> = "0123456789abcdef".chars().collect();
+        assert_eq!(eq_seq(&power, hex, &power.to_lowercase(), &lower), Ok(true));
+    }
+
+    #[test]
+    fn eq_seq_of_empty_and_zero() {
+        assert_eq!(eq_seq("", &DIGITS, "000", &DIGITS), Ok(true));
+        assert_eq!(eq_seq("0", &DIGITS, "0", &['0', '1']), Ok(true));
+        match eq_seq("x1", &DIGITS, "1", &DIGITS) {
+            Err(Error::MissingChar(msg)) => assert!(msg.contains("position 0 not found")),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cmp_seq_agrees_with_integer_comparison() {
+        let mut rng = XorShift::new(231);
+        for _ in 0..3000 {
+            let alphabet = &DIGITS_UPPER_LOWER_AZ[..2 + rng.below(61)];
+            let x = rng.next_u64() as usize >> rng.below(64);
+            let y = if rng.below(4) == 0 { x } else { rng.next_u64() as usize >> rng.below(64) };
+            let a = format!("{}{}", "0".repeat(rng.below(3)), dec2seq(x, alphabet).unwrap());
+            let b = dec2seq(y, alphabet).unwrap();
+            assert_eq!(cmp_seq(&a, &b, alphabet), Ok(x.cmp(&y)), "{} vs {}", a, b);
+        }
+        assert_eq!(cmp_seq("", "0", &DIGITS), Ok(Ordering::Equal));
+        assert!(cmp_seq("1", "2", &['0', '1']).is_err());
+    }
+
+    fn base32() -> Vec<char> {
+        "0123456789ABCDEFGHJKMNPQRSTVWXYZ".chars().collect()
+    }
+
+    #[test]
+    fn fixed_width_extremes_round_trip() {
+        // 32^10 = 2^50 needs 9 digits of base 62
+        let lowest = "0".repeat(10);
+        let highest = "Z".repeat(10);
+        let low = convert_fixed_width(&lowest, &base32(), &DIGITS_UPPER_LOWER_AZ, 10).unwrap();
+        let high = convert_fixed_width(&highest, &base32(), &DIGITS_UPPER_LOWER_AZ, 10).unwrap();
+        assert_eq!(low, "0".repeat(9));
+        assert_eq!(high.chars().count(), 9);
+        // the way back needs 11 digits, as 62^9 is bigger than 32^10
+        let back = convert_fixed_width(&low, &DIGITS_UPPER_LOWER_AZ, &base32(), 9).unwrap();
+        assert_eq!(back, format!("0{}", lowest));
+        let back = convert_fixed_width(&high, &DIGITS_UPPER_LOWER_AZ, &base32(), 9).unwrap();
+        assert_eq!(back, format!("0{}", highest));
+        assert_eq!(convert_fixed_width("", &base32(), &DIGITS, 0), Ok(String::new()));
+    }
+
+    #[test]
+    fn fixed_width_random_samples_are_injective() {
+        let mut rng = XorShift::new(2162);
+        let mut seen = HashMap::new();
+        for _ in 0..2000 {
+            let value = rng.below(1 << 50);
+            let seq = format!("{:0>10}", dec2seq(value, &base32()).unwrap());
+            let converted = convert_fixed_width(&seq, &base32(), &DIGITS_UPPER_LOWER_AZ, 10).unwrap();
+            assert_eq!(converted.chars().count(), 9);
+            assert_eq!(seq2dec(&converted, &DIGITS_UPPER_LOWER_AZ), Ok(value));
+            assert_eq!(*seen.entry(converted).or_insert(value), value);
+        }
+        // widths past usize take the digit string path
+        let seq = "Z".repeat(40);
+        let converted = convert_fixed_width(&seq, &base32(), &DIGITS_UPPER_AZ[..16], 40).unwrap();
+        assert_eq!(converted, "F".repeat(50));
+    }
+
+    #[test]
+    fn recode_preserves_values() {
+        let mut rng = XorShift::new(221);
+        for _ in 0..500 {
+            let base_a = 2 + rng.below(61);
+            let base_b = 2 + rng.below(61);
+            let from = Alphabet::new(&DIGITS_UPPER_LOWER_AZ[..base_a]).unwrap();
+            let to = Alphabet::new(&DIGITS_UPPER_LOWER_AZ[62 - base_b..]).unwrap();
+            let value = rng.next_u64() as usize >> rng.below(64);
+            let seq = dec2seq(value, from.chars()).unwrap();
+            let recoded = recode(&seq, &from, &to).unwrap();
+            assert_eq!(seq2dec(&recoded, to.chars()), Ok(value));
+            assert_eq!(eq_seq(&seq, from.chars(), &recoded, to.chars()), Ok(true));
+        }
+    }
+
+    #[test]
+    fn small_stays_small_and_huge_becomes_big() {
+        assert_eq!(seq2dec_auto("0042", &DIGITS), Ok(Number::Small(42)));
+        let max = dec2seq(usize::MAX, &DIGITS[..]).unwrap();
+        assert_eq!(seq2dec_auto(&max, &DIGITS), Ok(Number::Small(usize::MAX)));
+        let (next, _) = add_in_base(&max, "1", &DIGITS).unwrap();
+        let mut expected = vec![1];
+        expected.extend(vec![0; 8]);
+        assert_eq!(seq2dec_auto(&next, &DIGITS), Ok(Number::Big(expected)));
+        // 2^800 has a 1 followed by 100 zero bytes
+        let power = format!("1{}", "0".repeat(800));
+        match seq2dec_auto(&format!("000{}", power), &['0', '1']) {
+            Ok(Number::Big(bytes)) => {
+                assert_eq!(bytes.len(), 101);
+                assert_eq!(bytes[0], 1);
+                assert!(bytes[1..].iter().all(|byte| *byte == 0));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(seq2dec_auto("12", &['0', '1']).is_err());
+    }
+
+    #[test]
+    fn flags_round_trip_in_both_orders() {
+        let base36 = &DIGITS_UPPER_AZ[..];
+        let mut rng = XorShift::new(2232);
+        for &order in [BitOrder::MsbFirst, BitOrder::LsbFirst].iter() {
+            for count in 0..130 {
+                let random: Vec<bool> = (0..count).map(|_| rng.below(2) == 1).collect();
+                for flags in [vec![false; count], vec![true; count], random] {
+                    let seq = flags2seq(&flags, base36, order).unwrap();
+                    assert_eq!(seq.len(), flags2seq(&vec![true; count], base36, order).unwrap().len());
+                    assert_eq!(seq2flags(&seq, base36, count, order), Ok(flags));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flags_bit_orders_and_widths() {
+        let flags = [true, true, false, false, false];
+        // 11000 is 24, 00011 is 3
+        assert_eq!(flags2seq(&flags, &DIGITS, BitOrder::MsbFirst), Ok("24".to_string()));
+        assert_eq!(flags2seq(&flags, &DIGITS, BitOrder::LsbFirst), Ok("03".to_string()));
+        assert_eq!(flags2seq(&[false; 5], &DIGITS, BitOrder::LsbFirst), Ok("00".to_string()));
+        assert_eq!(flags2seq(&[true; 64], &DIGITS_UPPER_AZ[..16], BitOrder::LsbFirst), Ok("F".repeat(16)));
+        let mut hundred = vec![false; 100];
+        hundred[99] = true;
+        let seq = flags2seq(&hundred, &DIGITS_UPPER_AZ[..16], BitOrder::MsbFirst).unwrap();
+        assert_eq!(seq, format!("{:0>25}", "1"));
+        let seq = flags2seq(&hundred, &DIGITS_UPPER_AZ[..16], BitOrder::LsbFirst).unwrap();
+        assert_eq!(seq, format!("8{}", "0".repeat(24)));
+        assert_eq!(seq2flags(&seq, &DIGITS_UPPER_AZ[..16], 100, BitOrder::LsbFirst), Ok(hundred));
+        assert!(seq2flags(&seq, &DIGITS_UPPER_AZ[..16], 99, BitOrder::LsbFirst).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::str;
+
+use digits::missing_char;
+use parse::echo;
+use {positional_alphabet, Error};
+
+
+/// Marks ASCII bytes which aren't in the alphabet, never an index of a char.
+const NOT_A_DIGIT: usize = usize::MAX;
+
+
+/// Decoding failure of a row of `seq2dec_offsets`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    /// Index of the row, counted from 0
+    pub row: usize,
+    /// Position of the offending char within the row
+    pub position: usize,
+    /// Why the row wasn't decoded
+    pub error: Error,
+}
+
+
+/// Result of `seq2dec_offsets`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchReport {
+    /// Count of rows walked, each appended a value
+    pub rows: usize,
+    /// Rows which weren't decoded, in row order
+    pub errors: Vec<RowError>,
+}
+
+impl BatchReport {
+    /// Returns count of rows decoded successfully.
+    pub fn decoded(&self) -> usize {
+        self.rows - self.errors.len()
+    }
+}
+
+
+/// Checks `offsets` of `data` are monotonic and in bounds, before anything is decoded.
+fn check_offsets(data: &[u8], offsets: &[usize]) -> Result<(), Error> {
+    for (index, pair) in offsets.windows(2).enumerate() {
+        if pair[1] < pair[0] {
+            return Err(Error::OutOfRange(format!(
+                "Offset {} at index {} MUST NOT be less than offset {} before it",
+                pair[1],
+                index + 1,
+                pair[0]
+            )));
+        }
+    }
+    if let Some(last) = offsets.last() {
+        if *last > data.len() {
+            return Err(Error::OutOfRange(format!(
+                "Offset {} at index {} MUST NOT exceed data length {}",
+                last,
+                offsets.len() - 1,
+                data.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+
+/// Decodes `row` byte by byte, or returns `None` at its first non-ASCII byte.
+fn decode_ascii(row: &[u8], ascii: &[usize; 128], base: u64) -> Option<Result<u64, (usize, bool)>> {
+    let mut value: u64 = 0;
+    for (position, byte) in row.iter().enumerate() {
+        let digit = *ascii.get(*byte as usize)?;
+        if digit == NOT_A_DIGIT {
+            return Some(Err((position, false)));
+        }
+        value = match value.checked_mul(base).and_then(|v| v.checked_add(digit as u64)) {
+            Some(value) => value,
+            None => return Some(Err((position, true))),
+        };
+    }
+    Some(Ok(value))
+}
+
+
+/// Decodes `row` char by char, the path of rows with non-ASCII bytes.
+fn decode_chars(row: &str, char2val: &HashMap<char, usize>, base: u64) -> Result<u64, (usize, bool)> {
+    let mut value: u64 = 0;
+    for (position, glyph) in row.chars().enumerate() {
+        let digit = *char2val.get(&glyph).ok_or((position, false))?;
+        value = value
+            .checked_mul(base)
+            .and_then(|v| v.checked_add(digit as u64))
+            .ok_or((position, true))?;
+    }
+    Ok(value)
+}
+
+
+/// Decodes each row of a columnar buffer using `char2val` translation, appending the values to `out`.
+///
+/// Row `i` is `data[offsets[i]..offsets[i + 1]]`, like an Arrow string
+/// column, so there are `offsets.len() - 1` rows. An empty row decodes to 0,
+/// the way `dec2seq` encodes it. Rows are decoded straight from the bytes,
+/// with a table lookup for ASCII ones, so no row allocates unless it fails.
+///
+/// A row which fails doesn't abort the batch: it's recorded in the report
+/// and 0 is appended in its place, so `out` stays aligned with the rows.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{seq2dec_offsets, DIGITS_UPPER_AZ};
+///
+/// let data = b"ZZ10X!7";
+/// let mut out = Vec::new();
+/// let report = seq2dec_offsets(data, &[0, 2, 4, 4, 7], &DIGITS_UPPER_AZ, &mut out).unwrap();
+/// assert_eq!(out, vec![1295, 36, 0, 0]);
+/// assert_eq!(report.rows, 4);
+/// assert_eq!(report.decoded(), 3);
+/// assert_eq!((report.errors[0].row, report.errors[0].position), (3, 1));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::OutOfRange` when `offsets` decrease or exceed `data`, nothing is decoded then
+///
+/// ```
+/// use numsys::{seq2dec_offsets, DIGITS};
+/// use numsys::Error;
+///
+/// let mut out = Vec::new();
+/// let msg = "Offset 1 at index 2 MUST NOT be less than offset 3 before it".to_string();
+/// assert_eq!(seq2dec_offsets(b"123", &[0, 3, 1], &DIGITS, &mut out), Err(Error::OutOfRange(msg)));
+/// assert!(out.is_empty());
+/// ```
+///
+/// Rows fail with `Error::MissingChar` for a char out of `char2val` (or
+/// `Error::InvalidBytes` when the row isn't UTF-8) and with
+/// `Error::Overflow` when the value exceeds `u64`.
+pub fn seq2dec_offsets(data: &[u8], offsets: &[usize], char2val: &[char], out: &mut Vec<u64>)
+    -> Result<BatchReport, Error> {
+    let map = positional_alphabet(char2val)?;
+    check_offsets(data, offsets)?;
+    let base = char2val.len() as u64;
+    let mut ascii = [NOT_A_DIGIT; 128];
+    for (value, glyph) in char2val.iter().enumerate() {
+        if glyph.is_ascii() {
+            ascii[*glyph as usize] = value;
+        }
+    }
+    let rows = offsets.len().saturating_sub(1);
+    out.reserve(rows);
+    let mut errors = Vec::new();
+    for (row, pair) in offsets.windows(2).enumerate() {
+        let bytes = &data[pair[0]..pair[1]];
+        let decoded = match decode_ascii(bytes, &ascii, base) {
+            Some(decoded) => decoded,
+            None => match str::from_utf8(bytes) {
+                Ok(text) => decode_chars(text, &map, base),
+                Err(e) => {
+                    errors.push(RowError {
+                        row,
+                        position: e.valid_up_to(),
+                        error: Error::InvalidBytes(format!(
+                            "Row {} MUST be UTF-8, given invalid byte at offset {}",
+                            row,
+                            e.valid_up_to()
+                        )),
+                    });
+                    out.push(0);
+                    continue;
+                }
+            },
+        };
+        match decoded {
+            Ok(value) => out.push(value),
+            Err((position, overflow)) => {
+                let text = String::from_utf8_lossy(bytes);
+                let error = if overflow {
+                    Error::Overflow(format!("Value of {} exceeds u64", echo(&text)))
+                } else {
+                    let glyph = text.chars().nth(position).expect("Position of a char in the row");
+                    missing_char(glyph, Some(position), char2val)
+                };
+                errors.push(RowError { row, position, error });
+                out.push(0);
+            }
+        }
+    }
+    Ok(BatchReport { rows, errors })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::allocations;
+    use {seq2dec, DIGITS, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn agrees_with_per_row_decoding() {
+        let rows = ["0", "1", "Z", "10", "ZZZZ", "HELLO", "0042", "3W5E11264SGSF"];
+        let mut data = Vec::new();
+        let mut offsets = vec![0];
+        for row in rows.iter() {
+            data.extend_from_slice(row.as_bytes());
+            offsets.push(data.len());
+        }
+        let mut out = vec![7];
+        let report = seq2dec_offsets(&data, &offsets, &DIGITS_UPPER_AZ, &mut out).unwrap();
+        assert_eq!(report, BatchReport { rows: rows.len(), errors: vec![] });
+        // appended after what the caller had
+        assert_eq!(out[0], 7);
+        for (row, value) in rows.iter().zip(&out[1..]) {
+            assert_eq!(seq2dec(row, &DIGITS_UPPER_AZ).map(|v| v as u64), Ok(*value));
+        }
+    }
+
+    #[test]
+    fn empty_and_invalid_rows() {
+        let data = "12a3☆99999999999999999999999".as_bytes();
+        let offsets = [0, 0, 2, 4, 4, 7, data.len(), data.len()];
+        let mut out = Vec::new();
+        let report = seq2dec_offsets(data, &offsets, &DIGITS, &mut out).unwrap();
+        assert_eq!(out, vec![0, 12, 0, 0, 0, 0, 0]);
+        assert_eq!(report.rows, 7);
+        assert_eq!(report.decoded(), 4);
+        let failed: Vec<(usize, usize)> = report.errors.iter().map(|e| (e.row, e.position)).collect();
+        assert_eq!(failed, vec![(2, 0), (4, 0), (5, 19)]);
+        assert_eq!(
+            report.errors[1].error,
+            Error::MissingChar(format!("Char '☆' at position 0 not found in: {:?}", &DIGITS[..]))
+        );
+        match report.errors[2].error {
+            Error::Overflow(_) => {}
+            ref other => panic!("Overflow expected, given {:?}", other),
+        }
+        // an offset inside the multi-byte char splits it
+        let report = seq2dec_offsets(data, &[4, 5, 7], &DIGITS, &mut out).unwrap();
+        assert_eq!(report.errors.len(), 2);
+        match report.errors[0].error {
+            Error::InvalidBytes(_) => {}
+            ref other => panic!("InvalidBytes expected, given {:?}", other),
+        }
+    }
+
+    #[test]
+    fn non_ascii_alphabet() {
+        let stars = ['★', '☆'];
+        let data = "☆★☆☆12".as_bytes();
+        let mut out = Vec::new();
+        let report = seq2dec_offsets(data, &[0, 12, 13, data.len()], &stars, &mut out).unwrap();
+        assert_eq!(out, vec![11, 0, 0]);
+        assert_eq!(report.decoded(), 1);
+        assert!(report.errors.iter().all(|e| e.position == 0));
+    }
+
+    #[test]
+    fn ascii_digits_of_large_mixed_alphabet() {
+        // ASCII glyphs past index 255 of the alphabet, and at 255 itself
+        let mut alphabet: Vec<char> = (0..300).map(|index| ::std::char::from_u32(0x4E00 + index).unwrap()).collect();
+        alphabet.extend(&['0', '1']);
+        alphabet[255] = 'x';
+        let data = b"10x1";
+        let mut out = Vec::new();
+        let report = seq2dec_offsets(data, &[0, 1, 2, 3, 4], &alphabet, &mut out).unwrap();
+        assert_eq!(report.errors, vec![]);
+        assert_eq!(out, vec![301, 300, 255, 301]);
+        let report = seq2dec_offsets(data, &[0, 2], &alphabet, &mut out).unwrap();
+        assert_eq!(report.errors, vec![]);
+        assert_eq!(out.last(), Some(&(301 * 302 + 300)));
+    }
+
+    #[test]
+    fn offset_edge_cases() {
+        let mut out = Vec::new();
+        for offsets in [&[][..], &[0][..], &[3][..]].iter() {
+            let report = seq2dec_offsets(b"123", offsets, &DIGITS, &mut out).unwrap();
+            assert_eq!(report, BatchReport { rows: 0, errors: vec![] });
+        }
+        // the final offset may equal data length but not exceed it
+        assert!(seq2dec_offsets(b"123", &[1, 3], &DIGITS, &mut out).is_ok());
+        assert_eq!(out, vec![23]);
+        let msg = "Offset 4 at index 1 MUST NOT exceed data length 3".to_string();
+        assert_eq!(seq2dec_offsets(b"123", &[1, 4], &DIGITS, &mut out), Err(Error::OutOfRange(msg)));
+        assert!(seq2dec_offsets(b"123", &[4], &DIGITS, &mut out).is_err());
+        assert!(seq2dec_offsets(b"123", &[0, 1, 0, 3], &DIGITS, &mut out).is_err());
+        assert_eq!(out, vec![23]);
+        assert_eq!(seq2dec_offsets(b"", &[0, 0], &[], &mut out), Err(Error::DictEmpty));
+    }
+
+    #[test]
+    fn rows_decode_without_allocating() {
+        let data = b"Z".repeat(10_000);
+        let offsets: Vec<usize> = (0..=data.len()).collect();
+        let mut out = Vec::with_capacity(offsets.len());
+        let before = allocations();
+        seq2dec_offsets(&data, &offsets[..11], &DIGITS_UPPER_AZ, &mut out).unwrap();
+        let few = allocations() - before;
+        out.clear();
+        let before = allocations();
+        seq2dec_offsets(&data, &offsets, &DIGITS_UPPER_AZ, &mut out).unwrap();
+        assert_eq!(allocations() - before, few);
+        assert_eq!(out, vec![35; 10_000]);
+    }
+}
@@ -0,0 +1,131 @@
+use digits::missing_char;
+use parse::{accumulate, value_overflow};
+use {Error, OverflowMode};
+
+
+/// Digits of bijective base 10, valued from 1 to 10.
+const BIJECTIVE_DECIMAL: [char; 10] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', 'A'];
+
+
+/// Converts `n` to bijective base `digits.len()`, the digits are valued from 1.
+fn to_bijective(n: usize, digits: &[char]) -> String {
+    let base = digits.len();
+    let mut rest = n;
+    let mut sequence = Vec::new();
+    while rest != 0 {
+        // a digit is 1..=base, so a multiple of base ends with the top one
+        let digit = (rest - 1) % base;
+        sequence.push(digits[digit]);
+        rest = (rest - 1) / base;
+    }
+    sequence.iter().rev().collect()
+}
+
+
+/// Converts `sequence` of bijective base `digits.len()` to decimal, the inverse of `to_bijective`.
+fn from_bijective(sequence: &str, digits: &[char]) -> Result<usize, Error> {
+    let base = digits.len();
+    let mut n: usize = 0;
+    for (position, glyph) in sequence.chars().enumerate() {
+        let digit = digits.iter().position(|g| *g == glyph).ok_or_else(|| missing_char(glyph, Some(position), digits))?;
+        n = accumulate(n, base, digit + 1, OverflowMode::Error, || value_overflow(sequence))?;
+    }
+    Ok(n)
+}
+
+
+/// Converts `n` to bijective base 10, with digits `1-9` and `A` for ten.
+///
+/// There is no zero digit, so no leading zeros either and each number has
+/// exactly one representation: 10 is `A`, 11 is `11` and 20 is `1A`. Zero
+/// is the empty sequence.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_bijective_decimal;
+///
+/// assert_eq!(to_bijective_decimal(9), "9");
+/// assert_eq!(to_bijective_decimal(10), "A");
+/// assert_eq!(to_bijective_decimal(11), "11");
+/// assert_eq!(to_bijective_decimal(100), "9A");
+/// assert_eq!(to_bijective_decimal(0), "");
+/// ```
+pub fn to_bijective_decimal(n: usize) -> String {
+    to_bijective(n, &BIJECTIVE_DECIMAL)
+}
+
+
+/// Converts `sequence` of bijective base 10 to decimal, the inverse of `to_bijective_decimal`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_bijective_decimal;
+///
+/// assert_eq!(from_bijective_decimal("A"), Ok(10));
+/// assert_eq!(from_bijective_decimal("1A"), Ok(20));
+/// assert_eq!(from_bijective_decimal(""), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::MissingChar` when `sequence` includes a char other than `1-9` and `A`
+///
+/// ```
+/// use numsys::from_bijective_decimal;
+/// use numsys::Error;
+///
+/// let msg = "Char \'0\' at position 1 not found in: \
+///            [\'1\', \'2\', \'3\', \'4\', \'5\', \'6\', \'7\', \'8\', \'9\', \'A\']".to_string();
+/// assert_eq!(from_bijective_decimal("10"), Err(Error::MissingChar(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn from_bijective_decimal(sequence: &str) -> Result<usize, Error> {
+    from_bijective(sequence, &BIJECTIVE_DECIMAL)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    #[test]
+    fn nine_to_ten_boundary() {
+        let expected = ["8", "9", "A", "11", "12"];
+        for (n, sequence) in (8..13).zip(expected.iter()) {
+            assert_eq!(to_bijective_decimal(n), *sequence);
+            assert_eq!(from_bijective_decimal(sequence), Ok(n));
+        }
+        // each power of ten is one digit shorter than in decimal
+        assert_eq!(to_bijective_decimal(99), "99");
+        assert_eq!(to_bijective_decimal(110), "AA");
+        assert_eq!(to_bijective_decimal(111), "111");
+        assert_eq!(to_bijective_decimal(1000), "99A");
+        assert_eq!(to_bijective_decimal(1_000_000), "99999A");
+    }
+
+    #[test]
+    fn round_trip_and_limits() {
+        let mut previous = String::new();
+        for n in 1..5_000 {
+            let sequence = to_bijective_decimal(n);
+            assert!(!sequence.contains('0'));
+            // zeroless, so every sequence is a distinct number, ordered by length then digits
+            assert!((previous.len(), &previous) < (sequence.len(), &sequence));
+            assert_eq!(from_bijective_decimal(&sequence), Ok(n));
+            previous = sequence;
+        }
+        let mut rng = XorShift::new(245);
+        for _ in 0..1_000 {
+            let n = rng.next_u64() as usize;
+            assert_eq!(from_bijective_decimal(&to_bijective_decimal(n)), Ok(n));
+        }
+        let max = to_bijective_decimal(usize::MAX);
+        assert_eq!(from_bijective_decimal(&max), Ok(usize::MAX));
+        assert!(from_bijective_decimal(&format!("{}1", max)).is_err());
+        assert!(from_bijective_decimal("a").is_err());
+    }
+}
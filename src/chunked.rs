@@ -0,0 +1,212 @@
+use Error;
+
+
+fn check_layout(sequence: &str, group: usize, groups_per_line: usize, group_sep: char, line_sep: &str) -> Result<(), Error> {
+    if group == 0 || groups_per_line == 0 {
+        return Err(Error::OutOfRange(format!(
+            "Group size and groups per line MUST be at least 1, given {} and {}",
+            group,
+            groups_per_line
+        )));
+    }
+    if group.checked_mul(groups_per_line).is_none() {
+        return Err(Error::OutOfRange(format!(
+            "Chars per line MUST fit usize, given {} groups of {}",
+            groups_per_line,
+            group
+        )));
+    }
+    if line_sep.is_empty() {
+        return Err(Error::InvalidMarker("Line separator MUST NOT be empty".into()));
+    }
+    let line_glyphs = line_sep.chars();
+    if let Some(glyph) = Some(group_sep).into_iter().chain(line_glyphs).find(|glyph| sequence.contains(*glyph)) {
+        return Err(Error::InvalidMarker(format!("Separator {:?} MUST NOT be in: {:?}", glyph, sequence)));
+    }
+    Ok(())
+}
+
+
+/// Splits `sequence` for display in groups of `group` chars, `groups_per_line` groups per line.
+///
+/// Groups are joined with `group_sep` and lines with `line_sep`, like PGP
+/// fingerprints or license keys. Groups are counted from the start, only
+/// the last one may be shorter. Chars are counted, not bytes, so glyphs of
+/// any width are never split.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::format_chunked;
+///
+/// let chunked = format_chunked("0123456789ABCDEFGHIJ", 4, 2, ' ', "\n");
+/// assert_eq!(chunked, Ok("0123 4567\n89AB CDEF\nGHIJ".to_string()));
+/// assert_eq!(format_chunked("★☆★☆★", 2, 5, '-', "\n"), Ok("★☆-★☆-★".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfRange` when `group` or `groups_per_line` is 0, or chars of a line exceed `usize`
+///
+/// ```
+/// use numsys::format_chunked;
+/// use numsys::Error;
+///
+/// let msg = format!("Chars per line MUST fit usize, given {} groups of 2", usize::MAX);
+/// assert_eq!(format_chunked("ABC", 2, usize::MAX, ' ', "\n"), Err(Error::OutOfRange(msg)));
+/// ```
+///
+/// * Returns `Error::InvalidMarker` when `line_sep` is empty, or a separator char is in `sequence`
+///
+/// ```
+/// use numsys::format_chunked;
+/// use numsys::Error;
+///
+/// let msg = "Separator \'-\' MUST NOT be in: \"AB-CD\"".to_string();
+/// assert_eq!(format_chunked("AB-CD", 2, 2, '-', "\n"), Err(Error::InvalidMarker(msg)));
+/// ```
+pub fn format_chunked(sequence: &str, group: usize, groups_per_line: usize, group_sep: char, line_sep: &str) -> Result<String, Error> {
+    check_layout(sequence, group, groups_per_line, group_sep, line_sep)?;
+    let line = group * groups_per_line;
+    let mut chunked = String::with_capacity(sequence.len() * 2);
+    for (index, glyph) in sequence.chars().enumerate() {
+        if index != 0 && index % group == 0 {
+            if index % line == 0 {
+                chunked.push_str(line_sep);
+            } else {
+                chunked.push(group_sep);
+            }
+        }
+        chunked.push(glyph);
+    }
+    Ok(chunked)
+}
+
+
+/// Joins `text` chunked by `format_chunked` back into the sequence.
+///
+/// With `strict` set `text` MUST have exactly the layout `format_chunked`
+/// gives, otherwise all `group_sep` chars and `line_sep` strings are just
+/// removed, wherever they are.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::parse_chunked;
+///
+/// assert_eq!(parse_chunked("0123 4567\n89", 4, 2, ' ', "\n", true), Ok("0123456789".to_string()));
+/// assert_eq!(parse_chunked("01 234567 \n89", 4, 2, ' ', "\n", false), Ok("0123456789".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfRange` when `group` or `groups_per_line` is 0, or chars of a line exceed `usize`
+///
+/// * Returns `Error::InvalidMarker` when `line_sep` is empty
+///
+/// * Returns `Error::InvalidLayout` when `strict` is set and `text` has another layout, e.g. a part of `line_sep`
+///
+/// ```
+/// use numsys::parse_chunked;
+/// use numsys::Error;
+///
+/// let msg = "Char \' \' at position 3 MUST be \'3\' of the layout".to_string();
+/// assert_eq!(parse_chunked("012 34567", 4, 2, ' ', "\n", true), Err(Error::InvalidLayout(msg)));
+/// ```
+pub fn parse_chunked(text: &str, group: usize, groups_per_line: usize, group_sep: char, line_sep: &str,
+                     strict: bool) -> Result<String, Error> {
+    check_layout("", group, groups_per_line, group_sep, line_sep)?;
+    let sequence: String = text.split(line_sep).flat_map(|line| line.split(group_sep)).collect();
+    if strict {
+        // what's left of a partial `line_sep` is a broken layout, not a separator in the sequence
+        if let Some(glyph) = line_sep.chars().find(|glyph| sequence.contains(*glyph)) {
+            return Err(Error::InvalidLayout(
+                format!("Char {:?} MUST NOT be apart from line separator {:?}", glyph, line_sep),
+            ));
+        }
+        let expected = format_chunked(&sequence, group, groups_per_line, group_sep, line_sep)?;
+        let mut expected_chars = expected.chars();
+        // the layout has the same glyphs, so `text` can't be shorter
+        for (position, glyph) in text.chars().enumerate() {
+            match expected_chars.next() {
+                Some(wanted) if wanted == glyph => (),
+                Some(wanted) => {
+                    return Err(Error::InvalidLayout(format!(
+                        "Char {:?} at position {} MUST be {:?} of the layout",
+                        glyph,
+                        position,
+                        wanted
+                    )))
+                }
+                None => {
+                    return Err(Error::InvalidLayout(
+                        format!("Char {:?} at position {} MUST NOT be after the layout end", glyph, position),
+                    ))
+                }
+            }
+        }
+    }
+    Ok(sequence)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_at_group_and_line_boundaries() {
+        let glyphs: Vec<char> = "0123456789ABCDEFGHJKMNPQRSTVWXYZ€✓".chars().collect();
+        for length in 0..80 {
+            let sequence: String = (0..length).map(|index| glyphs[index * 7 % glyphs.len()]).collect();
+            for &(group, per_line) in [(1, 1), (4, 4), (5, 5), (3, 7)].iter() {
+                let chunked = format_chunked(&sequence, group, per_line, ' ', "\r\n").unwrap();
+                let groups = length.div_ceil(group);
+                assert_eq!(chunked.matches("\r\n").count(), groups.saturating_sub(1) / per_line);
+                for &strict in [true, false].iter() {
+                    assert_eq!(parse_chunked(&chunked, group, per_line, ' ', "\r\n", strict), Ok(sequence.clone()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pgp_fingerprint_layout() {
+        let fingerprint = "0123456789ABCDEF0123456789ABCDEF01234567";
+        let chunked = format_chunked(fingerprint, 4, 5, ' ', "  ").unwrap();
+        assert_eq!(chunked, "0123 4567 89AB CDEF 0123  4567 89AB CDEF 0123 4567");
+        assert_eq!(parse_chunked(&chunked, 4, 5, ' ', "  ", true), Ok(fingerprint.to_string()));
+    }
+
+    #[test]
+    fn strict_rejects_wrong_group_length() {
+        let chunked = "AAAAA BBBBB\nCCCCC";
+        assert_eq!(parse_chunked(chunked, 5, 2, ' ', "\n", true), Ok("AAAAABBBBBCCCCC".to_string()));
+        for broken in ["AAAAA BBBB\nCCCCC", "AAAAA BBBBBB\nCCCC", "AAAAA\nBBBBB CCCCC", "AAAAA BBBBB\n", "AAAAABBBBB\nCCCCC"] {
+            match parse_chunked(broken, 5, 2, ' ', "\n", true) {
+                Err(Error::InvalidLayout(_)) => (),
+                other => panic!("unexpected {:?} for {:?}", other, broken),
+            }
+            assert!(parse_chunked(broken, 5, 2, ' ', "\n", false).is_ok());
+        }
+        assert!(format_chunked("AB", 0, 2, ' ', "\n").is_err());
+        assert!(format_chunked("AB", 2, 2, ' ', "").is_err());
+        assert!(format_chunked("A\nB", 2, 2, ' ', "\n").is_err());
+    }
+
+    #[test]
+    fn layout_edge_cases() {
+        for &(group, per_line) in [(2, usize::MAX), (usize::MAX, 2)].iter() {
+            match format_chunked("ABC", group, per_line, ' ', "\n") {
+                Err(Error::OutOfRange(_)) => (),
+                other => panic!("unexpected {:?} for {} x {}", other, group, per_line),
+            }
+            assert!(parse_chunked("ABC", group, per_line, ' ', "\n", false).is_err());
+        }
+        assert_eq!(format_chunked("ABC", usize::MAX, 1, ' ', "\n"), Ok("ABC".to_string()));
+        let msg = "Char '\\r' MUST NOT be apart from line separator \"\\r\\n\"".to_string();
+        assert_eq!(parse_chunked("AB CD\rEF", 2, 2, ' ', "\r\n", true), Err(Error::InvalidLayout(msg)));
+        assert_eq!(parse_chunked("AB CD\r\nEF", 2, 2, ' ', "\r\n", true), Ok("ABCDEF".to_string()));
+        assert_eq!(parse_chunked("AB CD\rEF", 2, 2, ' ', "\r\n", false), Ok("ABCD\rEF".to_string()));
+    }
+}
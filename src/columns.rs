@@ -0,0 +1,142 @@
+use digits::missing_char;
+use {Error, UPPER_AZ};
+
+
+/// Converts column `index` to its spreadsheet name (`A`, `B`, ..., `Z`, `AA`, ...).
+///
+/// Names are bijective base 26, there is no zero digit. `one_based` selects
+/// the index convention: Excel numbers columns from 1 (`A` is 1), arrays
+/// from 0 (`A` is 0).
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_column_name;
+///
+/// // Excel
+/// assert_eq!(to_column_name(1, true), Ok("A".to_string()));
+/// assert_eq!(to_column_name(27, true), Ok("AA".to_string()));
+/// // arrays
+/// assert_eq!(to_column_name(0, false), Ok("A".to_string()));
+/// assert_eq!(to_column_name(26, false), Ok("AA".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfRange` when `index` is 0 and `one_based` is set
+///
+/// ```
+/// use numsys::to_column_name;
+/// use numsys::Error;
+///
+/// let msg = "Column index MUST be at least 1, given 0".to_string();
+/// assert_eq!(to_column_name(0, true), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn to_column_name(index: usize, one_based: bool) -> Result<String, Error> {
+    if one_based && index == 0 {
+        return Err(Error::OutOfRange("Column index MUST be at least 1, given 0".into()));
+    }
+    // the 0-based index, computed without overflowing usize::MAX + 1
+    let mut rest = if one_based { index - 1 } else { index };
+    let mut name = Vec::new();
+    loop {
+        name.push(UPPER_AZ[rest % 26]);
+        if rest < 26 {
+            break;
+        }
+        rest = rest / 26 - 1;
+    }
+    Ok(name.iter().rev().collect())
+}
+
+
+/// Converts spreadsheet column `name` to its index, the inverse of `to_column_name`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_column_name;
+///
+/// assert_eq!(from_column_name("A", true), Ok(1));
+/// assert_eq!(from_column_name("A", false), Ok(0));
+/// assert_eq!(from_column_name("XFD", true), Ok(16_384));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::SequenceEmpty` when `name` is empty
+///
+/// * Returns `Error::MissingChar` when `name` includes a char other than `A-Z`
+///
+/// ```
+/// use numsys::from_column_name;
+/// use numsys::Error;
+///
+/// match from_column_name("Aa", true) {
+///     Err(Error::MissingChar(msg)) => assert!(msg.starts_with("Char \'a\' at position 1 not found in: [\'A\', \'B\'")),
+///     other => panic!("{:?}", other),
+/// }
+/// ```
+///
+/// * Returns `Error::Overflow` when index exceeds `usize`
+pub fn from_column_name(name: &str, one_based: bool) -> Result<usize, Error> {
+    if name.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    let overflow = || Error::Overflow(format!("Index of column {:?} exceeds usize", name));
+    let mut index: u128 = 0;
+    for (position, glyph) in name.chars().enumerate() {
+        let digit = match glyph {
+            'A'..='Z' => glyph as u128 - 'A' as u128 + 1,
+            _ => return Err(missing_char(glyph, Some(position), &UPPER_AZ)),
+        };
+        index = index
+            .checked_mul(26)
+            .and_then(|i| i.checked_add(digit))
+            .ok_or_else(&overflow)?;
+    }
+    let index = if one_based { index } else { index - 1 };
+    if index > usize::MAX as u128 {
+        return Err(overflow());
+    }
+    Ok(index as usize)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_zero_under_each_convention() {
+        assert!(to_column_name(0, true).is_err());
+        assert_eq!(to_column_name(0, false), Ok("A".to_string()));
+        assert_eq!(from_column_name("A", false), Ok(0));
+        assert_eq!(from_column_name("A", true), Ok(1));
+    }
+
+    #[test]
+    fn conventions_differ_by_one() {
+        for index in 0..20_000 {
+            let name = to_column_name(index, false).unwrap();
+            assert_eq!(to_column_name(index + 1, true), Ok(name.clone()));
+            assert_eq!(from_column_name(&name, false), Ok(index));
+            assert_eq!(from_column_name(&name, true), Ok(index + 1));
+        }
+        assert_eq!(to_column_name(702, true), Ok("ZZ".to_string()));
+        assert_eq!(to_column_name(703, true), Ok("AAA".to_string()));
+    }
+
+    #[test]
+    fn usize_limits() {
+        for &one_based in [true, false].iter() {
+            let name = to_column_name(usize::MAX, one_based).unwrap();
+            assert_eq!(from_column_name(&name, one_based), Ok(usize::MAX));
+        }
+        // the 0-based name of usize::MAX is one past it when 1-based
+        let name = to_column_name(usize::MAX, false).unwrap();
+        assert!(from_column_name(&name, true).is_err());
+        assert!(from_column_name(&"Z".repeat(30), true).is_err());
+        assert_eq!(from_column_name("", true), Err(Error::SequenceEmpty));
+    }
+}
@@ -0,0 +1,121 @@
+use Error;
+
+
+/// Converts `numerator / denominator` to its (finite, regular) continued fraction coefficients.
+///
+/// It's the Euclidean algorithm, the first coefficient is the integer part
+/// and the rest describe the path in the Stern-Brocot tree. The last
+/// coefficient is never 1 (except for 1/1), so the representation is unique.
+/// Zero `denominator` (infinity) has no coefficients.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_continued_fraction;
+///
+/// assert_eq!(to_continued_fraction(355, 113), vec![3, 7, 16]);
+/// assert_eq!(to_continued_fraction(1, 3), vec![0, 3]);
+/// assert_eq!(to_continued_fraction(5, 1), vec![5]);
+/// ```
+pub fn to_continued_fraction(mut numerator: usize, mut denominator: usize) -> Vec<usize> {
+    let mut coeffs = Vec::new();
+    while denominator != 0 {
+        coeffs.push(numerator / denominator);
+        let rest = numerator % denominator;
+        numerator = denominator;
+        denominator = rest;
+    }
+    coeffs
+}
+
+
+/// Converts continued fraction `coeffs` back to a reduced `(numerator, denominator)`.
+///
+/// No coefficients means infinity, which is `(1, 0)`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_continued_fraction;
+///
+/// assert_eq!(from_continued_fraction(&[3, 7, 16]), Ok((355, 113)));
+/// assert_eq!(from_continued_fraction(&[0, 3]), Ok((1, 3)));
+/// assert_eq!(from_continued_fraction(&[]), Ok((1, 0)));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::Overflow` when the numerator or denominator exceeds `usize`
+///
+/// ```
+/// use numsys::from_continued_fraction;
+/// use numsys::Error;
+///
+/// let msg = "Convergent at coefficient 1 exceeds usize".to_string();
+/// assert_eq!(from_continued_fraction(&[usize::MAX, 2]), Err(Error::Overflow(msg)));
+/// ```
+pub fn from_continued_fraction(coeffs: &[usize]) -> Result<(usize, usize), Error> {
+    // convergents recurrence h(n) = a(n) * h(n - 1) + h(n - 2), same for k
+    let (mut h, mut h_prev) = (1usize, 0usize);
+    let (mut k, mut k_prev) = (0usize, 1usize);
+    for (index, coeff) in coeffs.iter().enumerate() {
+        let step = |last: usize, before: usize| {
+            coeff
+                .checked_mul(last)
+                .and_then(|v| v.checked_add(before))
+                .ok_or_else(|| Error::Overflow(format!("Convergent at coefficient {} exceeds usize", index)))
+        };
+        let next_h = step(h, h_prev)?;
+        let next_k = step(k, k_prev)?;
+        h_prev = h;
+        h = next_h;
+        k_prev = k;
+        k = next_k;
+    }
+    Ok((h, k))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fraction::gcd;
+    use testutil::XorShift;
+
+    #[test]
+    fn pi_approximations() {
+        assert_eq!(to_continued_fraction(22, 7), vec![3, 7]);
+        assert_eq!(to_continued_fraction(355, 113), vec![3, 7, 16]);
+        assert_eq!(to_continued_fraction(103_993, 33_102), vec![3, 7, 15, 1, 292]);
+        assert_eq!(from_continued_fraction(&[3, 7, 15, 1, 292]), Ok((103_993, 33_102)));
+        // unreduced input gives the same coefficients
+        assert_eq!(to_continued_fraction(710, 226), vec![3, 7, 16]);
+    }
+
+    #[test]
+    fn random_ratios_round_trip_reduced() {
+        let mut rng = XorShift::new(214);
+        for _ in 0..10_000 {
+            let numerator = rng.next_u64() as usize >> rng.below(64);
+            let denominator = (rng.next_u64() as usize >> rng.below(64)).max(1);
+            let coeffs = to_continued_fraction(numerator, denominator);
+            let divisor = gcd(numerator, denominator);
+            let expected = (numerator / divisor, denominator / divisor);
+            assert_eq!(from_continued_fraction(&coeffs), Ok(expected));
+        }
+        assert_eq!(to_continued_fraction(usize::MAX, 1), vec![usize::MAX]);
+        assert_eq!(to_continued_fraction(0, 5), vec![0]);
+        assert_eq!(to_continued_fraction(7, 0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn from_continued_fraction_overflow() {
+        let overflow = |index: usize| Err(Error::Overflow(format!("Convergent at coefficient {} exceeds usize", index)));
+        assert_eq!(from_continued_fraction(&[usize::MAX, 2]), overflow(1));
+        assert_eq!(from_continued_fraction(&[usize::MAX, 1]), overflow(1));
+        assert_eq!(from_continued_fraction(&[usize::MAX]), Ok((usize::MAX, 1)));
+        // Fibonacci convergents, F(93) is the first one past u64
+        assert_eq!(from_continued_fraction(&[1; 91]).map(|(h, _)| h), Ok(7_540_113_804_746_346_429));
+        assert_eq!(from_continued_fraction(&[1; 93]), overflow(92));
+    }
+}
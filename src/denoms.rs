@@ -0,0 +1,287 @@
+use Error;
+
+
+/// Largest `value` accepted by `greedy_is_optimal`.
+pub const GREEDY_CHECK_LIMIT: usize = 1_000_000;
+
+fn validate_denoms(denominations: &[usize]) -> Result<(), Error> {
+    if denominations.is_empty() {
+        return Err(Error::DictEmpty);
+    }
+    if let Some(idx) = denominations.iter().position(|d| *d == 0) {
+        return Err(Error::InvalidDenominations(format!(
+            "Denominations MUST be nonzero, given 0 at index {} in {:?}",
+            idx,
+            denominations
+        )));
+    }
+    if let Some(idx) = denominations.windows(2).position(|w| w[0] <= w[1]) {
+        return Err(Error::InvalidDenominations(format!(
+            "Denominations MUST be strictly decreasing, given {} before {} in {:?}",
+            denominations[idx],
+            denominations[idx + 1],
+            denominations
+        )));
+    }
+    Ok(())
+}
+
+
+/// Splits `value` into counts per each of `denominations` using greedy algorithm.
+///
+/// Returns counts (in `denominations` order) and the remainder which can't
+/// be represented by any denomination.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::dec2denoms;
+///
+/// assert_eq!(dec2denoms(68, &[25, 10, 5, 1]), Ok((vec![2, 1, 1, 3], 0)));
+/// assert_eq!(dec2denoms(23, &[10, 5]), Ok((vec![2, 0], 3)));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `denominations` length is 0
+///
+/// * Returns `Error::InvalidDenominations` when `denominations` are not strictly decreasing or include 0
+///
+/// ```
+/// use numsys::dec2denoms;
+/// use numsys::Error;
+///
+/// let msg = "Denominations MUST be strictly decreasing, given 5 before 10 in [5, 10]".to_string();
+/// assert_eq!(dec2denoms(10, &[5, 10]), Err(Error::InvalidDenominations(msg)));
+/// ```
+pub fn dec2denoms(mut value: usize, denominations: &[usize]) -> Result<(Vec<usize>, usize), Error> {
+    validate_denoms(denominations)?;
+    let counts = denominations
+        .iter()
+        .map(|denom| {
+            let count = value / denom;
+            value %= denom;
+            count
+        })
+        .collect();
+    Ok((counts, value))
+}
+
+
+/// Converts `counts` of each of `denominations` back to a value.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::denoms2dec;
+///
+/// assert_eq!(denoms2dec(&[2, 1, 1, 3], &[25, 10, 5, 1]), Ok(68));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `denominations` length is 0
+///
+/// * Returns `Error::InvalidDenominations` when `denominations` are not strictly decreasing or include 0
+///
+/// * Returns `Error::LengthMismatch` when `counts` and `denominations` lengths differ
+///
+/// * Returns `Error::Overflow` when the value exceeds `usize`
+///
+/// ```
+/// use numsys::denoms2dec;
+/// use numsys::Error;
+///
+/// let max = usize::MAX;
+/// let msg = format!("Value of [2] in [{}] exceeds usize", max);
+/// assert_eq!(denoms2dec(&[2], &[max]), Err(Error::Overflow(msg)));
+/// ```
+pub fn denoms2dec(counts: &[usize], denominations: &[usize]) -> Result<usize, Error> {
+    validate_denoms(denominations)?;
+    if counts.len() != denominations.len() {
+        return Err(Error::LengthMismatch(format!(
+            "Counts MUST have {} elements, given {}",
+            denominations.len(),
+            counts.len()
+        )));
+    }
+    counts
+        .iter()
+        .zip(denominations)
+        .try_fold(0usize, |total, (count, denom)| {
+            count.checked_mul(*denom).and_then(|v| total.checked_add(v))
+        })
+        .ok_or_else(|| {
+            Error::Overflow(format!("Value of {:?} in {:?} exceeds usize", counts, denominations))
+        })
+}
+
+
+/// Tells whether `dec2denoms` result for `value` is the optimal one.
+///
+/// Greedy split is optimal when no other split leaves smaller remainder, or
+/// leaves the same remainder using fewer pieces. It's verified by dynamic
+/// programming over all values up to `value`, so it's limited to
+/// `GREEDY_CHECK_LIMIT`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::greedy_is_optimal;
+///
+/// assert_eq!(greedy_is_optimal(68, &[25, 10, 5, 1]), Ok(true));
+/// // greedy gives 4 + 1 + 1, while 3 + 3 needs fewer pieces
+/// assert_eq!(greedy_is_optimal(6, &[4, 3, 1]), Ok(false));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `denominations` length is 0
+///
+/// * Returns `Error::InvalidDenominations` when `denominations` are not strictly decreasing or include 0
+///
+/// * Returns `Error::OutOfRange` when `value` exceeds `GREEDY_CHECK_LIMIT`
+///
+/// ```
+/// use numsys::greedy_is_optimal;
+/// use numsys::Error;
+///
+/// let msg = "Value MUST be at most 1000000, given 1000001".to_string();
+/// assert_eq!(greedy_is_optimal(1_000_001, &[1]), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn greedy_is_optimal(value: usize, denominations: &[usize]) -> Result<bool, Error> {
+    let (counts, remainder) = dec2denoms(value, denominations)?;
+    if value > GREEDY_CHECK_LIMIT {
+        return Err(Error::OutOfRange(format!(
+            "Value MUST be at most {}, given {}",
+            GREEDY_CHECK_LIMIT,
+            value
+        )));
+    }
+    // pieces[t] is the least count of pieces summing exactly to t
+    let mut pieces: Vec<Option<usize>> = vec![None; value + 1];
+    pieces[0] = Some(0);
+    for total in 1..value + 1 {
+        pieces[total] = denominations
+            .iter()
+            .filter(|denom| **denom <= total)
+            .filter_map(|denom| pieces[total - denom])
+            .min()
+            .map(|count| count + 1);
+    }
+    let best = (0..value + 1)
+        .rev()
+        .filter_map(|total| pieces[total].map(|count| (value - total, count)))
+        .next()
+        .unwrap_or((value, 0));
+    let greedy = (remainder, counts.iter().sum::<usize>());
+    Ok(best >= greedy)
+}
+
+
+/// Splits `total` (in the base unit) into counts of units of `unit_factors` sizes.
+///
+/// It's a mixed radix representation, each radix being the ratio of
+/// successive unit sizes, so every unit size MUST divide the previous one.
+/// Parts smaller than the smallest unit are dropped, use factor 1 for the
+/// base unit to keep them. `denoms2dec` converts the counts back.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_units;
+///
+/// // km, m and cm in cm
+/// assert_eq!(to_units(1_234_567, &[100_000, 100, 1]), Ok(vec![12, 345, 67]));
+/// // hours, minutes and seconds in seconds
+/// assert_eq!(to_units(3_725, &[3_600, 60, 1]), Ok(vec![1, 2, 5]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `unit_factors` length is 0
+///
+/// * Returns `Error::InvalidDenominations` when `unit_factors` are not strictly decreasing, include 0 or don't divide each other
+///
+/// ```
+/// use numsys::to_units;
+/// use numsys::Error;
+///
+/// let msg = "Unit factors MUST divide each other, given 12 before 5 in [12, 5, 1]".to_string();
+/// assert_eq!(to_units(30, &[12, 5, 1]), Err(Error::InvalidDenominations(msg)));
+/// ```
+pub fn to_units(total: usize, unit_factors: &[usize]) -> Result<Vec<usize>, Error> {
+    validate_denoms(unit_factors)?;
+    if let Some(idx) = unit_factors.windows(2).position(|w| w[0] % w[1] != 0) {
+        return Err(Error::InvalidDenominations(format!(
+            "Unit factors MUST divide each other, given {} before {} in {:?}",
+            unit_factors[idx],
+            unit_factors[idx + 1],
+            unit_factors
+        )));
+    }
+    let (counts, _) = dec2denoms(total, unit_factors)?;
+    Ok(counts)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_coins_round_trip() {
+        let coins = [200, 100, 50, 20, 10, 5, 2, 1];
+        for value in 0..500 {
+            let (counts, remainder) = dec2denoms(value, &coins).unwrap();
+            assert_eq!(remainder, 0);
+            assert_eq!(denoms2dec(&counts, &coins), Ok(value));
+            assert_eq!(greedy_is_optimal(value, &coins), Ok(true));
+        }
+    }
+
+    #[test]
+    fn non_canonical_set_is_detected() {
+        let coins = [4, 3, 1];
+        assert_eq!(dec2denoms(6, &coins), Ok((vec![1, 0, 2], 0)));
+        assert_eq!(greedy_is_optimal(6, &coins), Ok(false));
+        assert_eq!(greedy_is_optimal(5, &coins), Ok(true));
+    }
+
+    #[test]
+    fn greedy_remainder_may_be_suboptimal() {
+        // greedy gives 4 leaving 2, while 3 + 3 leaves nothing
+        assert_eq!(dec2denoms(6, &[4, 3]), Ok((vec![1, 0], 2)));
+        assert_eq!(greedy_is_optimal(6, &[4, 3]), Ok(false));
+    }
+
+    #[test]
+    fn remainder_is_returned() {
+        assert_eq!(dec2denoms(23, &[10, 5]), Ok((vec![2, 0], 3)));
+        assert_eq!(dec2denoms(4, &[10, 5]), Ok((vec![0, 0], 4)));
+        assert_eq!(greedy_is_optimal(23, &[10, 5]), Ok(true));
+    }
+
+    #[test]
+    fn zero_denomination_is_rejected() {
+        let msg = "Denominations MUST be nonzero, given 0 at index 1 in [5, 0]".to_string();
+        assert_eq!(dec2denoms(1, &[5, 0]), Err(Error::InvalidDenominations(msg)));
+        assert_eq!(denoms2dec(&[1], &[5, 1]).unwrap_err(),
+                   Error::LengthMismatch("Counts MUST have 2 elements, given 1".to_string()));
+    }
+
+    #[test]
+    fn length_in_km_m_cm() {
+        let factors = [100_000, 100, 1];
+        assert_eq!(to_units(0, &factors), Ok(vec![0, 0, 0]));
+        assert_eq!(to_units(99, &factors), Ok(vec![0, 0, 99]));
+        assert_eq!(to_units(100_000, &factors), Ok(vec![1, 0, 0]));
+        let counts = to_units(4_200_305, &factors).unwrap();
+        assert_eq!(counts, vec![42, 3, 5]);
+        assert_eq!(denoms2dec(&counts, &factors), Ok(4_200_305));
+        // no base unit, centimeters are dropped
+        assert_eq!(to_units(4_200_305, &[100_000, 100]), Ok(vec![42, 3]));
+        assert!(to_units(1, &[100, 0]).is_err());
+        assert!(to_units(1, &[1, 100]).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use alphabet::case_folded_chars;
+use {Alphabet, CompatibilityReport, NumeralSystem};
+
+
+/// Relationship of two sequences found by `diagnose_pair`.
+///
+/// Systems are given by their index in the `systems` slice.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnosis {
+    /// Both sequences are the very same string.
+    Identical,
+    /// Same value of `system`, only the count of leading zero glyphs differs.
+    Padding { system: usize, value: usize },
+    /// Same value of `system` once case is ignored.
+    Case { system: usize, value: usize },
+    /// Same value, `a` of `a_system` and `b` of `b_system`. Compatibility of
+    /// their alphabets is `None` when either isn't a valid `Alphabet`.
+    Alphabet {
+        a_system: usize,
+        b_system: usize,
+        value: usize,
+        compatibility: Option<CompatibilityReport>,
+    },
+    /// Digits at `position` and `position + 1` of `system` are swapped.
+    Transposed { system: usize, position: usize },
+    /// Genuinely different values of `system`.
+    Different { system: usize, a: usize, b: usize },
+    /// Both are valid, but never in the same system nor with the same value.
+    Unrelated,
+    /// At least one of the sequences isn't valid in any of the systems.
+    Invalid { a_valid: bool, b_valid: bool },
+}
+
+
+/// Replaces each char of `sequence` with the glyph of its case insensitive value in `system`.
+fn fold_case(sequence: &str, system: &NumeralSystem) -> Option<String> {
+    let alphabet = system.alphabet();
+    let table = case_folded_chars(alphabet).ok()?;
+    sequence.chars().map(|glyph| table.get(&glyph).map(|value| alphabet[*value])).collect()
+}
+
+/// Returns index of the only pair of adjacent chars swapped between `a` and `b`.
+fn adjacent_transposition(a: &str, b: &str) -> Option<usize> {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    if a.len() != b.len() {
+        return None;
+    }
+    let differing: Vec<usize> = (0..a.len()).filter(|position| a[*position] != b[*position]).collect();
+    match differing[..] {
+        [first, second] if second == first + 1 && a[first] == b[second] && a[second] == b[first] => Some(first),
+        _ => None,
+    }
+}
+
+
+/// Explains why `a` and `b` differ, trying each of `systems`.
+///
+/// The first matching explanation wins, in the order of `Diagnosis`
+/// variants, and systems are tried in their order. Garbage input never
+/// panics, it's diagnosed as `Diagnosis::Invalid`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{diagnose_pair, Diagnosis, NumeralSystem};
+///
+/// let hex = NumeralSystem::new(&"0123456789ABCDEF".chars().collect::<Vec<_>>()).unwrap();
+/// let dec = NumeralSystem::new(&"0123456789".chars().collect::<Vec<_>>()).unwrap();
+/// let systems = [&hex, &dec];
+/// assert_eq!(diagnose_pair("00FF", "FF", &systems), Diagnosis::Padding { system: 0, value: 255 });
+/// assert_eq!(diagnose_pair("ff", "FF", &systems), Diagnosis::Case { system: 0, value: 255 });
+/// assert_eq!(diagnose_pair("1A3", "13A", &systems), Diagnosis::Transposed { system: 0, position: 1 });
+/// assert_eq!(diagnose_pair("FF", "F0", &systems), Diagnosis::Different { system: 0, a: 255, b: 240 });
+/// assert_eq!(diagnose_pair("FF", "F?", &systems), Diagnosis::Invalid { a_valid: true, b_valid: false });
+/// match diagnose_pair("FF", "255", &systems) {
+///     Diagnosis::Alphabet { a_system: 0, b_system: 1, value: 255, .. } => (),
+///     other => panic!("{:?}", other),
+/// }
+/// ```
+pub fn diagnose_pair(a: &str, b: &str, systems: &[&NumeralSystem]) -> Diagnosis {
+    if a == b {
+        return Diagnosis::Identical;
+    }
+    let parsed: Vec<(Option<usize>, Option<usize>)> =
+        systems.iter().map(|system| (system.parse(a).ok(), system.parse(b).ok())).collect();
+    for (system, values) in parsed.iter().enumerate() {
+        if let (Some(a_value), Some(b_value)) = *values {
+            if a_value == b_value {
+                return Diagnosis::Padding { system, value: a_value };
+            }
+        }
+    }
+    for (index, system) in systems.iter().enumerate() {
+        if let (Some(a_folded), Some(b_folded)) = (fold_case(a, system), fold_case(b, system)) {
+            if let (Ok(a_value), Ok(b_value)) = (system.parse(&a_folded), system.parse(&b_folded)) {
+                if a_value == b_value {
+                    return Diagnosis::Case { system: index, value: a_value };
+                }
+            }
+        }
+    }
+    for (a_system, &(a_value, _)) in parsed.iter().enumerate() {
+        for (b_system, &(_, b_value)) in parsed.iter().enumerate() {
+            let value = match a_value {
+                Some(value) if a_system != b_system && b_value == Some(value) => value,
+                _ => continue,
+            };
+            let compatibility = match (
+                Alphabet::new(systems[a_system].alphabet()),
+                Alphabet::new(systems[b_system].alphabet()),
+            ) {
+                (Ok(from), Ok(to)) => Some(from.compatibility(&to)),
+                _ => None,
+            };
+            return Diagnosis::Alphabet { a_system, b_system, value, compatibility };
+        }
+    }
+    let both_valid = parsed.iter().position(|values| values.0.is_some() && values.1.is_some());
+    if let Some(system) = both_valid {
+        if let Some(position) = adjacent_transposition(a, b) {
+            return Diagnosis::Transposed { system, position };
+        }
+        if let (Some(a_value), Some(b_value)) = parsed[system] {
+            return Diagnosis::Different { system, a: a_value, b: b_value };
+        }
+    }
+    let a_valid = parsed.iter().any(|values| values.0.is_some());
+    let b_valid = parsed.iter().any(|values| values.1.is_some());
+    if a_valid && b_valid {
+        Diagnosis::Unrelated
+    } else {
+        Diagnosis::Invalid { a_valid, b_valid }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use {DIGITS_UPPER_AZ, DIGITS_UPPER_LOWER_AZ};
+
+    fn system(alphabet: &[char]) -> NumeralSystem {
+        NumeralSystem::new(alphabet).unwrap()
+    }
+
+    #[test]
+    fn each_scenario_is_diagnosed() {
+        let (hex, dec, bin) = (system(&DIGITS_UPPER_AZ[..16]), system(&DIGITS_UPPER_AZ[..10]), system(&['0', '1']));
+        let systems = [&bin, &dec, &hex];
+        assert_eq!(diagnose_pair("42", "42", &systems), Diagnosis::Identical);
+        assert_eq!(diagnose_pair("0042", "42", &systems), Diagnosis::Padding { system: 1, value: 42 });
+        assert_eq!(diagnose_pair("0", "000", &systems), Diagnosis::Padding { system: 0, value: 0 });
+        assert_eq!(diagnose_pair("00ab", "AB", &systems), Diagnosis::Case { system: 2, value: 0xAB });
+        match diagnose_pair("101", "5", &systems) {
+            Diagnosis::Alphabet { a_system: 0, b_system: 1, value: 5, compatibility: Some(report) } => {
+                // same glyphs, but "10" isn't the same value in both
+                assert!(!report.safe);
+                assert_eq!(report.added.len(), 8);
+            }
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(diagnose_pair("12345", "12354", &systems), Diagnosis::Transposed { system: 1, position: 3 });
+        assert_eq!(diagnose_pair("21345", "12345", &systems), Diagnosis::Transposed { system: 1, position: 0 });
+        assert_eq!(diagnose_pair("12", "13", &systems), Diagnosis::Different { system: 1, a: 12, b: 13 });
+        assert_eq!(diagnose_pair("F", "9", &systems), Diagnosis::Different { system: 2, a: 15, b: 9 });
+        assert_eq!(diagnose_pair("", "?", &systems), Diagnosis::Invalid { a_valid: true, b_valid: false });
+        assert_eq!(diagnose_pair("x", "y", &systems), Diagnosis::Invalid { a_valid: false, b_valid: false });
+        assert_eq!(diagnose_pair("1", "2", &[]), Diagnosis::Invalid { a_valid: false, b_valid: false });
+        let (letters, digits) = (system(&['a', 'b']), system(&['0', '1']));
+        assert_eq!(diagnose_pair("ab", "10", &[&letters, &digits]), Diagnosis::Unrelated);
+    }
+
+    #[test]
+    fn transposition_detector() {
+        let dec = system(&DIGITS_UPPER_AZ[..10]);
+        // non-adjacent swaps and swaps of equal digits aren't transpositions
+        assert_eq!(diagnose_pair("13245", "15243", &[&dec]), Diagnosis::Different { system: 0, a: 13245, b: 15243 });
+        assert_eq!(diagnose_pair("1234", "4231", &[&dec]), Diagnosis::Different { system: 0, a: 1234, b: 4231 });
+        assert_eq!(diagnose_pair("1213", "2113", &[&dec]), Diagnosis::Transposed { system: 0, position: 0 });
+        assert_eq!(diagnose_pair("123", "1234", &[&dec]), Diagnosis::Different { system: 0, a: 123, b: 1234 });
+        // two adjacent transpositions are two mistakes
+        assert_eq!(diagnose_pair("1234", "2143", &[&dec]), Diagnosis::Different { system: 0, a: 1234, b: 2143 });
+        let wide = system(&['★', '☆', 'ż']);
+        assert_eq!(diagnose_pair("★☆żż", "☆★żż", &[&wide]), Diagnosis::Transposed { system: 0, position: 0 });
+    }
+
+    #[test]
+    fn case_needs_case_fold_safe_alphabet() {
+        let base62 = system(&DIGITS_UPPER_LOWER_AZ);
+        assert_eq!(diagnose_pair("a", "A", &[&base62]), Diagnosis::Different { system: 0, a: 36, b: 10 });
+        let padded = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap().with_width(4).unwrap();
+        assert_eq!(diagnose_pair("00ff", "FF", &[&padded]), Diagnosis::Case { system: 0, value: 255 });
+    }
+
+    #[test]
+    fn garbage_never_panics() {
+        let mut rng = XorShift::new(238);
+        let hex = system(&DIGITS_UPPER_AZ[..16]);
+        let narrow = NumeralSystem::new(&['0', '1']).unwrap().with_width(3).unwrap().with_max_value(5);
+        let glyphs = ['0', '1', 'f', 'F', 'ß', '\u{0}', '\u{10FFFF}', 'ǅ', '\u{130}', 'K'];
+        for _ in 0..3000 {
+            let mut pair = Vec::new();
+            for _ in 0..2 {
+                let length = rng.below(30);
+                pair.push((0..length).map(|_| glyphs[rng.below(glyphs.len())]).collect::<String>());
+            }
+            diagnose_pair(&pair[0], &pair[1], &[&hex, &narrow]);
+        }
+    }
+}
@@ -0,0 +1,1121 @@
+use std::collections::HashMap;
+
+use parse::accumulate;
+use {index_chars, positional_alphabet, Error, OverflowMode};
+
+
+pub(crate) fn check_radix(base: usize) -> Result<(), Error> {
+    if base < 2 {
+        return Err(Error::BaseTooSmall(
+            format!("Base MUST be 2 or higer, given {}", base),
+        ));
+    }
+    Ok(())
+}
+
+
+/// Returns floor of logarithm of `value` in `base`, with integer math only.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::ilog;
+///
+/// assert_eq!(ilog(255, 16), Ok(1));
+/// assert_eq!(ilog(256, 16), Ok(2));
+/// assert_eq!(ilog(1, 2), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::OutOfRange` when `value` is 0
+///
+/// ```
+/// use numsys::ilog;
+/// use numsys::Error;
+///
+/// let msg = "Value MUST be positive, given 0".to_string();
+/// assert_eq!(ilog(0, 10), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn ilog(value: usize, base: usize) -> Result<u32, Error> {
+    check_radix(base)?;
+    if value == 0 {
+        return Err(Error::OutOfRange("Value MUST be positive, given 0".into()));
+    }
+    Ok(largest_power_le(value, base)?.0)
+}
+
+
+/// Returns `base` to the power of `exp`, or `None` when it exceeds `usize`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::checked_pow_base;
+///
+/// assert_eq!(checked_pow_base(16, 2), Some(256));
+/// assert_eq!(checked_pow_base(2, 64), None);
+/// ```
+pub fn checked_pow_base(base: usize, exp: u32) -> Option<usize> {
+    base.checked_pow(exp)
+}
+
+
+/// Returns the biggest `(k, base^k)` such that `base^k <= value`.
+///
+/// Zero is treated as one, so it gives `(0, 1)`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::largest_power_le;
+///
+/// assert_eq!(largest_power_le(1000, 10), Ok((3, 1000)));
+/// assert_eq!(largest_power_le(999, 10), Ok((2, 100)));
+/// assert_eq!(largest_power_le(usize::MAX, 2), Ok((63, 1 << 63)));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// ```
+/// use numsys::largest_power_le;
+/// use numsys::Error;
+///
+/// let msg = "Base MUST be 2 or higer, given 1".to_string();
+/// assert_eq!(largest_power_le(10, 1), Err(Error::BaseTooSmall(msg)));
+/// ```
+pub fn largest_power_le(value: usize, base: usize) -> Result<(u32, usize), Error> {
+    check_radix(base)?;
+    let mut exp = 0;
+    let mut power: usize = 1;
+    // power <= value / base is power * base <= value, without overflow
+    while power <= value / base {
+        power *= base;
+        exp += 1;
+    }
+    Ok((exp, power))
+}
+
+
+/// Converts `decimal` to digit values of `base`, least significant first.
+///
+/// It's the natural output of the division loop, the building block of
+/// LEB128-like (continuation bit) encodings. Any `base` from 2 up is
+/// supported, as no glyphs are involved.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_leb_like;
+///
+/// assert_eq!(to_leb_like(300, 128), Ok(vec![44, 2]));
+/// assert_eq!(to_leb_like(6, 2), Ok(vec![0, 1, 1]));
+/// assert_eq!(to_leb_like(0, 10), Ok(vec![0]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// ```
+/// use numsys::to_leb_like;
+/// use numsys::Error;
+///
+/// let msg = "Base MUST be 2 or higer, given 1".to_string();
+/// assert_eq!(to_leb_like(10, 1), Err(Error::BaseTooSmall(msg)));
+/// ```
+pub fn to_leb_like(mut decimal: usize, base: usize) -> Result<Vec<usize>, Error> {
+    check_radix(base)?;
+    let mut digits = vec![decimal % base];
+    decimal /= base;
+    while decimal != 0 {
+        digits.push(decimal % base);
+        decimal /= base;
+    }
+    Ok(digits)
+}
+
+
+/// Converts `decimal` to digit values of `base`, most significant first.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::dec2digits;
+///
+/// assert_eq!(dec2digits(300, 128), Ok(vec![2, 44]));
+/// assert_eq!(dec2digits(255, 16), Ok(vec![15, 15]));
+/// assert_eq!(dec2digits(0, 16), Ok(vec![0]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn dec2digits(decimal: usize, base: usize) -> Result<Vec<usize>, Error> {
+    let mut digits = to_leb_like(decimal, base)?;
+    digits.reverse();
+    Ok(digits)
+}
+
+
+/// Returns count of `base` digits needed to enumerate `count` distinct values.
+///
+/// It's `ceil(log_base(count))`, computed with integer math only, so it's
+/// exact at powers of `base`. A single value needs no digits at all.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digits_for_count;
+///
+/// assert_eq!(digits_for_count(256, 16), Ok(2));
+/// assert_eq!(digits_for_count(257, 16), Ok(3));
+/// assert_eq!(digits_for_count(1, 16), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn digits_for_count(count: usize, base: usize) -> Result<usize, Error> {
+    check_radix(base)?;
+    if count <= 1 {
+        return Ok(0);
+    }
+    // the biggest value to enumerate is count - 1
+    Ok(largest_power_le(count - 1, base)?.0 as usize + 1)
+}
+
+
+/// Returns count of digits of `decimal` in `base`, zero has exactly one digit.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digit_count;
+///
+/// assert_eq!(digit_count(255, 16), Ok(2));
+/// assert_eq!(digit_count(256, 16), Ok(3));
+/// assert_eq!(digit_count(0, 16), Ok(1));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn digit_count(decimal: usize, base: usize) -> Result<usize, Error> {
+    Ok(largest_power_le(decimal, base)?.0 as usize + 1)
+}
+
+
+/// Returns `k`-th digit value of `decimal` in `base`, counted from the most significant one.
+///
+/// Only one power and one division are needed, the representation isn't built.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digit_at;
+///
+/// assert_eq!(digit_at(0xABC, 16, 0), Ok(0xA));
+/// assert_eq!(digit_at(0xABC, 16, 2), Ok(0xC));
+/// assert_eq!(digit_at(0, 16, 0), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::OutOfRange` when `k` isn't less than `digit_count`
+///
+/// ```
+/// use numsys::digit_at;
+/// use numsys::Error;
+///
+/// let msg = "Digit index MUST be less than 3, given 3".to_string();
+/// assert_eq!(digit_at(0xABC, 16, 3), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn digit_at(decimal: usize, base: usize, k: usize) -> Result<usize, Error> {
+    let count = digit_count(decimal, base)?;
+    if k >= count {
+        return Err(Error::OutOfRange(
+            format!("Digit index MUST be less than {}, given {}", count, k),
+        ));
+    }
+    digit_at_lsb(decimal, base, count - 1 - k)
+}
+
+
+/// Returns `k`-th digit value of `decimal` in `base`, counted from the least significant one.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digit_at_lsb;
+///
+/// assert_eq!(digit_at_lsb(0xABC, 16, 0), Ok(0xC));
+/// assert_eq!(digit_at_lsb(0xABC, 16, 2), Ok(0xA));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::OutOfRange` when `k` isn't less than `digit_count`
+pub fn digit_at_lsb(decimal: usize, base: usize, k: usize) -> Result<usize, Error> {
+    let count = digit_count(decimal, base)?;
+    if k >= count {
+        return Err(Error::OutOfRange(
+            format!("Digit index MUST be less than {}, given {}", count, k),
+        ));
+    }
+    // k < count, so base^k <= decimal (or k == 0) and can't overflow
+    let power = checked_pow_base(base, k as u32).expect("power within digit count");
+    Ok(decimal / power % base)
+}
+
+
+/// Converts `decimal` to decimal digit values, most significant first, zero padded to `digits`.
+///
+/// Made for driving seven-segment like displays, each value can index a
+/// segment table directly.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_display_nibbles;
+///
+/// assert_eq!(to_display_nibbles(42, 4), Ok(vec![0, 0, 4, 2]));
+/// assert_eq!(to_display_nibbles(1234, 4), Ok(vec![1, 2, 3, 4]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfRange` when `decimal` needs more than `digits` digits
+///
+/// ```
+/// use numsys::to_display_nibbles;
+/// use numsys::Error;
+///
+/// let msg = "Value 12345 MUST fit in 4 digits".to_string();
+/// assert_eq!(to_display_nibbles(12345, 4), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn to_display_nibbles(decimal: usize, digits: usize) -> Result<Vec<u8>, Error> {
+    let values = dec2digits(decimal, 10)?;
+    if values.len() > digits {
+        return Err(Error::OutOfRange(
+            format!("Value {} MUST fit in {} digits", decimal, digits),
+        ));
+    }
+    let mut nibbles = vec![0; digits - values.len()];
+    nibbles.extend(values.iter().map(|value| *value as u8));
+    Ok(nibbles)
+}
+
+
+/// Node of a singly linked list of digit values, built by `dec_to_digit_list`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigitNode {
+    /// Value of the digit.
+    pub digit: usize,
+    /// Next (less significant) digit.
+    pub next: Option<Box<DigitNode>>,
+}
+
+
+/// Converts `decimal` to a linked list of `base` digit values, most significant first.
+///
+/// Meant for teaching list algorithms on real data.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{dec_to_digit_list, DigitNode};
+///
+/// let list = dec_to_digit_list(0x1F, 16).unwrap();
+/// let last = DigitNode { digit: 15, next: None };
+/// assert_eq!(list, Some(Box::new(DigitNode { digit: 1, next: Some(Box::new(last)) })));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn dec_to_digit_list(decimal: usize, base: usize) -> Result<Option<Box<DigitNode>>, Error> {
+    let mut list = None;
+    // least significant digits come first, so pushing to the front keeps MSB first
+    for digit in to_leb_like(decimal, base)? {
+        list = Some(Box::new(DigitNode { digit, next: list }));
+    }
+    Ok(list)
+}
+
+
+/// Converts linked `list` of `base` digit values, most significant first, back to decimal.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{dec_to_digit_list, digit_list_to_dec};
+///
+/// let list = dec_to_digit_list(2024, 10).unwrap();
+/// assert_eq!(digit_list_to_dec(&list, 10), Ok(2024));
+/// assert_eq!(digit_list_to_dec(&None, 10), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::OutOfRange` when a digit isn't less than `base`
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn digit_list_to_dec(list: &Option<Box<DigitNode>>, base: usize) -> Result<usize, Error> {
+    check_radix(base)?;
+    let mut dec: usize = 0;
+    let mut node = list;
+    let mut index = 0;
+    while let Some(ref current) = *node {
+        if current.digit >= base {
+            return Err(Error::OutOfRange(format!(
+                "Digit at index {} MUST be less than {}, given {}",
+                index,
+                base,
+                current.digit
+            )));
+        }
+        dec = accumulate(dec, base, current.digit, OverflowMode::Error, || {
+            Error::Overflow(format!("Value of digit list exceeds usize at index {}", index))
+        })?;
+        node = &current.next;
+        index += 1;
+    }
+    Ok(dec)
+}
+
+
+/// Returns glyph of digit `value` in `char2val`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::glyph_of;
+///
+/// assert_eq!(glyph_of(10, &"0123456789X".chars().collect::<Vec<_>>()), Ok('X'));
+/// assert_eq!(glyph_of(2, &['★', '☆', 'ż']), Ok('ż'));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::OutOfRange` when `value` isn't less than `char2val` length
+///
+/// ```
+/// use numsys::glyph_of;
+/// use numsys::Error;
+///
+/// let msg = "Digit MUST be less than 2, given 2".to_string();
+/// assert_eq!(glyph_of(2, &['0', '1']), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn glyph_of(value: usize, char2val: &[char]) -> Result<char, Error> {
+    index_chars(char2val)?;
+    char2val
+        .get(value)
+        .cloned()
+        .ok_or_else(|| Error::OutOfRange(format!("Digit MUST be less than {}, given {}", char2val.len(), value)))
+}
+
+
+/// Returns digit value of `glyph` in `char2val`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::value_of;
+///
+/// assert_eq!(value_of('ż', &['★', '☆', 'ż']), Ok(2));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `glyph` isn't in `char2val`
+///
+/// ```
+/// use numsys::value_of;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(value_of('2', &['0', '1']), Err(Error::MissingChar(msg)));
+/// ```
+pub fn value_of(glyph: char, char2val: &[char]) -> Result<usize, Error> {
+    value_at(glyph, None, &index_chars(char2val)?, char2val)
+}
+
+
+/// Returns digit value of `glyph` like `value_of`, with `map` of `char2val` made once by `index_chars`.
+///
+/// It's the lookup of every decoding, `position` is where `glyph` is in
+/// the decoded sequence.
+pub(crate) fn value_at(glyph: char, position: Option<usize>, map: &HashMap<char, usize>, char2val: &[char])
+    -> Result<usize, Error> {
+    map.get(&glyph).cloned().ok_or_else(|| missing_char(glyph, position, char2val))
+}
+
+
+/// Returns `Error::MissingChar` of `glyph` at `position` not found in `char2val`, the one text of it.
+pub(crate) fn missing_char(glyph: char, position: Option<usize>, char2val: &[char]) -> Error {
+    let at = match position {
+        Some(position) => format!(" at position {}", position),
+        None => String::new(),
+    };
+    Error::MissingChar(format!("Char {:?}{} not found in: {:?}{}", glyph, at, char2val, case_hint(glyph, char2val)))
+}
+
+
+/// Returns `", did you mean 'A'?"` when `glyph` missing in `char2val` is there in the other case, otherwise nothing.
+///
+/// It's appended to every `Error::MissingChar` message by `missing_char`.
+fn case_hint(glyph: char, char2val: &[char]) -> String {
+    let mappings: [Vec<char>; 2] = [glyph.to_lowercase().collect(), glyph.to_uppercase().collect()];
+    // mappings to several chars (like 'ß' to "SS") can't be a single digit
+    let mut others = mappings.iter().filter(|mapped| mapped.len() == 1).map(|mapped| mapped[0]);
+    match others.find(|other| *other != glyph && char2val.contains(other)) {
+        Some(other) => format!(", did you mean {:?}?", other),
+        None => String::new(),
+    }
+}
+
+
+/// Returns glyphs of digit `values` in `char2val`, like `glyph_of` for each of them.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::glyphs_of;
+///
+/// assert_eq!(glyphs_of(&[1, 0, 2], &['★', '☆', 'ż']), Ok("☆★ż".to_string()));
+/// assert_eq!(glyphs_of(&[], &['0', '1']), Ok(String::new()));
+/// ```
+///
+/// # Errors
+///
+/// Same as `glyph_of`, `Error::OutOfRange` also names the index of the value.
+pub fn glyphs_of(values: &[usize], char2val: &[char]) -> Result<String, Error> {
+    index_chars(char2val)?;
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            char2val.get(*value).cloned().ok_or_else(|| {
+                Error::OutOfRange(format!(
+                    "Digit at index {} MUST be less than {}, given {}",
+                    index,
+                    char2val.len(),
+                    value
+                ))
+            })
+        })
+        .collect()
+}
+
+
+/// Returns digit values of all glyphs of `sequence` in `char2val`, like `value_of` for each of them.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::values_of;
+///
+/// assert_eq!(values_of("☆★ż", &['★', '☆', 'ż']), Ok(vec![1, 0, 2]));
+/// ```
+///
+/// # Errors
+///
+/// Same as `value_of`, `Error::MissingChar` also names the position of the glyph.
+pub fn values_of(sequence: &str, char2val: &[char]) -> Result<Vec<usize>, Error> {
+    let map = index_chars(char2val)?;
+    sequence
+        .chars()
+        .enumerate()
+        .map(|(position, glyph)| {
+            value_at(glyph, Some(position), &map, char2val)
+        })
+        .collect()
+}
+
+
+/// Converts digit values `digits`, most significant first, to a sequence of `char2val` glyphs.
+///
+/// No numeric conversion is made, so vectors of any length are fine.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::digits2seq;
+///
+/// assert_eq!(digits2seq(&[1, 0, 2], &['★', '☆', 'ż']), Ok("☆★ż".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::SequenceEmpty` when `digits` is empty
+///
+/// * Returns `Error::OutOfRange` when a digit isn't less than the base
+///
+/// ```
+/// use numsys::digits2seq;
+/// use numsys::Error;
+///
+/// let msg = "Digit at index 1 MUST be less than 2, given 2".to_string();
+/// assert_eq!(digits2seq(&[1, 2], &['0', '1']), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn digits2seq(digits: &[usize], char2val: &[char]) -> Result<String, Error> {
+    digits2seq_limited(digits, char2val, usize::MAX)
+}
+
+
+/// Converts `digits` like `digits2seq`, refusing more than `max_chars` digits.
+///
+/// Length is checked before anything else, so it's safe for untrusted input.
+///
+/// # Errors
+///
+/// * Returns `Error::InputTooLong` when `digits` has more than `max_chars` elements
+///
+/// ```
+/// use numsys::digits2seq_limited;
+/// use numsys::Error;
+///
+/// let msg = "Sequence MUST have at most 3 chars".to_string();
+/// assert_eq!(digits2seq_limited(&[1, 0, 1, 0], &['0', '1'], 3), Err(Error::InputTooLong(msg)));
+/// ```
+///
+/// * Returns the errors of `digits2seq` otherwise
+pub fn digits2seq_limited(digits: &[usize], char2val: &[char], max_chars: usize) -> Result<String, Error> {
+    if digits.len() > max_chars {
+        return Err(Error::InputTooLong(format!("Sequence MUST have at most {} chars", max_chars)));
+    }
+    positional_alphabet(char2val)?;
+    if digits.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    glyphs_of(digits, char2val)
+}
+
+
+/// Converts `sequence` to digit values of `char2val`, most significant first.
+///
+/// The inverse of `digits2seq`, no numeric conversion is made.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2digits;
+///
+/// assert_eq!(seq2digits("☆★ż", &['★', '☆', 'ż']), Ok(vec![1, 0, 2]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::SequenceEmpty` when `sequence` is empty
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+pub fn seq2digits(sequence: &str, char2val: &[char]) -> Result<Vec<usize>, Error> {
+    positional_alphabet(char2val)?;
+    if sequence.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    values_of(sequence, char2val)
+}
+
+
+/// Returns complement of `decimal` to the biggest `width` digits value of `base`.
+///
+/// It's `(base^width - 1) - decimal`, so complements of equal width
+/// sort in reverse, e.g. for reverse-chronological cursors. Applied
+/// twice it gives `decimal` back.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::invert_representation;
+///
+/// assert_eq!(invert_representation(0, 10, 3), Ok(999));
+/// assert_eq!(invert_representation(123, 10, 3), Ok(876));
+/// assert_eq!(invert_representation(0x0F, 16, 2), Ok(0xF0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::OutOfRange` when `decimal` doesn't fit `width` digits
+///
+/// ```
+/// use numsys::invert_representation;
+/// use numsys::Error;
+///
+/// let msg = "Value MUST be less than base^width (10^3), given 1000".to_string();
+/// assert_eq!(invert_representation(1000, 10, 3), Err(Error::OutOfRange(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when the complement exceeds `usize`
+pub fn invert_representation(decimal: usize, base: usize, width: usize) -> Result<usize, Error> {
+    check_radix(base)?;
+    let overflow = || {
+        Error::Overflow(format!("Complement of {} to {} digits of base {} exceeds usize", decimal, width, base))
+    };
+    // any width above 128 overflows `u128` too
+    let power = (base as u128).checked_pow(width.min(129) as u32).ok_or_else(overflow)?;
+    if decimal as u128 >= power {
+        return Err(Error::OutOfRange(format!(
+            "Value MUST be less than base^width ({}^{}), given {}",
+            base,
+            width,
+            decimal
+        )));
+    }
+    let complement = power - 1 - decimal as u128;
+    if complement > usize::MAX as u128 {
+        return Err(overflow());
+    }
+    Ok(complement as usize)
+}
+
+
+/// Returns the smallest value not less than `from` whose `base` representation is a palindrome.
+///
+/// The higher half of the digits is mirrored onto the lower one, and
+/// incremented once if that falls below `from`, so no values are scanned.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::next_palindrome;
+///
+/// assert_eq!(next_palindrome(123, 10), Ok(131));
+/// assert_eq!(next_palindrome(131, 10), Ok(131));
+/// assert_eq!(next_palindrome(999, 10), Ok(999));
+/// assert_eq!(next_palindrome(1000, 10), Ok(1001));
+/// // 0b1001
+/// assert_eq!(next_palindrome(8, 2), Ok(9));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::Overflow` when the palindrome exceeds `usize`
+pub fn next_palindrome(from: usize, base: usize) -> Result<usize, Error> {
+    let mut digits = dec2digits(from, base)?;
+    let length = digits.len();
+    let half = length.div_ceil(2);
+    let mirror = |digits: &mut Vec<usize>| {
+        for position in half..length {
+            digits[position] = digits[length - 1 - position];
+        }
+    };
+    mirror(&mut digits);
+    let overflow = || Error::Overflow(format!("Palindrome of base {} not less than {} exceeds usize", base, from));
+    let value = |digits: &[usize]| {
+        digits.iter().try_fold(0_usize, |dec, digit| dec.checked_mul(base)?.checked_add(*digit))
+    };
+    if let Some(palindrome) = value(&digits) {
+        if palindrome >= from {
+            return Ok(palindrome);
+        }
+    }
+    match digits[..half].iter().rposition(|digit| *digit != base - 1) {
+        Some(position) => {
+            digits[position] += 1;
+            for digit in &mut digits[position + 1..half] {
+                *digit = 0;
+            }
+            mirror(&mut digits);
+            value(&digits).ok_or_else(overflow)
+        }
+        // all the highest digits, the next palindrome is base^length + 1
+        None => checked_pow_base(base, length as u32)
+            .and_then(|power| power.checked_add(1))
+            .ok_or_else(overflow),
+    }
+}
+
+
+/// Returns count of steps `step` takes to map `decimal` to a single `base` digit.
+fn persistence<F: Fn(&[usize]) -> usize>(decimal: usize, base: usize, step: F) -> Result<usize, Error> {
+    check_radix(base)?;
+    let mut steps = 0;
+    let mut value = decimal;
+    // both sum and product of two digits or more are less than the value, so it ends
+    while value >= base {
+        value = step(&dec2digits(value, base)?);
+        steps += 1;
+    }
+    Ok(steps)
+}
+
+
+/// Returns multiplicative persistence of `decimal` in `base`, times its digits are multiplied until one is left.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::multiplicative_persistence;
+///
+/// // 39 -> 27 -> 14 -> 4
+/// assert_eq!(multiplicative_persistence(39, 10), Ok(3));
+/// assert_eq!(multiplicative_persistence(7, 10), Ok(0));
+/// assert_eq!(multiplicative_persistence(277_777_788_888_899, 10), Ok(11));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn multiplicative_persistence(decimal: usize, base: usize) -> Result<usize, Error> {
+    persistence(decimal, base, |digits| digits.iter().product())
+}
+
+
+/// Returns additive persistence of `decimal` in `base`, times its digits are summed until one is left.
+///
+/// The digit left is the digital root.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::additive_persistence;
+///
+/// // 199 -> 19 -> 10 -> 1
+/// assert_eq!(additive_persistence(199, 10), Ok(3));
+/// assert_eq!(additive_persistence(9, 10), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn additive_persistence(decimal: usize, base: usize) -> Result<usize, Error> {
+    persistence(decimal, base, |digits| digits.iter().sum())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use {decode, decode_tokens, decode_with_allowed, eq_seq, padic2int, parse_fields, seq2dec_with_options, seq2ratio,
+         switch_dec_base, OverflowMode, ParseOptions, DIGITS};
+
+    fn is_palindrome(value: usize, base: usize) -> bool {
+        let sequence = switch_dec_base(value, base).unwrap();
+        sequence.chars().rev().collect::<String>() == sequence
+    }
+
+    #[test]
+    fn persistence_records() {
+        // smallest numbers of base 10 with multiplicative persistence 1 to 11
+        let smallest =
+            [10, 25, 39, 77, 679, 6788, 68_889, 2_677_889, 26_888_999, 3_778_888_999, 277_777_788_888_899];
+        for (steps, value) in smallest.iter().enumerate() {
+            assert_eq!(multiplicative_persistence(*value, 10), Ok(steps + 1));
+            assert_eq!(multiplicative_persistence(*value - 1, 10).map(|p| p <= steps), Ok(true));
+        }
+        // binary digits multiply to 0 or 1 at once, 0b111 -> 0b11 -> 0b10 -> 0b1 when summed
+        assert_eq!(multiplicative_persistence(0b1011, 2), Ok(1));
+        assert_eq!(multiplicative_persistence(0b1, 2), Ok(0));
+        assert_eq!(additive_persistence(0b111, 2), Ok(3));
+        // 64 ones -> 0b1000000 -> 0b1
+        assert_eq!(additive_persistence(usize::MAX, 2), Ok(2));
+        // digit sum 87 -> 15 -> 6
+        assert_eq!(additive_persistence(usize::MAX, 10), Ok(3));
+        assert!(additive_persistence(10, 1).is_err());
+        assert!(multiplicative_persistence(10, 0).is_err());
+    }
+
+    #[test]
+    fn next_palindrome_matches_scanning() {
+        assert_eq!(next_palindrome(123, 10), Ok(131));
+        for base in 2..37 {
+            let mut expected = 0;
+            for from in 0..3000 {
+                if from > expected {
+                    expected = (from..).find(|value| is_palindrome(*value, base)).unwrap();
+                }
+                assert_eq!(next_palindrome(from, base), Ok(expected), "{} base {}", from, base);
+            }
+        }
+        let mut rng = XorShift::new(240);
+        for _ in 0..2000 {
+            let (from, base) = (rng.next_u64() as usize >> rng.below(64), 2 + rng.below(35));
+            match next_palindrome(from, base) {
+                Ok(palindrome) => {
+                    assert!(palindrome >= from && is_palindrome(palindrome, base));
+                    if palindrome - from < 1000 {
+                        assert!((from..palindrome).all(|value| !is_palindrome(value, base)));
+                    }
+                }
+                Err(Error::Overflow(_)) => assert!(from > usize::MAX / 2),
+                Err(other) => panic!("{:?}", other),
+            }
+        }
+        assert_eq!(next_palindrome(usize::MAX, 2), Ok(usize::MAX));
+        assert!(next_palindrome(usize::MAX, 10).is_err());
+        assert!(next_palindrome(1, 1).is_err());
+    }
+
+    #[test]
+    fn decodings_report_missing_chars_alike() {
+        let msg = format!("Char 'x' at position 1 not found in: {:?}", &DIGITS[..]);
+        let expected = Err(Error::MissingChar(msg));
+        let any: Vec<usize> = (0..10).collect();
+        assert_eq!(values_of("1x", &DIGITS).map(|_| 0), expected);
+        assert_eq!(decode("1x", &DIGITS, OverflowMode::Error), expected);
+        assert_eq!(seq2dec_with_options("1x", &DIGITS, &ParseOptions::default()), expected);
+        assert_eq!(decode_with_allowed("1x", &DIGITS, &[&any, &any]), expected);
+        assert_eq!(seq2ratio("1x", &DIGITS, '.').map(|_| 0), expected);
+        assert_eq!(parse_fields("1x", '.', &DIGITS).map(|_| 0), expected);
+        assert_eq!(decode_tokens("1x", &DIGITS, 2).map(|_| 0), expected);
+        assert_eq!(padic2int("1x", &DIGITS, false).map(|_| 0), expected);
+        assert_eq!(eq_seq("1x", &DIGITS, "1", &DIGITS).map(|_| 0), expected);
+    }
+
+    #[test]
+    fn decodings_suggest_the_other_case() {
+        let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+        let msg = format!("Char 'f' at position 1 not found in: {:?}, did you mean 'F'?", hex);
+        let expected = Err(Error::MissingChar(msg));
+        let any: Vec<usize> = (0..16).collect();
+        assert_eq!(values_of("Ff", &hex).map(|_| 0), expected);
+        assert_eq!(decode_with_allowed("Ff", &hex, &[&any, &any]), expected);
+        assert_eq!(seq2ratio("Ff", &hex, '.').map(|_| 0), expected);
+        assert_eq!(eq_seq("Ff", &hex, "1", &hex).map(|_| 0), expected);
+        let msg = format!("Char 'f' not found in: {:?}, did you mean 'F'?", hex);
+        assert_eq!(value_of('f', &hex), Err(Error::MissingChar(msg)));
+        assert_eq!(case_hint('ß', &['S', 's']), "");
+        assert_eq!(case_hint('Ω', &['ω']), ", did you mean 'ω'?");
+    }
+
+    #[test]
+    fn single_digit_glyphs_and_values() {
+        let alphabet = ['★', '☆', 'ż', '𝟘'];
+        for (value, glyph) in alphabet.iter().enumerate() {
+            assert_eq!(glyph_of(value, &alphabet), Ok(*glyph));
+            assert_eq!(value_of(*glyph, &alphabet), Ok(value));
+        }
+        assert_eq!(glyphs_of(&[3, 2, 1, 0], &alphabet), Ok("𝟘ż☆★".to_string()));
+        assert_eq!(values_of("𝟘ż☆★", &alphabet), Ok(vec![3, 2, 1, 0]));
+        // a single glyph alphabet is fine for single digits
+        assert_eq!(glyph_of(0, &['x']), Ok('x'));
+        let msg = "Digit MUST be less than 4, given 4".to_string();
+        assert_eq!(glyph_of(4, &alphabet), Err(Error::OutOfRange(msg)));
+        let msg = "Digit at index 1 MUST be less than 4, given 9".to_string();
+        assert_eq!(glyphs_of(&[0, 9], &alphabet), Err(Error::OutOfRange(msg)));
+        let msg = "Char 'x' at position 1 not found in: ['★', '☆', 'ż', '𝟘']".to_string();
+        assert_eq!(values_of("★x", &alphabet), Err(Error::MissingChar(msg)));
+        assert!(value_of('x', &alphabet).is_err());
+        assert_eq!(glyph_of(0, &[]), Err(Error::DictEmpty));
+        assert_eq!(value_of('x', &[]), Err(Error::DictEmpty));
+        for duplicated in [&['ż', '★', 'ż'][..], &['a', 'a']].iter() {
+            match (glyph_of(0, duplicated), value_of('a', duplicated), glyphs_of(&[], duplicated)) {
+                (Err(Error::MultipleChar(_)), Err(Error::MultipleChar(_)), Err(Error::MultipleChar(_))) => (),
+                other => panic!("{:?} gave {:?}", duplicated, other),
+            }
+        }
+    }
+
+    #[test]
+    fn to_leb_like_is_reversed_dec2digits() {
+        for base in 2..300 {
+            for decimal in (0..1_000_000).step_by(9_973).chain(vec![usize::MAX]) {
+                let mut digits = dec2digits(decimal, base).unwrap();
+                digits.reverse();
+                assert_eq!(to_leb_like(decimal, base), Ok(digits));
+            }
+        }
+    }
+
+    #[test]
+    fn to_leb_like_matches_unsigned_leb128_groups() {
+        // 624485 is the LEB128 example: 0xE5 0x8E 0x26
+        let groups = to_leb_like(624_485, 128).unwrap();
+        assert_eq!(groups, vec![0x65, 0x0E, 0x26]);
+    }
+
+    #[test]
+    fn digits_for_count_at_powers_of_base() {
+        assert_eq!(digits_for_count(0, 10), Ok(0));
+        assert_eq!(digits_for_count(10, 10), Ok(1));
+        assert_eq!(digits_for_count(11, 10), Ok(2));
+        assert_eq!(digits_for_count(usize::MAX, 2), Ok(64));
+        assert_eq!(digits_for_count(usize::MAX, 16), Ok(16));
+        for base in 2..40 {
+            let mut power: usize = base;
+            let mut exp = 1;
+            while let Some(next) = power.checked_mul(base) {
+                assert_eq!(digits_for_count(power, base), Ok(exp));
+                assert_eq!(digits_for_count(power + 1, base), Ok(exp + 1));
+                if power - 1 > power / base {
+                    assert_eq!(digits_for_count(power - 1, base), Ok(exp));
+                }
+                power = next;
+                exp += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn digit_at_agrees_with_dec2digits() {
+        let mut rng = XorShift::new(211);
+        for base in 2..37 {
+            let mut samples = vec![0, 1, base - 1, base, base + 1, usize::MAX, usize::MAX / base];
+            samples.extend((0..300).map(|_| rng.next_u64() as usize >> rng.below(64)));
+            for decimal in samples {
+                let digits = dec2digits(decimal, base).unwrap();
+                assert_eq!(digit_count(decimal, base), Ok(digits.len()));
+                for (k, digit) in digits.iter().enumerate() {
+                    assert_eq!(digit_at(decimal, base, k), Ok(*digit));
+                    assert_eq!(digit_at_lsb(decimal, base, digits.len() - 1 - k), Ok(*digit));
+                }
+                assert!(digit_at(decimal, base, digits.len()).is_err());
+                assert!(digit_at_lsb(decimal, base, digits.len()).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn display_nibbles_padding_and_overflow() {
+        assert_eq!(to_display_nibbles(42, 4), Ok(vec![0, 0, 4, 2]));
+        assert_eq!(to_display_nibbles(0, 3), Ok(vec![0, 0, 0]));
+        assert_eq!(to_display_nibbles(9999, 4), Ok(vec![9, 9, 9, 9]));
+        assert!(to_display_nibbles(10_000, 4).is_err());
+        // zero still needs its one digit
+        assert!(to_display_nibbles(0, 0).is_err());
+    }
+
+    #[test]
+    fn ilog_exact_around_every_power() {
+        for base in 2..37 {
+            assert_eq!(ilog(1, base), Ok(0));
+            assert_eq!(ilog(base - 1, base), Ok(0));
+            let mut exp = 1;
+            while let Some(power) = checked_pow_base(base, exp) {
+                assert_eq!(ilog(power - 1, base), Ok(exp - 1), "{}^{} - 1", base, exp);
+                assert_eq!(ilog(power, base), Ok(exp), "{}^{}", base, exp);
+                assert_eq!(largest_power_le(power, base), Ok((exp, power)));
+                assert_eq!(largest_power_le(power - 1, base).map(|(exp, _)| exp), Ok(exp - 1));
+                if let Some(next) = power.checked_add(1) {
+                    assert_eq!(ilog(next, base), Ok(exp), "{}^{} + 1", base, exp);
+                    assert_eq!(largest_power_le(next, base), Ok((exp, power)));
+                }
+                exp += 1;
+            }
+            // the last power fitting usize is the answer up to usize::MAX
+            assert_eq!(largest_power_le(usize::MAX, base), Ok((exp - 1, base.pow(exp - 1))));
+            assert_eq!(checked_pow_base(base, exp), None);
+        }
+        assert_eq!(largest_power_le(0, 10), Ok((0, 1)));
+        assert!(ilog(10, 1).is_err());
+        assert!(largest_power_le(10, 0).is_err());
+        assert!(largest_power_le(0, 1).is_err());
+    }
+
+    #[test]
+    fn digit_list_round_trip_and_length() {
+        let mut rng = XorShift::new(2202);
+        for base in 2..300 {
+            for _ in 0..20 {
+                let decimal = rng.next_u64() as usize >> rng.below(64);
+                let list = dec_to_digit_list(decimal, base).unwrap();
+                let mut length = 0;
+                let mut node = &list;
+                while let Some(ref current) = *node {
+                    length += 1;
+                    node = &current.next;
+                }
+                assert_eq!(Ok(length), digit_count(decimal, base));
+                assert_eq!(digit_list_to_dec(&list, base), Ok(decimal));
+            }
+        }
+        let bad = Some(Box::new(DigitNode { digit: 1, next: Some(Box::new(DigitNode { digit: 10, next: None })) }));
+        let msg = "Digit at index 1 MUST be less than 10, given 10".to_string();
+        assert_eq!(digit_list_to_dec(&bad, 10), Err(Error::OutOfRange(msg)));
+    }
+
+    #[test]
+    fn digit_vectors_round_trip_multi_byte_glyphs() {
+        let glyphs = ['★', '☆', 'ż', 'ł', '€'];
+        let mut rng = XorShift::new(2222);
+        for length in 1..200 {
+            let digits: Vec<usize> = (0..length).map(|_| rng.below(glyphs.len())).collect();
+            let seq = digits2seq(&digits, &glyphs).unwrap();
+            assert_eq!(seq.chars().count(), length);
+            assert_eq!(seq2digits(&seq, &glyphs), Ok(digits));
+        }
+    }
+
+    #[test]
+    fn digit_vectors_validation() {
+        let glyphs = ['★', '☆', 'ż'];
+        assert_eq!(digits2seq(&[], &glyphs), Err(Error::SequenceEmpty));
+        assert_eq!(seq2digits("", &glyphs), Err(Error::SequenceEmpty));
+        let msg = "Digit at index 2 MUST be less than 3, given 7".to_string();
+        assert_eq!(digits2seq(&[0, 1, 7], &glyphs), Err(Error::OutOfRange(msg)));
+        let msg = "Char 'x' at position 1 not found in: ['★', '☆', 'ż']".to_string();
+        assert_eq!(seq2digits("☆x", &glyphs), Err(Error::MissingChar(msg)));
+        assert!(digits2seq_limited(&vec![0; 1000], &glyphs, 999).is_err());
+        assert!(digits2seq_limited(&vec![0; 1000], &glyphs, 1000).is_ok());
+        assert_eq!(digits2seq(&[0], &['★']).unwrap_err(), Error::BaseTooSmall("Base MUST be 2 or higer, given 1".into()));
+        assert_eq!(digits2seq(&[0], &[]), Err(Error::DictEmpty));
+    }
+
+    #[test]
+    fn inverted_values_sort_in_reverse() {
+        assert_eq!(invert_representation(0, 10, 3), Ok(999));
+        assert_eq!(invert_representation(123, 10, 3), Ok(876));
+        assert_eq!(invert_representation(999, 10, 3), Ok(0));
+        assert_eq!(invert_representation(0, 10, 0), Ok(0));
+        let base36 = &::DIGITS_UPPER_AZ[..36];
+        let mut previous = None;
+        for value in (0..36 * 36 * 36).step_by(37) {
+            let inverted = invert_representation(value, 36, 3).unwrap();
+            assert_eq!(invert_representation(inverted, 36, 3), Ok(value));
+            let padded = format!("{:0>3}", ::dec2seq(inverted, base36).unwrap());
+            if let Some(previous) = previous {
+                assert!(padded < previous);
+            }
+            previous = Some(padded);
+        }
+    }
+
+    #[test]
+    fn invert_at_usize_limits() {
+        assert_eq!(invert_representation(0, 2, 64), Ok(usize::MAX));
+        assert_eq!(invert_representation(usize::MAX, 16, 16), Ok(0));
+        // 10^20 - 1 - usize::MAX is still above usize::MAX
+        assert!(invert_representation(usize::MAX, 10, 20).is_err());
+        assert!(invert_representation(0, 10, 20).is_err());
+        assert!(invert_representation(0, 2, 1000).is_err());
+        assert!(invert_representation(2, 2, 1).is_err());
+        assert!(invert_representation(0, 1, 1).is_err());
+    }
+}
@@ -0,0 +1,406 @@
+use digits::value_at;
+use parse::{accumulate, value_overflow};
+use {index_chars, positional_alphabet, switch_dec_base, Error, OverflowMode};
+
+
+/// Greatest common divisor of `a` and `b`.
+pub(crate) fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    a
+}
+
+/// Converts `numerator / denominator` to the nearest `f64` (ties to even).
+///
+/// Quotient bits are produced by exact long division, so the result is
+/// rounded only once.
+fn ratio2f64(numerator: usize, denominator: usize) -> f64 {
+    if numerator == 0 {
+        return 0.0;
+    }
+    let denominator = denominator as u128;
+    let mut quotient = numerator as u128 / denominator;
+    let mut remainder = numerator as u128 % denominator;
+    let mut exponent: i32 = 0;
+    while quotient < 1 << 54 {
+        quotient <<= 1;
+        remainder <<= 1;
+        if remainder >= denominator {
+            remainder -= denominator;
+            quotient += 1;
+        }
+        exponent -= 1;
+    }
+    let shift = 128 - quotient.leading_zeros() - 53;
+    let dropped = quotient & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let mut mantissa = quotient >> shift;
+    if dropped > half || (dropped == half && (remainder != 0 || mantissa & 1 == 1)) {
+        mantissa += 1;
+    }
+    mantissa as f64 * 2f64.powi(exponent + shift as i32)
+}
+
+
+/// Converts `sequence` with radix `point` to a reduced fraction using `char2val` translation.
+///
+/// Returns `(numerator, denominator)`, integer part is folded into numerator.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2ratio;
+///
+/// assert_eq!(seq2ratio("101.011", &['0', '1'], '.'), Ok((43, 8)));
+/// assert_eq!(seq2ratio("0.5", &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'], '.'), Ok((1, 2)));
+/// assert_eq!(seq2ratio("BA", &['A', 'B'], ','), Ok((2, 1)));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::InvalidPoint` when `point` is in `char2val`, is repeated, or isn't between digits
+///
+/// ```
+/// use numsys::seq2ratio;
+/// use numsys::Error;
+///
+/// let msg = "Point \'.\' at position 3 repeated, first at 1".to_string();
+/// assert_eq!(seq2ratio("1.0.1", &['0', '1'], '.'), Err(Error::InvalidPoint(msg)));
+///
+/// let msg = "Point \'.\' at position 0 MUST be between digits".to_string();
+/// assert_eq!(seq2ratio(".1", &['0', '1'], '.'), Err(Error::InvalidPoint(msg)));
+///
+/// let msg = "Point \'1\' MUST NOT be in: [\'0\', \'1\']".to_string();
+/// assert_eq!(seq2ratio("101", &['0', '1'], '1'), Err(Error::InvalidPoint(msg)));
+/// ```
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// ```
+/// use numsys::seq2ratio;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' at position 2 not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(seq2ratio("1.2", &['0', '1'], '.'), Err(Error::MissingChar(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when numerator or denominator exceeds `usize`
+pub fn seq2ratio(sequence: &str, char2val: &[char], point: char) -> Result<(usize, usize), Error> {
+    let map = index_chars(char2val)?;
+    let base = char2val.len();
+    if base < 2 {
+        return Err(Error::BaseTooSmall(
+            format!("Base MUST be 2 or higer, given {}", base),
+        ));
+    }
+    if map.contains_key(&point) {
+        return Err(Error::InvalidPoint(
+            format!("Point {:?} MUST NOT be in: {:?}", point, char2val),
+        ));
+    }
+    let length = sequence.chars().count();
+    let overflow = || value_overflow(sequence);
+    let mut numerator: usize = 0;
+    let mut denominator: usize = 1;
+    let mut point_at = None;
+    for (position, glyph) in sequence.chars().enumerate() {
+        if glyph == point {
+            if let Some(first) = point_at {
+                return Err(Error::InvalidPoint(format!(
+                    "Point {:?} at position {} repeated, first at {}",
+                    point,
+                    position,
+                    first
+                )));
+            }
+            if position == 0 || position + 1 == length {
+                return Err(Error::InvalidPoint(format!(
+                    "Point {:?} at position {} MUST be between digits",
+                    point,
+                    position
+                )));
+            }
+            point_at = Some(position);
+            continue;
+        }
+        let digit = value_at(glyph, Some(position), &map, char2val)?;
+        numerator = accumulate(numerator, base, digit, OverflowMode::Error, overflow)?;
+        if point_at.is_some() {
+            denominator = denominator.checked_mul(base).ok_or_else(&overflow)?;
+        }
+    }
+    let divisor = gcd(numerator, denominator);
+    Ok((numerator / divisor, denominator / divisor))
+}
+
+
+/// Converts `sequence` with radix `point` to `f64` using `char2val` translation.
+///
+/// The value is computed exactly by `seq2ratio` and then rounded once to
+/// the nearest `f64` (ties to even), so no error accumulates per digit.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2f64;
+///
+/// let dec = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+/// assert_eq!(seq2f64("101.011", &['0', '1'], '.'), Ok(5.375));
+/// assert_eq!(seq2f64("0.1", &dec, '.'), Ok(0.1));
+/// ```
+///
+/// # Errors
+///
+/// Same as `seq2ratio`. The value is a ratio of `usize` values, so it's always finite.
+pub fn seq2f64(sequence: &str, char2val: &[char], point: char) -> Result<f64, Error> {
+    let (numerator, denominator) = seq2ratio(sequence, char2val, point)?;
+    Ok(ratio2f64(numerator, denominator))
+}
+
+
+/// Converts integer `sequence` to an approximate `f64` using `char2val` translation.
+///
+/// Meant for magnitudes of sequences too long for `usize`. Digits are
+/// accumulated exactly while the value fits in `u64`, then in floating point,
+/// so the relative error is at most `2 * n * f64::EPSILON` for `n` digits
+/// (values within `u64` are correctly rounded).
+///
+/// Returns `Ok(f64::INFINITY)` for values past the `f64` range (`f64::MAX`,
+/// about 1.8e308), so unlike `seq2f64` the result isn't always
+/// finite; check it with `f64::is_finite` when that matters.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2f64_approx;
+///
+/// let dec = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+/// assert_eq!(seq2f64_approx("255", &dec), Ok(255.0));
+/// let googol = format!("1{}", "0".repeat(100));
+/// let approx = seq2f64_approx(&googol, &dec).unwrap();
+/// assert!((approx - 1e100).abs() / 1e100 <= 2.0 * 101.0 * f64::EPSILON);
+/// let huge = "9".repeat(400);
+/// assert_eq!(seq2f64_approx(&huge, &dec), Ok(f64::INFINITY));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// ```
+/// use numsys::seq2f64_approx;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' at position 1 not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(seq2f64_approx("12", &['0', '1']), Err(Error::MissingChar(msg)));
+/// ```
+///
+/// Values past the `f64` range aren't an error, they give `Ok(f64::INFINITY)`.
+pub fn seq2f64_approx(sequence: &str, char2val: &[char]) -> Result<f64, Error> {
+    let map = positional_alphabet(char2val)?;
+    let base = char2val.len();
+    let mut exact: Option<u64> = Some(0);
+    let mut approx = 0.0;
+    for (position, glyph) in sequence.chars().enumerate() {
+        let digit = value_at(glyph, Some(position), &map, char2val)?;
+        if let Some(value) = exact {
+            exact = value.checked_mul(base as u64).and_then(|v| v.checked_add(digit as u64));
+            if exact.is_none() {
+                approx = value as f64;
+            }
+        }
+        if exact.is_none() {
+            approx = approx * base as f64 + digit as f64;
+        }
+    }
+    Ok(exact.map_or(approx, |value| value as f64))
+}
+
+
+/// Converts `numerator / denominator` to `"num/den"` with both parts in `base`, like `switch_dec_base`.
+///
+/// The ratio is shown as given, not reduced.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::ratio_in_base;
+///
+/// assert_eq!(ratio_in_base(10, 3, 16), Ok("A/3".to_string()));
+/// assert_eq!(ratio_in_base(4, 8, 2), Ok("100/1000".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::OutOfRange` when `denominator` is 0
+///
+/// ```
+/// use numsys::ratio_in_base;
+/// use numsys::Error;
+///
+/// let msg = "Denominator MUST NOT be 0".to_string();
+/// assert_eq!(ratio_in_base(1, 0, 10), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn ratio_in_base(numerator: usize, denominator: usize, base: usize) -> Result<String, Error> {
+    let numerator = switch_dec_base(numerator, base)?;
+    if denominator == 0 {
+        return Err(Error::OutOfRange("Denominator MUST NOT be 0".into()));
+    }
+    Ok(format!("{}/{}", numerator, switch_dec_base(denominator, base)?))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arith::{add_in_base, mul_in_base};
+    use testutil::XorShift;
+    use {dec2seq, DIGITS, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn ratio_bar_in_base() {
+        assert_eq!(ratio_in_base(10, 3, 16), Ok("A/3".to_string()));
+        assert_eq!(ratio_in_base(0, 1, 2), Ok("0/1".to_string()));
+        assert_eq!(ratio_in_base(usize::MAX, 36, 36), Ok("3W5E11264SGSF/10".to_string()));
+        assert!(ratio_in_base(10, 0, 16).is_err());
+        assert!(ratio_in_base(10, 3, 37).is_err());
+    }
+
+    /// Renders terminating `numerator / denominator` with `frac_len` fractional digits.
+    fn ratio2seq(numerator: usize, denominator: usize, char2val: &[char], frac_len: usize) -> String {
+        let base = char2val.len();
+        let int = dec2seq(numerator / denominator, char2val).unwrap();
+        let int = if int.is_empty() { char2val[0].to_string() } else { int };
+        if frac_len == 0 {
+            return int;
+        }
+        let mut rest = (numerator % denominator) as u128;
+        let mut frac = String::new();
+        for _ in 0..frac_len {
+            rest *= base as u128;
+            frac.push(char2val[(rest / denominator as u128) as usize]);
+            rest %= denominator as u128;
+        }
+        assert_eq!(rest, 0, "expansion doesn't terminate");
+        format!("{}.{}", int, frac)
+    }
+
+    #[test]
+    fn round_trips_terminating_expansions() {
+        for base in 2..37 {
+            let char2val = &DIGITS_UPPER_AZ[..base];
+            for frac_len in 0..4 {
+                let denominator = base.pow(frac_len as u32);
+                for numerator in (0..5000).step_by(7) {
+                    let seq = ratio2seq(numerator, denominator, char2val, frac_len);
+                    let divisor = gcd(numerator, denominator);
+                    let expected = (numerator / divisor, denominator / divisor);
+                    assert_eq!(seq2ratio(&seq, char2val, '.'), Ok(expected), "{}", seq);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seq2f64_matches_std_decimal_parsing() {
+        let samples = [
+            "0.1",
+            "0.3333333333333333333",
+            "12345678901234567.89",
+            "9007199254740993.0",
+            "9007199254740995.0",
+            "0.0000000000000000001",
+            "1844674407370955161.5",
+            "2.5",
+            "7.0",
+        ];
+        for sample in samples.iter() {
+            let expected: f64 = sample.parse().unwrap();
+            assert_eq!(seq2f64(sample, &DIGITS, '.'), Ok(expected), "{}", sample);
+        }
+    }
+
+    #[test]
+    fn seq2f64_rounds_binary_exactly() {
+        let seq = format!("1.{}1", "0".repeat(51));
+        assert_eq!(seq2f64(&seq, &['0', '1'], '.'), Ok(1.0 + 2f64.powi(-52)));
+        // exact ties round to even
+        let seq = format!("1.{}1", "0".repeat(52));
+        assert_eq!(seq2f64(&seq, &['0', '1'], '.'), Ok(1.0));
+        let seq = format!("1.{}11", "0".repeat(51));
+        assert_eq!(seq2f64(&seq, &['0', '1'], '.'), Ok(1.0 + 2f64.powi(-51)));
+    }
+
+    #[test]
+    fn trailing_point_and_overflow_are_errors() {
+        let msg = "Point '.' at position 1 MUST be between digits".to_string();
+        assert_eq!(seq2ratio("1.", &DIGITS, '.'), Err(Error::InvalidPoint(msg)));
+        let seq = format!("1.{}", "0".repeat(30));
+        match seq2ratio(&seq, &DIGITS, '.') {
+            Err(Error::Overflow(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn seq2f64_approx_is_exact_within_u64() {
+        let mut rng = XorShift::new(2142);
+        for base in 2..37 {
+            for _ in 0..300 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let seq = dec2seq(value, &DIGITS_UPPER_AZ[..base]).unwrap();
+                assert_eq!(seq2f64_approx(&seq, &DIGITS_UPPER_AZ[..base]), Ok(value as f64));
+            }
+        }
+        assert_eq!(seq2f64_approx("", &DIGITS), Ok(0.0));
+    }
+
+    #[test]
+    fn seq2f64_approx_of_100_digits_within_bound() {
+        let mut rng = XorShift::new(2143);
+        for _ in 0..20 {
+            let seq: String = (0..100).map(|_| DIGITS_UPPER_AZ[rng.below(36)]).collect();
+            // exact decimal reference by digit string arithmetic, rounded once by std
+            let mut reference = "0".to_string();
+            for glyph in seq.chars() {
+                reference = mul_in_base(&reference, "36", &DIGITS).unwrap();
+                let digit = DIGITS_UPPER_AZ.iter().position(|g| *g == glyph).unwrap();
+                reference = add_in_base(&reference, &digit.to_string(), &DIGITS).unwrap().0;
+            }
+            let reference: f64 = reference.parse().unwrap();
+            let approx = seq2f64_approx(&seq, &DIGITS_UPPER_AZ).unwrap();
+            let bound = 2.0 * 100.0 * f64::EPSILON;
+            assert!((approx - reference).abs() / reference <= bound, "{}", seq);
+        }
+    }
+
+    #[test]
+    fn seq2f64_approx_past_f64_range_is_infinity() {
+        let seq = "Z".repeat(300);
+        assert_eq!(seq2f64_approx(&seq, &DIGITS_UPPER_AZ), Ok(f64::INFINITY));
+        let seq = format!("{}1", "0".repeat(1000));
+        assert_eq!(seq2f64_approx(&seq, &DIGITS_UPPER_AZ), Ok(1.0));
+        let msg = format!("Char '!' at position 300 not found in: {:?}", &DIGITS_UPPER_AZ[..]);
+        let seq = format!("{}!", "Z".repeat(300));
+        assert_eq!(seq2f64_approx(&seq, &DIGITS_UPPER_AZ), Err(Error::MissingChar(msg)));
+    }
+}
@@ -0,0 +1,225 @@
+use digits::missing_char;
+use parse::{accumulate, value_overflow};
+use {check_base, Error, OverflowMode, DIGITS_UPPER_AZ};
+
+
+/// Separator between groups of `mint_grouped_checked`.
+const GROUP_SEPARATOR: char = '-';
+
+/// Check digit of group `digits`, sum of their values modulo `base`.
+fn group_check(digits: &[usize], base: usize) -> usize {
+    digits.iter().fold(0, |sum, digit| (sum + digit) % base)
+}
+
+fn check_group_size(group_size: usize) -> Result<(), Error> {
+    if group_size == 0 {
+        return Err(Error::OutOfRange("Group size MUST be at least 1, given 0".into()));
+    }
+    Ok(())
+}
+
+
+/// Digit values of `decimal` in `base`, most significant first, for `DIGITS_UPPER_AZ` glyphs.
+pub(crate) fn digit_values(mut decimal: usize, base: usize) -> Result<Vec<usize>, Error> {
+    check_base(base)?;
+    let mut digits = vec![decimal % base];
+    decimal /= base;
+    while decimal != 0 {
+        digits.push(decimal % base);
+        decimal /= base;
+    }
+    digits.reverse();
+    Ok(digits)
+}
+
+
+/// Joins `glyphs` in groups of `group_size` counted from the start, 0 keeps them whole.
+pub(crate) fn join_groups(glyphs: &[char], group_size: usize, separator: char) -> String {
+    if group_size == 0 {
+        return glyphs.iter().collect();
+    }
+    let mut joined = String::with_capacity(glyphs.len() + glyphs.len() / group_size);
+    for (index, group) in glyphs.chunks(group_size).enumerate() {
+        if index != 0 {
+            joined.push(separator);
+        }
+        joined.extend(group);
+    }
+    joined
+}
+
+
+/// Value of `glyph` at `position` of a sequence, a digit of `alphabet`.
+pub(crate) fn digit_at(glyph: char, position: usize, alphabet: &[char]) -> Result<usize, Error> {
+    alphabet.iter().position(|g| *g == glyph).ok_or_else(|| missing_char(glyph, Some(position), alphabet))
+}
+
+
+/// Fails when check digit `found` of `checked` isn't `expected`.
+pub(crate) fn verify_check(found: usize, expected: usize, alphabet: &[char], checked: &str) -> Result<(), Error> {
+    if found != expected {
+        return Err(Error::ChecksumMismatch(
+            format!("Check digit {:?} of {} MUST be {:?}", alphabet[found], checked, alphabet[expected]),
+        ));
+    }
+    Ok(())
+}
+
+
+/// Value of `digits` in `base`, most significant first, overflow naming `sequence`.
+pub(crate) fn digits_value(digits: &[usize], base: usize, sequence: &str) -> Result<usize, Error> {
+    digits
+        .iter()
+        .try_fold(0_usize, |n, digit| accumulate(n, base, *digit, OverflowMode::Error, || value_overflow(sequence)))
+}
+
+
+/// Converts `decimal` to `base` split in groups of `group_size` digits, each with its own check digit.
+///
+/// The most significant group is zero padded, groups are joined with `-`
+/// and each of them ends with the sum of its digits modulo `base`. So any
+/// single mistyped digit is detected and localized to its group by
+/// `parse_grouped_checked`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::mint_grouped_checked;
+///
+/// assert_eq!(mint_grouped_checked(1_234_567, 10, 3), Ok("0011-2349-5678".to_string()));
+/// assert_eq!(mint_grouped_checked(0xABCD, 16, 2), Ok("AB5-CD9".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::OutOfRange` when `group_size` is 0
+pub fn mint_grouped_checked(decimal: usize, base: usize, group_size: usize) -> Result<String, Error> {
+    check_group_size(group_size)?;
+    let digits = digit_values(decimal, base)?;
+    let padding = (group_size - digits.len() % group_size) % group_size;
+    let mut padded = vec![0; padding];
+    padded.extend(digits);
+    let glyphs: Vec<char> = padded
+        .chunks(group_size)
+        .flat_map(|group| group.iter().cloned().chain(Some(group_check(group, base))))
+        .map(|digit| DIGITS_UPPER_AZ[digit])
+        .collect();
+    Ok(join_groups(&glyphs, group_size + 1, GROUP_SEPARATOR))
+}
+
+
+/// Converts `sequence` minted by `mint_grouped_checked` back to decimal, verifying each group.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::parse_grouped_checked;
+///
+/// assert_eq!(parse_grouped_checked("0011-2349-5678", 10, 3), Ok(1_234_567));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::OutOfRange` when `group_size` is 0
+///
+/// * Returns `Error::LengthMismatch` when a group hasn't `group_size` digits and a check digit
+///
+/// * Returns `Error::MissingChar` when a group includes a char which isn't a digit of `base`
+///
+/// * Returns `Error::ChecksumMismatch` when check digit of a group doesn't match its digits
+///
+/// ```
+/// use numsys::parse_grouped_checked;
+/// use numsys::Error;
+///
+/// let msg = "Check digit \'8\' of group 1 MUST be \'9\'".to_string();
+/// assert_eq!(parse_grouped_checked("0011-2348-5678", 10, 3), Err(Error::ChecksumMismatch(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn parse_grouped_checked(sequence: &str, base: usize, group_size: usize) -> Result<usize, Error> {
+    check_base(base)?;
+    check_group_size(group_size)?;
+    let alphabet = &DIGITS_UPPER_AZ[..base];
+    let mut payload = Vec::with_capacity(sequence.len());
+    for (index, group) in sequence.split(GROUP_SEPARATOR).enumerate() {
+        let glyphs: Vec<char> = group.chars().collect();
+        if glyphs.len() != group_size + 1 {
+            return Err(Error::LengthMismatch(format!(
+                "Group {} MUST have {} chars, given {}",
+                index,
+                group_size + 1,
+                glyphs.len()
+            )));
+        }
+        let digits = glyphs
+            .iter()
+            .enumerate()
+            // groups before this one have the right length, so positions count in `sequence`
+            .map(|(position, glyph)| digit_at(*glyph, index * (group_size + 2) + position, alphabet))
+            .collect::<Result<Vec<_>, _>>()?;
+        let expected = group_check(&digits[..group_size], base);
+        verify_check(digits[group_size], expected, alphabet, &format!("group {}", index))?;
+        payload.extend_from_slice(&digits[..group_size]);
+    }
+    digits_value(&payload, base, sequence)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    #[test]
+    fn grouped_round_trip() {
+        let mut rng = XorShift::new(219);
+        for base in 2..37 {
+            for group_size in 1..6 {
+                for _ in 0..50 {
+                    let value = rng.next_u64() as usize >> rng.below(64);
+                    let minted = mint_grouped_checked(value, base, group_size).unwrap();
+                    assert!(minted.split('-').all(|group| group.len() == group_size + 1));
+                    assert_eq!(parse_grouped_checked(&minted, base, group_size), Ok(value));
+                }
+            }
+        }
+        assert_eq!(mint_grouped_checked(0, 10, 4), Ok("00000".to_string()));
+        assert_eq!(mint_grouped_checked(usize::MAX, 16, 4), Ok("FFFFC-FFFFC-FFFFC-FFFFC".to_string()));
+    }
+
+    #[test]
+    fn corrupted_group_is_localized() {
+        let minted = mint_grouped_checked(0xDEAD_BEEF, 16, 4).unwrap();
+        assert_eq!(minted, "DEAD2-BEEF6");
+        let msg = "Check digit '6' of group 1 MUST be '5'".to_string();
+        assert_eq!(parse_grouped_checked("DEAD2-BEEE6", 16, 4), Err(Error::ChecksumMismatch(msg)));
+        // every single digit substitution is caught
+        for position in (0..minted.len()).filter(|p| *p != 5) {
+            for glyph in DIGITS_UPPER_AZ[..16].iter() {
+                let mut corrupted: Vec<char> = minted.chars().collect();
+                if corrupted[position] == *glyph {
+                    continue;
+                }
+                corrupted[position] = *glyph;
+                let corrupted: String = corrupted.into_iter().collect();
+                match parse_grouped_checked(&corrupted, 16, 4) {
+                    Err(Error::ChecksumMismatch(msg)) => {
+                        assert!(msg.contains(&format!("group {}", position / 6)))
+                    }
+                    other => panic!("{} gave {:?}", corrupted, other),
+                }
+            }
+        }
+        assert!(parse_grouped_checked("DEAD2-BEEF", 16, 4).is_err());
+        assert!(parse_grouped_checked("dead2-BEEF6", 16, 4).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use digits::value_at;
+use {positional_alphabet, Error};
+
+
+/// Precomputed digit regrouping between bases `b` and `b^k`.
+///
+/// Every `k` digits of the source base make exactly one digit of the
+/// target base. The digits of a group are looked up one by one in a
+/// table built by `new`, a trie whose last level gives the target digit,
+/// so long sequences are converted with table lookups only, in linear time.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::GroupingTable;
+///
+/// let bin = ['0', '1'];
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let table = GroupingTable::new(&bin, &hex).unwrap();
+/// assert_eq!(table.group_size(), 4);
+/// assert_eq!(table.convert("111111110"), Ok("1FE".to_string()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GroupingTable {
+    from: Vec<char>,
+    char2val: HashMap<char, usize>,
+    to: Vec<char>,
+    group_size: usize,
+    // node `n` maps a digit to the next node, nodes of a group's last digit to the target digit
+    trie: Vec<Vec<usize>>,
+    // node reached after `j` leading zeros, where partial groups start
+    padded: Vec<usize>,
+}
+
+impl GroupingTable {
+    /// Creates table converting from `from_alpha` to `to_alpha`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when an alphabet length is 0
+    ///
+    /// * Returns `Error::BaseTooSmall` when an alphabet length is 1
+    ///
+    /// * Returns `Error::MultipleChar` when an alphabet includes duplicated chars
+    ///
+    /// * Returns `Error::IncompatibleBases` when target base isn't a power of source base
+    ///
+    /// ```
+    /// use numsys::{Error, GroupingTable};
+    ///
+    /// let dec: Vec<char> = "0123456789".chars().collect();
+    /// let msg = "Base 10 MUST be a power of 2, convert via arithmetic (e.g. `convert_fixed_width`)".to_string();
+    /// assert_eq!(GroupingTable::new(&['0', '1'], &dec).unwrap_err(), Error::IncompatibleBases(msg));
+    /// ```
+    pub fn new(from_alpha: &[char], to_alpha: &[char]) -> Result<GroupingTable, Error> {
+        let char2val = positional_alphabet(from_alpha)?;
+        positional_alphabet(to_alpha)?;
+        let (from_base, to_base) = (from_alpha.len(), to_alpha.len());
+        let mut power = from_base;
+        let mut group_size = 1;
+        while power < to_base {
+            power = power.saturating_mul(from_base);
+            group_size += 1;
+        }
+        if power != to_base {
+            return Err(Error::IncompatibleBases(format!(
+                "Base {} MUST be a power of {}, convert via arithmetic (e.g. `convert_fixed_width`)",
+                to_base,
+                from_base
+            )));
+        }
+        // prefixes of the groups at the current depth, one node each
+        let mut prefixes = vec![0];
+        let mut trie: Vec<Vec<usize>> = Vec::new();
+        for depth in 0..group_size {
+            let last = depth + 1 == group_size;
+            let children = trie.len() + prefixes.len();
+            let mut next = Vec::with_capacity(if last { 0 } else { prefixes.len() * from_base });
+            for (index, prefix) in prefixes.iter().enumerate() {
+                trie.push(
+                    (0..from_base)
+                        .map(|digit| if last { prefix * from_base + digit } else { children + index * from_base + digit })
+                        .collect(),
+                );
+                if !last {
+                    next.extend((0..from_base).map(|digit| prefix * from_base + digit));
+                }
+            }
+            prefixes = next;
+        }
+        let mut padded = vec![0];
+        while padded.len() < group_size {
+            let node = trie[padded[padded.len() - 1]][0];
+            padded.push(node);
+        }
+        Ok(GroupingTable {
+            from: from_alpha.to_vec(),
+            char2val,
+            to: to_alpha.to_vec(),
+            group_size,
+            trie,
+            padded,
+        })
+    }
+
+    /// Returns count of source digits making one target digit.
+    pub fn group_size(&self) -> usize {
+        self.group_size
+    }
+
+    /// Converts `sequence` of the source alphabet to the target alphabet.
+    ///
+    /// The leading partial group is padded with zeros, and leading zeros
+    /// of the result are skipped (one zero is kept for a nonempty `sequence`).
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::MissingChar` when the source alphabet missing a char or more
+    pub fn convert(&self, sequence: &str) -> Result<String, Error> {
+        let digits = sequence
+            .chars()
+            .enumerate()
+            .map(|(position, glyph)| value_at(glyph, Some(position), &self.char2val, &self.from))
+            .collect::<Result<Vec<_>, _>>()?;
+        let partial = digits.len() % self.group_size;
+        let mut groups = Vec::with_capacity(digits.len() / self.group_size + 1);
+        if partial != 0 {
+            groups.push(&digits[..partial]);
+        }
+        groups.extend(digits[partial..].chunks(self.group_size));
+        let converted: Vec<char> = groups
+            .iter()
+            .map(|group| {
+                let start = self.padded[self.group_size - group.len()];
+                self.to[group.iter().fold(start, |node, digit| self.trie[node][*digit])]
+            })
+            .collect();
+        let first = converted
+            .iter()
+            .position(|glyph| *glyph != self.to[0])
+            .unwrap_or_else(|| converted.len().saturating_sub(1));
+        Ok(converted[first..].iter().collect())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use {dec2seq, DIGITS_UPPER_LOWER_AZ};
+
+    #[test]
+    fn agrees_with_arithmetic_path() {
+        let mut rng = XorShift::new(2192);
+        for &(from, to) in [(2, 16), (3, 27), (7, 49), (2, 32), (6, 36), (5, 5)].iter() {
+            let table = GroupingTable::new(&DIGITS_UPPER_LOWER_AZ[..from], &DIGITS_UPPER_LOWER_AZ[..to]).unwrap();
+            for _ in 0..1000 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let source = dec2seq(value, &DIGITS_UPPER_LOWER_AZ[..from]).unwrap();
+                let converted = table.convert(&source).unwrap();
+                let expected = dec2seq(value, &DIGITS_UPPER_LOWER_AZ[..to]).unwrap();
+                if value == 0 {
+                    assert_eq!(converted, "");
+                } else {
+                    assert_eq!(converted, expected, "{} in base {}", source, from);
+                }
+                // leading zeros of the source don't show in the result
+                let padded = format!("00{}", source);
+                let expected = if value == 0 { "0".to_string() } else { expected };
+                assert_eq!(table.convert(&padded), Ok(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn long_sequence_is_converted_by_lookups() {
+        let table = GroupingTable::new(&DIGITS_UPPER_LOWER_AZ[..3], &DIGITS_UPPER_LOWER_AZ[..27]).unwrap();
+        let source: String = "12".repeat(50_000);
+        let converted = table.convert(&source).unwrap();
+        // "1" and then "212" (23) and "121" (16) alternating
+        assert_eq!(converted.len(), 33_334);
+        assert!(converted.starts_with("1NGNG"));
+        assert!(GroupingTable::new(&DIGITS_UPPER_LOWER_AZ[..3], &DIGITS_UPPER_LOWER_AZ[..26]).is_err());
+        assert!(table.convert("123").is_err());
+    }
+
+    #[test]
+    fn convert_reads_the_table() {
+        let mut table = GroupingTable::new(&['0', '1'], &DIGITS_UPPER_LOWER_AZ[..16]).unwrap();
+        // root, 2 nodes of the second digit, 4 of the third and 8 of the last one
+        assert_eq!(table.trie.len(), 15);
+        assert_eq!(table.padded, vec![0, 1, 3, 7]);
+        assert_eq!(table.convert("11110101"), Ok("F5".to_string()));
+        // the digits come from the table, not from arithmetic on the group
+        let last = table.trie.len() - 1;
+        table.trie[last].swap(0, 1);
+        assert_eq!(table.convert("11110101"), Ok("E5".to_string()));
+        assert_eq!(table.convert("1111"), Ok("E".to_string()));
+        assert_eq!(table.convert("111"), Ok("7".to_string()));
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use {dec2seq, positional_alphabet, Error};
+
+
+/// Counters of an `InterningEncoder`, see `InterningEncoder::stats`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InternStats {
+    /// Count of values interned now (at most the capacity)
+    pub unique: usize,
+    /// Count of encodings served from the interner
+    pub hits: usize,
+    /// Count of encodings which had to be converted
+    pub misses: usize,
+}
+
+impl InternStats {
+    /// Returns share of encodings served from the interner, 0 before any encoding.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / total as f64
+    }
+}
+
+
+#[derive(Debug)]
+struct Interned {
+    // sequence and whether it was used since the last eviction pass over it
+    sequences: HashMap<usize, (Arc<str>, bool)>,
+    // eviction queue, the next candidate first
+    order: VecDeque<usize>,
+    hits: usize,
+    misses: usize,
+}
+
+
+/// Encoder sharing one `Arc<str>` for all encodings of the same value.
+///
+/// Useful when few values are encoded very often, each repeated value costs
+/// a reference count instead of a new `String`. At most `capacity` values are
+/// kept, so memory stays bounded whatever the input. When it's full, values
+/// are evicted in order of interning, but a value used since it was last
+/// considered gets a second chance (CLOCK policy), so frequent values stay.
+/// Encoder is `Sync`, workers can share it.
+///
+/// Sequences are the same as `dec2seq` ones.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::InterningEncoder;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let encoder = InterningEncoder::new(&hex, 100).unwrap();
+/// let first = encoder.encode(255);
+/// let second = encoder.encode(255);
+/// assert_eq!(&*first, "FF");
+/// assert!(std::sync::Arc::ptr_eq(&first, &second));
+/// assert_eq!(encoder.stats().hit_rate(), 0.5);
+/// ```
+#[derive(Debug)]
+pub struct InterningEncoder {
+    alphabet: Vec<char>,
+    capacity: usize,
+    interned: Mutex<Interned>,
+}
+
+impl InterningEncoder {
+    /// Creates encoder of `alphabet` keeping at most `capacity` values.
+    ///
+    /// Nothing is reserved up front, the map grows with the values interned
+    /// up to `capacity`, so a generous cap costs nothing until it's used.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when `alphabet` length is 0
+    ///
+    /// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+    ///
+    /// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+    pub fn new(alphabet: &[char], capacity: usize) -> Result<InterningEncoder, Error> {
+        positional_alphabet(alphabet)?;
+        Ok(InterningEncoder {
+            alphabet: alphabet.to_vec(),
+            capacity,
+            interned: Mutex::new(Interned {
+                sequences: HashMap::new(),
+                order: VecDeque::new(),
+                hits: 0,
+                misses: 0,
+            }),
+        })
+    }
+
+    /// Returns the most values kept at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Converts `value`, sharing the sequence with previous encodings of it.
+    pub fn encode(&self, value: usize) -> Arc<str> {
+        let mut interned = self.interned.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(&mut (ref sequence, ref mut used)) = interned.sequences.get_mut(&value) {
+            *used = true;
+            let sequence = sequence.clone();
+            interned.hits += 1;
+            return sequence;
+        }
+        interned.misses += 1;
+        let sequence: Arc<str> = dec2seq(value, &self.alphabet).expect("Alphabet is validated").into();
+        if self.capacity == 0 {
+            return sequence;
+        }
+        while interned.order.len() == self.capacity {
+            let candidate = interned.order.pop_front().expect("Capacity is not 0");
+            let used = interned.sequences.get_mut(&candidate).map(|entry| ::std::mem::replace(&mut entry.1, false));
+            if used == Some(true) {
+                interned.order.push_back(candidate);
+            } else {
+                interned.sequences.remove(&candidate);
+            }
+        }
+        interned.order.push_back(value);
+        interned.sequences.insert(value, (sequence.clone(), false));
+        sequence
+    }
+
+    /// Returns counters of interned values and encodings so far.
+    pub fn stats(&self) -> InternStats {
+        let interned = self.interned.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        InternStats {
+            unique: interned.sequences.len(),
+            hits: interned.hits,
+            misses: interned.misses,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use testutil::{allocations, XorShift};
+    use DIGITS_UPPER_LOWER_AZ;
+
+    // 9 of 10 values are 0..200, the rest spread over 0..100_000
+    fn skewed(rng: &mut XorShift) -> usize {
+        if rng.below(10) < 9 { rng.below(200) } else { rng.below(100_000) }
+    }
+
+    #[test]
+    fn same_sequences_as_dec2seq() {
+        let mut rng = XorShift::new(227);
+        for &base in [2, 10, 36, 62].iter() {
+            let alphabet = &DIGITS_UPPER_LOWER_AZ[..base];
+            for &capacity in [0, 1, 50].iter() {
+                let encoder = InterningEncoder::new(alphabet, capacity).unwrap();
+                for _ in 0..2000 {
+                    let value = skewed(&mut rng);
+                    assert_eq!(*encoder.encode(value), *dec2seq(value, alphabet).unwrap());
+                }
+                let stats = encoder.stats();
+                assert_eq!(stats.hits + stats.misses, 2000);
+                assert!(stats.unique <= capacity);
+            }
+        }
+        assert!(InterningEncoder::new(&['0', '0'], 10).is_err());
+    }
+
+    #[test]
+    fn map_grows_lazily_up_to_capacity() {
+        let before = allocations();
+        InterningEncoder::new(&DIGITS_UPPER_LOWER_AZ[..16], 1).unwrap();
+        let small = allocations() - before;
+        // as much as for the smallest cap, however big it is
+        let before = allocations();
+        let encoder = InterningEncoder::new(&DIGITS_UPPER_LOWER_AZ[..16], usize::MAX).unwrap();
+        assert_eq!(allocations() - before, small);
+        for value in 0..1000 {
+            encoder.encode(value % 10);
+        }
+        assert_eq!(encoder.stats(), InternStats { unique: 10, hits: 990, misses: 10 });
+        assert_eq!(encoder.capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn unused_value_is_evicted_first() {
+        let encoder = InterningEncoder::new(&['0', '1'], 2).unwrap();
+        let one = encoder.encode(1);
+        let two = encoder.encode(2);
+        assert!(Arc::ptr_eq(&one, &encoder.encode(1)));
+        // 1 was interned first, but used since
+        encoder.encode(3);
+        assert!(Arc::ptr_eq(&one, &encoder.encode(1)));
+        assert!(!Arc::ptr_eq(&two, &encoder.encode(2)));
+        let stats = encoder.stats();
+        assert_eq!(stats, InternStats { unique: 2, hits: 2, misses: 4 });
+        assert_eq!(stats.hit_rate(), 2.0 / 6.0);
+        assert_eq!(InterningEncoder::new(&['0', '1'], 2).unwrap().stats().hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn workers_share_one_interner() {
+        let encoder = InterningEncoder::new(&DIGITS_UPPER_LOWER_AZ[..16], 16).unwrap();
+        let shared = encoder.encode(7);
+        thread::scope(|scope| {
+            for seed in 1..5 {
+                let encoder = &encoder;
+                scope.spawn(move || {
+                    let mut rng = XorShift::new(seed);
+                    for _ in 0..1000 {
+                        let value = rng.below(16);
+                        assert_eq!(*encoder.encode(value), *dec2seq(value, &DIGITS_UPPER_LOWER_AZ[..16]).unwrap());
+                    }
+                });
+            }
+        });
+        assert!(Arc::ptr_eq(&shared, &encoder.encode(7)));
+        assert_eq!(encoder.stats().misses, 16);
+    }
+
+    #[test]
+    fn skewed_values_allocate_less_than_plain_path() {
+        let alphabet = &DIGITS_UPPER_LOWER_AZ[..36];
+        let mut rng = XorShift::new(2270);
+        let values: Vec<usize> = (0..10_000).map(|_| skewed(&mut rng)).collect();
+        let encoder = InterningEncoder::new(alphabet, 256).unwrap();
+        let mut kept = Vec::with_capacity(values.len());
+
+        let before = allocations();
+        for value in &values {
+            kept.push(encoder.encode(*value));
+        }
+        let interned = allocations() - before;
+        let before = allocations();
+        let plain: Vec<String> = values.iter().map(|value| dec2seq(*value, alphabet).unwrap()).collect();
+        let plain_allocations = allocations() - before;
+
+        assert!(encoder.stats().hit_rate() > 0.8, "{:?}", encoder.stats());
+        assert!(interned * 3 < plain_allocations, "{} vs {}", interned, plain_allocations);
+        assert!(kept.iter().zip(&plain).all(|(interned, plain)| **interned == **plain));
+    }
+}
@@ -0,0 +1,105 @@
+use Error;
+
+
+/// Returns remainder of decimal `digits` modulo 97, digit by digit so any length is fine.
+fn mod97(digits: &str) -> Result<u32, Error> {
+    if digits.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    digits.chars().enumerate().try_fold(0, |remainder, (position, glyph)| {
+        let digit = glyph.to_digit(10).ok_or_else(|| {
+            Error::MissingChar(format!("Char {:?} at position {} is not a decimal digit", glyph, position))
+        })?;
+        Ok((remainder * 10 + digit) % 97)
+    })
+}
+
+
+/// Computes ISO 7064 MOD 97-10 check digits (`02` to `98`) of decimal `digits`, as used by IBAN.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::iso7064_check;
+///
+/// // IBAN GB82 WEST 1234 5698 7654 32, rearranged with letters as numbers
+/// assert_eq!(iso7064_check("32142829123456987654321611"), Ok(82));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::SequenceEmpty` when `digits` is empty
+///
+/// * Returns `Error::MissingChar` when `digits` includes a char which isn't a decimal digit
+///
+/// ```
+/// use numsys::iso7064_check;
+/// use numsys::Error;
+///
+/// let msg = "Char \'W\' at position 0 is not a decimal digit".to_string();
+/// assert_eq!(iso7064_check("WEST"), Err(Error::MissingChar(msg)));
+/// ```
+pub fn iso7064_check(digits: &str) -> Result<u8, Error> {
+    // appending "00" multiplies by 100
+    let remainder = mod97(digits)? * 100 % 97;
+    Ok((98 - remainder) as u8)
+}
+
+
+/// Tells whether decimal `digits` end with valid ISO 7064 MOD 97-10 check digits.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::iso7064_verify;
+///
+/// assert_eq!(iso7064_verify("3214282912345698765432161182"), Ok(true));
+/// assert_eq!(iso7064_verify("3214282912345698765432161183"), Ok(false));
+/// ```
+///
+/// # Errors
+///
+/// Same as `iso7064_check`.
+pub fn iso7064_verify(digits: &str) -> Result<bool, Error> {
+    Ok(mod97(digits)? == 1)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    // IBAN digits: the first 4 chars moved to the end, letters as 10 (A) to 35 (Z)
+    fn iban_digits(iban: &str) -> String {
+        let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+        rearranged.chars().map(|glyph| glyph.to_digit(36).unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn known_iban_checks() {
+        for iban in ["GB82WEST12345698765432", "DE89370400440532013000", "NL91ABNA0417164300"] {
+            assert_eq!(iso7064_verify(&iban_digits(iban)), Ok(true), "{}", iban);
+            let unchecked = iban_digits(&format!("{}00{}", &iban[..2], &iban[4..]));
+            let check = iso7064_check(&unchecked[..unchecked.len() - 2]).unwrap();
+            assert_eq!(format!("{:02}", check), &iban[2..4]);
+        }
+        assert_eq!(iso7064_verify(&iban_digits("GB83WEST12345698765432")), Ok(false));
+    }
+
+    #[test]
+    fn appended_check_always_verifies() {
+        let mut rng = XorShift::new(233);
+        for _ in 0..1000 {
+            let digits: String = (0..1 + rng.below(60)).map(|_| (b'0' + rng.below(10) as u8) as char).collect();
+            let check = iso7064_check(&digits).unwrap();
+            assert!((2..=98).contains(&check));
+            assert_eq!(iso7064_verify(&format!("{}{:02}", digits, check)), Ok(true));
+            // any other check digits fail
+            let wrong = (check - 2 + 1) % 97 + 2;
+            assert_eq!(iso7064_verify(&format!("{}{:02}", digits, wrong)), Ok(false));
+        }
+        assert_eq!(iso7064_check(""), Err(Error::SequenceEmpty));
+        assert!(iso7064_verify("12 3").is_err());
+    }
+}
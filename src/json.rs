@@ -0,0 +1,431 @@
+use std::fmt;
+
+use digits::missing_char;
+use parse::echo;
+use {positional_alphabet, Error, DIGITS};
+
+
+/// Largest integer a JSON number keeps exactly in JavaScript, `2^53 - 1` (`Number.MAX_SAFE_INTEGER`).
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+
+/// JSON form of an integer given by `encode_json_safe`, its `Display` is the JSON text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaybeNumber {
+    /// Value at most `MAX_SAFE_INTEGER`, sent as a JSON number
+    Number(u64),
+    /// Digits of a larger value, sent as a JSON string
+    Text(String),
+}
+
+impl fmt::Display for MaybeNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MaybeNumber::Number(n) => write!(f, "{}", n),
+            MaybeNumber::Text(ref digits) => write!(f, "\"{}\"", digits),
+        }
+    }
+}
+
+
+/// Checks `char2val` is a positional alphabet whose chars need no escaping in JSON strings.
+fn json_alphabet(char2val: &[char]) -> Result<(), Error> {
+    positional_alphabet(char2val)?;
+    if let Some((index, glyph)) = char2val.iter().enumerate().find(|&(_, g)| *g == '"' || *g == '\\' || *g < ' ') {
+        return Err(Error::ForbiddenCodePoint(format!(
+            "Char {:?} at index {} MUST NOT need escaping in JSON strings",
+            glyph,
+            index
+        )));
+    }
+    Ok(())
+}
+
+
+/// Converts `value` to its JSON form: a number up to `MAX_SAFE_INTEGER`, a decimal string above it.
+///
+/// JavaScript reads JSON numbers as `f64`, which rounds integers above
+/// `2^53 - 1`, so those are sent as strings instead. With feature `serde`,
+/// `#[serde(with = "numsys::json_safe")]` applies it to fields.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{encode_json_safe, MaybeNumber};
+///
+/// assert_eq!(encode_json_safe(42), MaybeNumber::Number(42));
+/// assert_eq!(encode_json_safe(1 << 53).to_string(), "\"9007199254740992\"");
+/// assert_eq!(encode_json_safe(u64::MAX).to_string(), "\"18446744073709551615\"");
+/// ```
+pub fn encode_json_safe(value: u64) -> MaybeNumber {
+    encode_json_safe_with(value, &DIGITS).expect("DIGITS MUST be a JSON safe alphabet")
+}
+
+
+/// Converts `value` to its JSON form like `encode_json_safe`, with strings using `char2val` translation.
+///
+/// Numbers are always decimal, JSON has no other.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{encode_json_safe_with, MaybeNumber, DIGITS_UPPER_AZ};
+///
+/// let hex = &DIGITS_UPPER_AZ[..16];
+/// assert_eq!(encode_json_safe_with(255, hex), Ok(MaybeNumber::Number(255)));
+/// assert_eq!(encode_json_safe_with(u64::MAX, hex), Ok(MaybeNumber::Text("FFFFFFFFFFFFFFFF".to_string())));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::ForbiddenCodePoint` when `char2val` includes `"`, `\` or a control char
+///
+/// ```
+/// use numsys::encode_json_safe_with;
+/// use numsys::Error;
+///
+/// let msg = "Char \'\"\' at index 1 MUST NOT need escaping in JSON strings".to_string();
+/// assert_eq!(encode_json_safe_with(1 << 60, &['0', '"']), Err(Error::ForbiddenCodePoint(msg)));
+/// ```
+pub fn encode_json_safe_with(value: u64, char2val: &[char]) -> Result<MaybeNumber, Error> {
+    json_alphabet(char2val)?;
+    Ok(encode_checked(value as u128, char2val))
+}
+
+
+/// Converts `value` to its JSON form like `encode_json_safe`, for `u128` values.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{encode_json_safe_u128, MaybeNumber};
+///
+/// assert_eq!(encode_json_safe_u128(42), MaybeNumber::Number(42));
+/// assert_eq!(encode_json_safe_u128(u128::MAX).to_string(), "\"340282366920938463463374607431768211455\"");
+/// ```
+pub fn encode_json_safe_u128(value: u128) -> MaybeNumber {
+    encode_checked(value, &DIGITS)
+}
+
+
+/// Converts `value` to its JSON form, `char2val` being a JSON safe alphabet.
+pub(crate) fn encode_checked(value: u128, char2val: &[char]) -> MaybeNumber {
+    if value <= MAX_SAFE_INTEGER as u128 {
+        return MaybeNumber::Number(value as u64);
+    }
+    let base = char2val.len() as u128;
+    let mut rest = value;
+    let mut digits = Vec::new();
+    while rest != 0 {
+        digits.push(char2val[(rest % base) as usize]);
+        rest /= base;
+    }
+    MaybeNumber::Text(digits.iter().rev().collect())
+}
+
+
+/// Returns value of JSON number `json` when it's an integer `MAX_SAFE_INTEGER` at most.
+pub(crate) fn parse_number(json: &str) -> Result<u64, Error> {
+    let invalid = || Error::InvalidLiteral(format!("JSON value {:?} MUST be a number or a string", json));
+    let bytes = json.as_bytes();
+    if bytes.len() > 1 && bytes[0] == b'-' && bytes[1].is_ascii_digit() {
+        return Err(Error::OutOfRange(format!("Number {} MUST NOT be negative", json)));
+    }
+    let digits_from = |start: usize| start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let int_end = digits_from(0);
+    if int_end == 0 || (bytes[0] == b'0' && int_end > 1) {
+        return Err(invalid());
+    }
+    let mut end = int_end;
+    let mut fraction = "";
+    if bytes.get(end) == Some(&b'.') {
+        let frac_end = digits_from(end + 1);
+        if frac_end == end + 1 {
+            return Err(invalid());
+        }
+        fraction = &json[end + 1..frac_end];
+        end = frac_end;
+    }
+    let mut exponent: i64 = 0;
+    if bytes.get(end) == Some(&b'e') || bytes.get(end) == Some(&b'E') {
+        let sign = match bytes.get(end + 1) {
+            Some(&b'-') => -1,
+            _ => 1,
+        };
+        let start = if sign < 0 || bytes.get(end + 1) == Some(&b'+') { end + 2 } else { end + 1 };
+        if start > bytes.len() || digits_from(start) == start {
+            return Err(invalid());
+        }
+        end = digits_from(start);
+        // saturates far beyond any digit count, which is all that matters
+        exponent = json[start..end].bytes().fold(0_i64, |e, b| e.saturating_mul(10).saturating_add((b - b'0') as i64));
+        exponent *= sign;
+    }
+    if end != bytes.len() {
+        return Err(invalid());
+    }
+    // all digits with the decimal point moved by the exponent, leading zeros dropped
+    let all: Vec<u8> = json[..int_end].bytes().chain(fraction.bytes()).collect();
+    let zeros = all.iter().take_while(|b| **b == b'0').count();
+    let significant = &all[zeros..];
+    let point = (int_end as i64).saturating_add(exponent).saturating_sub(zeros as i64);
+    if significant.is_empty() {
+        return Ok(0);
+    }
+    let whole = point.max(0).min(significant.len() as i64) as usize;
+    if point <= 0 || significant[whole..].iter().any(|b| *b != b'0') {
+        return Err(Error::OutOfRange(format!("Number {} MUST have no fractional part", json)));
+    }
+    let too_big = || {
+        Error::OutOfRange(format!(
+            "Number {} MUST be at most 2^53 - 1 ({}), larger ones are sent as strings",
+            json,
+            MAX_SAFE_INTEGER
+        ))
+    };
+    // `MAX_SAFE_INTEGER` has 16 digits
+    if point > 16 {
+        return Err(too_big());
+    }
+    let value = significant[..whole]
+        .iter()
+        .fold(0_u64, |value, b| value * 10 + (b - b'0') as u64)
+        * 10_u64.pow((point as usize - whole) as u32);
+    if value > MAX_SAFE_INTEGER {
+        return Err(too_big());
+    }
+    Ok(value)
+}
+
+
+/// Returns value of JSON string contents `digits` using `char2val` translation, `max` at most.
+pub(crate) fn parse_text(digits: &str, char2val: &[char], max: u128, kind: &str) -> Result<u128, Error> {
+    if digits.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    if digits.contains('\\') {
+        return Err(Error::InvalidLiteral(format!("String {:?} MUST NOT contain escapes", digits)));
+    }
+    if digits.chars().count() > 1 && digits.starts_with(char2val[0]) {
+        return Err(Error::NonCanonical(format!("Sequence {} MUST NOT have leading zeros", echo(digits))));
+    }
+    let base = char2val.len() as u128;
+    let mut value: u128 = 0;
+    for (position, glyph) in digits.chars().enumerate() {
+        let digit =
+            char2val.iter().position(|g| *g == glyph).ok_or_else(|| missing_char(glyph, Some(position), char2val))?;
+        value = value
+            .checked_mul(base)
+            .and_then(|v| v.checked_add(digit as u128))
+            .filter(|v| *v <= max)
+            .ok_or_else(|| Error::Overflow(format!("Value of {} exceeds {}", echo(digits), kind)))?;
+    }
+    Ok(value)
+}
+
+
+/// Converts JSON value `json` given by `encode_json_safe` back to the integer.
+///
+/// Either form is accepted, a number or a decimal string, surrounded with
+/// JSON whitespace or not. A number above `MAX_SAFE_INTEGER` is rejected,
+/// since a JavaScript producer may have rounded it already; so is one with a
+/// fractional part. An integral number in other notation, like `1e3` or
+/// `2.50e1`, is fine.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_json_safe;
+///
+/// assert_eq!(decode_json_safe("42"), Ok(42));
+/// assert_eq!(decode_json_safe("\"42\""), Ok(42));
+/// assert_eq!(decode_json_safe("1e3"), Ok(1000));
+/// assert_eq!(decode_json_safe(" \"18446744073709551615\"\n"), Ok(u64::MAX));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InvalidLiteral` when `json` is neither JSON number nor string, or the string has escapes
+///
+/// * Returns `Error::OutOfRange` when the number is negative or has a fractional part
+///
+/// ```
+/// use numsys::decode_json_safe;
+/// use numsys::Error;
+///
+/// let msg = "Number 2.5 MUST have no fractional part".to_string();
+/// assert_eq!(decode_json_safe("2.5"), Err(Error::OutOfRange(msg)));
+/// ```
+///
+/// * Returns `Error::OutOfRange` when the number exceeds `MAX_SAFE_INTEGER`
+///
+/// ```
+/// use numsys::decode_json_safe;
+/// use numsys::Error;
+///
+/// let msg = "Number 9007199254740993 MUST be at most 2^53 - 1 (9007199254740991), \
+///            larger ones are sent as strings".to_string();
+/// assert_eq!(decode_json_safe("9007199254740993"), Err(Error::OutOfRange(msg)));
+/// ```
+///
+/// * Returns `Error::SequenceEmpty` when the string is empty
+///
+/// * Returns `Error::NonCanonical` when the string has leading zeros
+///
+/// * Returns `Error::MissingChar` when the string includes a char other than a digit
+///
+/// * Returns `Error::Overflow` when the string value exceeds `u64`
+pub fn decode_json_safe(json: &str) -> Result<u64, Error> {
+    decode_json_safe_with(json, &DIGITS)
+}
+
+
+/// Converts JSON value `json` back to the integer like `decode_json_safe`, with strings using `char2val` translation.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{decode_json_safe_with, DIGITS_UPPER_AZ};
+///
+/// let hex = &DIGITS_UPPER_AZ[..16];
+/// assert_eq!(decode_json_safe_with("255", hex), Ok(255));
+/// assert_eq!(decode_json_safe_with("\"FF\"", hex), Ok(255));
+/// ```
+///
+/// # Errors
+///
+/// * Returns errors of `decode_json_safe`, and of `encode_json_safe_with` for `char2val`
+pub fn decode_json_safe_with(json: &str, char2val: &[char]) -> Result<u64, Error> {
+    json_alphabet(char2val)?;
+    let json = json.trim_matches(|g| g == ' ' || g == '\t' || g == '\n' || g == '\r');
+    if json.len() >= 2 && json.starts_with('"') && json.ends_with('"') {
+        return parse_text(&json[1..json.len() - 1], char2val, u64::MAX as u128, "u64").map(|value| value as u64);
+    }
+    parse_number(json)
+}
+
+
+/// Converts JSON value `json` back to the integer like `decode_json_safe`, for `u128` values.
+///
+/// Numbers have the same `MAX_SAFE_INTEGER` bound, larger values are
+/// strings whatever the type.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_json_safe_u128;
+///
+/// assert_eq!(decode_json_safe_u128("42"), Ok(42));
+/// assert_eq!(decode_json_safe_u128("\"340282366920938463463374607431768211455\""), Ok(u128::MAX));
+/// ```
+///
+/// # Errors
+///
+/// * Returns errors of `decode_json_safe`, with `Error::Overflow` when the string value exceeds `u128`
+pub fn decode_json_safe_u128(json: &str) -> Result<u128, Error> {
+    let json = json.trim_matches(|g| g == ' ' || g == '\t' || g == '\n' || g == '\r');
+    if json.len() >= 2 && json.starts_with('"') && json.ends_with('"') {
+        return parse_text(&json[1..json.len() - 1], &DIGITS, u128::MAX, "u128");
+    }
+    parse_number(json).map(|value| value as u128)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DIGITS_UPPER_AZ;
+
+    #[test]
+    fn boundary_of_safe_integers() {
+        let max = MAX_SAFE_INTEGER;
+        assert_eq!(max, 9_007_199_254_740_991);
+        assert_eq!(encode_json_safe(max), MaybeNumber::Number(max));
+        assert_eq!(encode_json_safe(max + 1), MaybeNumber::Text("9007199254740992".to_string()));
+        assert_eq!(encode_json_safe(max + 2), MaybeNumber::Text("9007199254740993".to_string()));
+        assert_eq!(decode_json_safe("9007199254740991"), Ok(max));
+        for value in [max + 1, max + 2].iter() {
+            assert!(decode_json_safe(&value.to_string()).is_err());
+            assert_eq!(decode_json_safe(&format!("\"{}\"", value)), Ok(*value));
+        }
+        // in any notation
+        assert_eq!(decode_json_safe("9.007199254740991e15"), Ok(max));
+        assert!(decode_json_safe("9.007199254740992e15").is_err());
+        assert!(decode_json_safe("1e400").is_err());
+        // smaller ones may come as strings too
+        assert_eq!(decode_json_safe("\"7\""), Ok(7));
+    }
+
+    #[test]
+    fn round_trip_of_both_forms() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let mut next = Some(1_u64);
+        while let Some(value) = next {
+            for candidate in [value - 1, value, value + 1].iter() {
+                let json = encode_json_safe(*candidate).to_string();
+                assert_eq!(decode_json_safe(&json), Ok(*candidate), "{}", json);
+                let json = encode_json_safe_with(*candidate, hex).unwrap().to_string();
+                assert_eq!(decode_json_safe_with(&json, hex), Ok(*candidate), "{}", json);
+            }
+            next = value.checked_mul(3);
+        }
+        assert_eq!(decode_json_safe(&encode_json_safe(u64::MAX).to_string()), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn integral_floats_only() {
+        for (json, value) in [("0", 0), ("0.0", 0), ("0e5", 0), ("1.0", 1), ("2.50e1", 25), ("1E+2", 100),
+                              ("12300e-2", 123), ("0.001e3", 1)].iter() {
+            assert_eq!(decode_json_safe(json), Ok(*value), "{}", json);
+        }
+        for json in ["1.5", "0.5", "1e-1", "12345e-3", "9007199254740991.5", "5e-400"].iter() {
+            match decode_json_safe(json) {
+                Err(Error::OutOfRange(_)) => {}
+                other => panic!("{} gave {:?}", json, other),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_values() {
+        for json in ["", "-", "01", "1.", ".5", "1e", "1e+", "+1", "1 2", "\"", "true", "0x10", "\"1"].iter() {
+            match decode_json_safe(json) {
+                Err(Error::InvalidLiteral(_)) => {}
+                other => panic!("{:?} gave {:?}", json, other),
+            }
+        }
+        assert_eq!(decode_json_safe("-1"), Err(Error::OutOfRange("Number -1 MUST NOT be negative".into())));
+        assert_eq!(decode_json_safe("\"\""), Err(Error::SequenceEmpty));
+        assert!(decode_json_safe("\"\\u0031\"").is_err());
+        assert!(decode_json_safe("\"012\"").is_err());
+        assert!(decode_json_safe("\"1.5\"").is_err());
+        assert!(decode_json_safe("\"18446744073709551616\"").is_err());
+        assert!(decode_json_safe_with("1", &['0', '\n']).is_err());
+    }
+
+    #[test]
+    fn u128_values() {
+        let max = MAX_SAFE_INTEGER as u128;
+        assert_eq!(encode_json_safe_u128(max), MaybeNumber::Number(MAX_SAFE_INTEGER));
+        assert_eq!(encode_json_safe_u128(max + 1), MaybeNumber::Text("9007199254740992".to_string()));
+        assert_eq!(encode_json_safe_u128(max + 2), MaybeNumber::Text("9007199254740993".to_string()));
+        assert_eq!(decode_json_safe_u128("9007199254740991"), Ok(max));
+        assert!(decode_json_safe_u128("9007199254740992").is_err());
+        assert!(decode_json_safe_u128("2.5").is_err());
+        for value in [0, max, max + 1, max + 2, u64::MAX as u128 + 1, u128::MAX].iter() {
+            assert_eq!(decode_json_safe_u128(&encode_json_safe_u128(*value).to_string()), Ok(*value));
+        }
+        match decode_json_safe_u128("\"340282366920938463463374607431768211456\"") {
+            Err(Error::Overflow(ref msg)) if msg.ends_with("exceeds u128") => {}
+            other => panic!("{:?}", other),
+        }
+        let msg = "Value of \"18446744073709551616\" exceeds u64".to_string();
+        assert_eq!(decode_json_safe("\"18446744073709551616\""), Err(Error::Overflow(msg)));
+    }
+}
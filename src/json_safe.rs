@@ -0,0 +1,227 @@
+//! Serde adapter of the JSON-safe integer policy of `encode_json_safe`, for `u64` and `u128` fields.
+//!
+//! Values up to `MAX_SAFE_INTEGER` are serialized as numbers, larger ones as
+//! decimal strings. Either form is deserialized, numbers above
+//! `MAX_SAFE_INTEGER` and ones with a fractional part are rejected. Floats
+//! are checked as the `f64` the format gives, so digits past its precision
+//! aren't seen.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate numsys;
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde_json;
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Event {
+//!     #[serde(with = "numsys::json_safe")]
+//!     id: u64,
+//!     #[serde(with = "numsys::json_safe")]
+//!     trace: u128,
+//! }
+//!
+//! # fn main() {
+//! let event = Event { id: 42, trace: 1 << 64 };
+//! let json = serde_json::to_string(&event).unwrap();
+//! assert_eq!(json, r#"{"id":42,"trace":"18446744073709551616"}"#);
+//! assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+//! assert!(serde_json::from_str::<Event>(r#"{"id":9007199254740992,"trace":1}"#).is_err());
+//! # }
+//! ```
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+
+use json::{encode_checked, parse_text};
+use {MaybeNumber, DIGITS, MAX_SAFE_INTEGER};
+
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u64 {}
+    impl Sealed for u128 {}
+}
+
+
+/// Integer types of `json_safe` fields, `u64` and `u128`.
+pub trait JsonSafe: sealed::Sealed + Copy {
+    /// Name of the type in errors.
+    const NAME: &'static str;
+
+    /// Returns the value as `u128`.
+    fn to_u128(self) -> u128;
+
+    /// Returns `value` as the type, `None` when it doesn't fit.
+    fn from_u128(value: u128) -> Option<Self>;
+}
+
+impl JsonSafe for u64 {
+    const NAME: &'static str = "u64";
+
+    fn to_u128(self) -> u128 {
+        self as u128
+    }
+
+    fn from_u128(value: u128) -> Option<u64> {
+        if value > u64::MAX as u128 { None } else { Some(value as u64) }
+    }
+}
+
+impl JsonSafe for u128 {
+    const NAME: &'static str = "u128";
+
+    fn to_u128(self) -> u128 {
+        self
+    }
+
+    fn from_u128(value: u128) -> Option<u128> {
+        Some(value)
+    }
+}
+
+
+/// Serializes `value` as a number up to `MAX_SAFE_INTEGER`, as a decimal string above it.
+pub fn serialize<T: JsonSafe, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    match encode_checked(value.to_u128(), &DIGITS) {
+        MaybeNumber::Number(n) => serializer.serialize_u64(n),
+        MaybeNumber::Text(ref digits) => serializer.serialize_str(digits),
+    }
+}
+
+
+/// Deserializes a number up to `MAX_SAFE_INTEGER` or a decimal string.
+pub fn deserialize<'de, T: JsonSafe, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    deserializer.deserialize_any(JsonSafeVisitor(PhantomData))
+}
+
+
+struct JsonSafeVisitor<T>(PhantomData<T>);
+
+/// Error of number `shown` above `MAX_SAFE_INTEGER`.
+fn too_big<E: de::Error, N: fmt::Display>(shown: N) -> E {
+    E::custom(format!(
+        "Number {} MUST be at most 2^53 - 1 ({}), larger ones are sent as strings",
+        shown,
+        MAX_SAFE_INTEGER
+    ))
+}
+
+impl<T: JsonSafe> JsonSafeVisitor<T> {
+    fn number<E: de::Error>(value: u128) -> Result<T, E> {
+        if value > MAX_SAFE_INTEGER as u128 {
+            return Err(too_big(value));
+        }
+        Ok(T::from_u128(value).expect("MAX_SAFE_INTEGER fits u64"))
+    }
+}
+
+impl<'de, T: JsonSafe> Visitor<'de> for JsonSafeVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an integer at most 2^53 - 1 or a decimal string of {}", T::NAME)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        JsonSafeVisitor::number(value as u128)
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<T, E> {
+        JsonSafeVisitor::number(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        if value < 0 {
+            return Err(E::custom(format!("Number {} MUST NOT be negative", value)));
+        }
+        JsonSafeVisitor::number(value as u128)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<T, E> {
+        if value < 0.0 {
+            return Err(E::custom(format!("Number {} MUST NOT be negative", value)));
+        }
+        if value.fract() != 0.0 || !value.is_finite() {
+            return Err(E::custom(format!("Number {} MUST have no fractional part", value)));
+        }
+        if value > MAX_SAFE_INTEGER as f64 {
+            return Err(too_big(value));
+        }
+        JsonSafeVisitor::number(value as u128)
+    }
+
+    fn visit_str<E: de::Error>(self, digits: &str) -> Result<T, E> {
+        let max = T::from_u128(u128::MAX).map_or(u64::MAX as u128, JsonSafe::to_u128);
+        parse_text(digits, &DIGITS, max, T::NAME)
+            .map(|value| T::from_u128(value).expect("Value at most the max of the type"))
+            .map_err(E::custom)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Ids {
+        #[serde(with = "super")]
+        small: u64,
+        #[serde(with = "super")]
+        big: u128,
+    }
+
+    fn ids(json: &str) -> Result<Ids, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn boundary_of_safe_integers() {
+        let max = MAX_SAFE_INTEGER;
+        for (value, json) in [(max, "9007199254740991"), (max + 1, "\"9007199254740992\""),
+                              (max + 2, "\"9007199254740993\"")].iter() {
+            let both = Ids { small: *value, big: *value as u128 };
+            let text = serde_json::to_string(&both).unwrap();
+            assert_eq!(text, format!("{{\"small\":{},\"big\":{}}}", json, json));
+            assert_eq!(ids(&text), Ok(both));
+        }
+        let msg = "Number 9007199254740992 MUST be at most 2^53 - 1 (9007199254740991)";
+        assert!(ids("{\"small\":9007199254740992,\"big\":0}").unwrap_err().starts_with(msg));
+        assert!(ids("{\"small\":0,\"big\":9007199254740993}").unwrap_err().starts_with("Number 9007199254740993"));
+    }
+
+    #[test]
+    fn both_forms_deserialize() {
+        assert_eq!(ids("{\"small\":\"7\",\"big\":7}"), Ok(Ids { small: 7, big: 7 }));
+        assert_eq!(ids("{\"small\":1e3,\"big\":2.0}"), Ok(Ids { small: 1000, big: 2 }));
+        let json = "{\"small\":\"18446744073709551615\",\"big\":\"340282366920938463463374607431768211455\"}";
+        assert_eq!(ids(json), Ok(Ids { small: u64::MAX, big: u128::MAX }));
+        assert_eq!(serde_json::to_string(&Ids { small: u64::MAX, big: u128::MAX }).unwrap(), json);
+        let msg = "Overflow: Value of \"18446744073709551616\" exceeds u64 at line 1 column 31";
+        assert_eq!(ids("{\"small\":\"18446744073709551616\",\"big\":0}").unwrap_err(), msg);
+    }
+
+    #[test]
+    fn rejects_fractions_and_negatives() {
+        for json in ["2.5", "0.5", "1e-1", "-1", "-0.5", "9007199254740991.5e3"].iter() {
+            let text = format!("{{\"small\":{},\"big\":0}}", json);
+            assert!(ids(&text).is_err(), "{}", text);
+        }
+        for json in ["\"\"", "\"012\"", "\"1.5\"", "\"-1\"", "true", "null"].iter() {
+            let text = format!("{{\"small\":0,\"big\":{}}}", json);
+            assert!(ids(&text).is_err(), "{}", text);
+        }
+        assert!(ids("{\"small\":2.5,\"big\":0}").unwrap_err().starts_with("Number 2.5 MUST have no fractional part"));
+        // large floats are shown as given, not as any integer
+        let msg = "Number 100000000000000000000 MUST be at most 2^53 - 1 (9007199254740991)";
+        assert!(ids("{\"small\":1e20,\"big\":0}").unwrap_err().starts_with(msg));
+        let msg = "Number 9007199254740992 MUST be at most 2^53 - 1";
+        assert!(ids("{\"small\":0,\"big\":9.007199254740992e15}").unwrap_err().starts_with(msg));
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt;
+use std::str;
+
+use {digit_count, switch_dec_base, Error, DIGITS_UPPER_AZ};
+
+
+/// `value` in `base`, formatted only when displayed and without allocating.
+///
+/// Digits are the same as `switch_dec_base` ones, written straight into the
+/// `Formatter` from a stack buffer, which suits logging hot paths. A `base`
+/// outside 2 to 36 can't fail a `Display`, so `<invalid base N>` is written instead.
+/// With features `ufmt` and `defmt` it's `ufmt::uDisplay` and `defmt::Format`
+/// too, writing the same digits from the same stack buffer.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::LazyBased;
+///
+/// assert_eq!(format!("{}", LazyBased { value: 255, base: 16 }), "FF");
+/// assert_eq!(format!("{}", LazyBased { value: 0, base: 2 }), "0");
+/// assert_eq!(format!("{}", LazyBased { value: 7, base: 1 }), "<invalid base 1>");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LazyBased {
+    pub value: usize,
+    pub base: usize,
+}
+
+impl LazyBased {
+    /// Writes digits into the end of `buffer`, returning them or `None` for an invalid base.
+    fn render<'a>(&self, buffer: &'a mut [u8; 64]) -> Option<&'a str> {
+        if self.base < 2 || self.base > DIGITS_UPPER_AZ.len() {
+            return None;
+        }
+        // 64 is enough for any `usize` in base 2
+        let mut start = buffer.len();
+        let mut rest = self.value;
+        loop {
+            start -= 1;
+            buffer[start] = DIGITS_UPPER_AZ[rest % self.base] as u8;
+            rest /= self.base;
+            if rest == 0 {
+                break;
+            }
+        }
+        Some(str::from_utf8(&buffer[start..]).expect("ASCII digits"))
+    }
+}
+
+impl fmt::Display for LazyBased {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.render(&mut [0; 64]) {
+            Some(digits) => f.write_str(digits),
+            None => write!(f, "<invalid base {}>", self.base),
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for LazyBased {
+    fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<W>) -> Result<(), W::Error> {
+        match self.render(&mut [0; 64]) {
+            Some(digits) => f.write_str(digits),
+            None => ufmt::uwrite!(f, "<invalid base {}>", self.base),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for LazyBased {
+    fn format(&self, f: defmt::Formatter) {
+        match self.render(&mut [0; 64]) {
+            Some(digits) => defmt::write!(f, "{=str}", digits),
+            None => defmt::write!(f, "<invalid base {=usize}>", self.base),
+        }
+    }
+}
+
+
+/// Value in `base` kept encoded, re-encoding on change only the digits which differ.
+///
+/// Suits rapidly updating counters: a step usually changes the last digit
+/// or two, only they are rewritten. When count of digits changes the whole
+/// sequence is encoded anew. Digits are the same as `switch_dec_base` ones.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::LiveValue;
+///
+/// let mut counter = LiveValue::new(35, 36).unwrap();
+/// assert_eq!(counter.as_str(), "Z");
+/// assert_eq!(counter.set(36), "10");
+/// assert_eq!(counter.set(37), "11");
+/// assert_eq!(counter.digits_changed(), &[1]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LiveValue {
+    value: usize,
+    base: usize,
+    sequence: String,
+    changed: Vec<usize>,
+}
+
+impl LiveValue {
+    /// Creates live `value` of `base`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::BaseTooSmall` when `base` is less then 2
+    ///
+    /// * Returns `Error::BaseTooBig` when `base` is greater then 36
+    pub fn new(value: usize, base: usize) -> Result<LiveValue, Error> {
+        let sequence = switch_dec_base(value, base)?;
+        Ok(LiveValue {
+            value,
+            base,
+            changed: (0..sequence.len()).collect(),
+            sequence,
+        })
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> usize {
+        self.value
+    }
+
+    /// Returns the current sequence.
+    pub fn as_str(&self) -> &str {
+        &self.sequence
+    }
+
+    /// Changes the value to `new_value`, rewriting only the digits which differ.
+    pub fn set(&mut self, new_value: usize) -> &str {
+        self.changed.clear();
+        let length = digit_count(new_value, self.base).expect("Base is validated");
+        if length != self.sequence.len() {
+            self.sequence = switch_dec_base(new_value, self.base).expect("Base is validated");
+            self.changed.extend(0..length);
+        } else {
+            let (mut old, mut new) = (self.value, new_value);
+            let mut position = length;
+            // digits above the highest differing one are equal
+            while old != new {
+                position -= 1;
+                let digit = new % self.base;
+                if old % self.base != digit {
+                    let glyph = DIGITS_UPPER_AZ[digit];
+                    self.sequence.replace_range(position..=position, glyph.encode_utf8(&mut [0; 4]));
+                    self.changed.push(position);
+                }
+                old /= self.base;
+                new /= self.base;
+            }
+            self.changed.reverse();
+        }
+        self.value = new_value;
+        &self.sequence
+    }
+
+    /// Returns positions (0 is the most significant) rewritten by the last `set`, in order.
+    ///
+    /// All positions are reported after `new` and after a change of digit count.
+    pub fn digits_changed(&self) -> &[usize] {
+        &self.changed
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+    use testutil::{allocations, XorShift};
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn ufmt_agrees_with_display() {
+        use testutil::ufmt_string;
+
+        let mut rng = XorShift::new(210);
+        for base in 0..40 {
+            for &value in [0, 1, base, usize::MAX].iter() {
+                let lazy = LazyBased { value, base };
+                assert_eq!(ufmt_string(&lazy), lazy.to_string());
+            }
+            for _ in 0..100 {
+                let lazy = LazyBased { value: rng.next_u64() as usize >> rng.below(64), base };
+                assert_eq!(ufmt_string(&lazy), lazy.to_string());
+            }
+        }
+        let mut out = ::testutil::UfmtBuffer(String::with_capacity(64));
+        let before = allocations();
+        ufmt::uwrite!(out, "{}", LazyBased { value: usize::MAX, base: 2 }).unwrap();
+        assert_eq!(allocations(), before);
+        assert_eq!(out.0, "1".repeat(64));
+    }
+
+    #[test]
+    fn formats_like_switch_dec_base() {
+        let mut rng = XorShift::new(226);
+        for base in 2..37 {
+            for &value in [0, 1, base - 1, base, usize::MAX].iter() {
+                assert_eq!(LazyBased { value, base }.to_string(), switch_dec_base(value, base).unwrap());
+            }
+            for _ in 0..200 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                assert_eq!(LazyBased { value, base }.to_string(), switch_dec_base(value, base).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_base_placeholder_without_allocations() {
+        // force `DIGITS_UPPER_AZ` initialization first
+        assert_eq!(LazyBased { value: 0, base: 10 }.to_string(), "0");
+        let mut out = String::with_capacity(256);
+        let before = allocations();
+        write!(out, "{} {} {}", LazyBased { value: usize::MAX, base: 2 }, LazyBased { value: 5, base: 0 },
+               LazyBased { value: 5, base: 37 }).unwrap();
+        assert_eq!(allocations(), before);
+        assert_eq!(out, format!("{} <invalid base 0> <invalid base 37>", "1".repeat(64)));
+    }
+
+    #[test]
+    fn live_value_agrees_with_full_encoding_over_random_walks() {
+        let mut rng = XorShift::new(2332);
+        for base in 2..37 {
+            let start = rng.next_u64() as usize >> rng.below(64);
+            let mut live = LiveValue::new(start, base).unwrap();
+            for _ in 0..500 {
+                let old = live.as_str().to_string();
+                let step = 1 + rng.below(base * base);
+                let value = match rng.below(5) {
+                    0 => live.value().wrapping_sub(step),
+                    1 => rng.next_u64() as usize >> rng.below(64),
+                    2 => live.value().saturating_sub(step),
+                    _ => live.value().saturating_add(step),
+                };
+                let expected = switch_dec_base(value, base).unwrap();
+                assert_eq!(live.set(value), expected);
+                if old.len() == expected.len() {
+                    let differing: Vec<usize> = (0..old.len())
+                        .filter(|position| old.as_bytes()[*position] != expected.as_bytes()[*position])
+                        .collect();
+                    assert_eq!(live.digits_changed(), &differing[..]);
+                } else {
+                    assert_eq!(live.digits_changed().len(), expected.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn live_value_carries_grow_and_shrink() {
+        let mut live = LiveValue::new(36_usize.pow(6) - 2, 36).unwrap();
+        assert_eq!(live.set(36_usize.pow(6) - 1), "ZZZZZZ");
+        assert_eq!(live.digits_changed(), &[5]);
+        assert_eq!(live.set(36_usize.pow(6)), "1000000");
+        assert_eq!(live.digits_changed(), &[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(live.set(36_usize.pow(6) + 36_usize.pow(5)), "1100000");
+        assert_eq!(live.digits_changed(), &[1]);
+        assert_eq!(live.set(0), "0");
+        assert_eq!(live.set(0), "0");
+        assert_eq!(live.digits_changed(), &[] as &[usize]);
+        let mut binary = LiveValue::new(usize::MAX - 1, 2).unwrap();
+        assert_eq!(binary.set(usize::MAX), "1".repeat(64));
+        assert_eq!(binary.digits_changed(), &[63]);
+        assert_eq!(binary.set(1 << 63), format!("1{}", "0".repeat(63)));
+        assert_eq!(binary.digits_changed(), &(1..64).collect::<Vec<usize>>()[..]);
+        assert!(LiveValue::new(1, 37).is_err());
+    }
+}
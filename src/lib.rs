@@ -8,44 +8,269 @@
 //! * works for u8, u16, u32, u64, optionally u128
 //!     * [solution](https://doc.rust-lang.org/src/core/num/mod.rs.html#2272-2282)
 //!
+//! ### Unsafe code
+//!
+//! There's none of it in the crate, it's `forbid(unsafe_code)`. Tests only
+//! `deny` it, as the allocation counting allocator of the tests implements
+//! `GlobalAlloc`, which needs it.
+//!
 
+#![cfg_attr(not(test), forbid(unsafe_code))]
+#![cfg_attr(test, deny(unsafe_code))]
 
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
 #[macro_use]
-extern crate lazy_static;
+extern crate serde_derive;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(feature = "ufmt")]
+extern crate ufmt;
+#[cfg(feature = "defmt")]
+extern crate defmt;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::error::Error as StdErr;
 use std::fmt;
 
+use digits::value_at;
+
+mod alphabet;
+mod arena;
+mod analysis;
+mod arith;
+mod batch;
+mod bijective;
+mod chunked;
+mod columns;
+mod continued;
+mod denoms;
+mod diagnose;
+mod digits;
+mod fraction;
+mod grouped;
+mod grouping;
+mod intern;
+mod iso7064;
+mod json;
+mod lazy;
+mod natural;
+mod notation;
+mod numbering;
+mod obfuscate;
+mod padic;
+mod parse;
+mod permutation;
+mod positional;
+mod procedural;
+mod radix;
+mod record;
+mod scheme;
+mod styled;
+mod system;
+mod tagged;
+mod tokens;
+mod url;
+mod version;
+mod zigzag;
+#[cfg(any(test, feature = "verify"))]
+mod verify;
+#[cfg(feature = "serde")]
+pub mod json_safe;
+#[cfg(test)]
+#[allow(unsafe_code)]
+mod testutil;
+
+pub use alphabet::{alphabets_equivalent, alphabets_same_set, validate_alphabet, validate_alphabet_no_whitespace,
+                   Alphabet, AlphabetBuilder, CaseFoldSafety, CompatibilityReport};
+pub use analysis::{char_distribution, detect_corpus_base, digit_histogram, digit_histogram_all, digit_histogram_of,
+                   glyph_positions, leading_digit_distribution, length_histogram};
+pub use arena::{encode_in, EncodeArena};
+pub use arith::{add_in_base, cmp_seq, convert_fixed_width, eq_seq, flags2seq, mul_in_base, recode, seq2dec_auto, seq2flags,
+                sub_in_base, BigConversion, BitOrder, Number, StepResult};
+pub use batch::{seq2dec_offsets, BatchReport, RowError};
+pub use bijective::{from_bijective_decimal, to_bijective_decimal};
+pub use chunked::{format_chunked, parse_chunked};
+pub use columns::{from_column_name, to_column_name};
+pub use continued::{from_continued_fraction, to_continued_fraction};
+pub use denoms::{dec2denoms, denoms2dec, greedy_is_optimal, to_units, GREEDY_CHECK_LIMIT};
+pub use diagnose::{diagnose_pair, Diagnosis};
+pub use digits::{additive_persistence, checked_pow_base, dec2digits, dec_to_digit_list, digit_at, digit_at_lsb,
+                 digit_count, digit_list_to_dec, digits2seq, digits2seq_limited, digits_for_count, glyph_of, glyphs_of,
+                 ilog, invert_representation, largest_power_le, multiplicative_persistence, next_palindrome,
+                 seq2digits, to_display_nibbles, to_leb_like, value_of, values_of, DigitNode};
+pub use fraction::{ratio_in_base, seq2f64, seq2f64_approx, seq2ratio};
+pub use grouped::{mint_grouped_checked, parse_grouped_checked};
+pub use grouping::GroupingTable;
+pub use intern::{InternStats, InterningEncoder};
+pub use iso7064::{iso7064_check, iso7064_verify};
+pub use json::{decode_json_safe, decode_json_safe_u128, decode_json_safe_with, encode_json_safe, encode_json_safe_u128,
+               encode_json_safe_with, MaybeNumber, MAX_SAFE_INTEGER};
+pub use lazy::{LazyBased, LiveValue};
+pub use natural::{natural_cmp, natural_sort};
+pub use notation::{dec2seq_truncated, decode_range, dump_range, encode_range, format_column, format_column_with,
+                   scientific_base, to_fixed_point_base, to_fixed_visual, write_all, write_column, TruncateSide};
+pub use numbering::{Numbering, Roman, Tally};
+pub use obfuscate::{from_obfuscated, to_obfuscated};
+pub use padic::{int2padic, padic2int};
+pub use parse::{decode, decode_with_allowed, parse_hardened, sanitize_input, seq2dec_lossy, seq2dec_sanitized,
+                 seq2dec_strict_canonical, seq2dec_with_options, OverflowMode, ParseOptions};
+pub use permutation::{index_to_permutation, is_de_bruijn_representation, permutation_index};
+pub use positional::PositionalSystem;
+pub use procedural::{dec2seq_procedural, dec2seq_with, decode_mixed_procedural, encode_mixed_procedural,
+                     seq2dec_procedural, seq2dec_with};
+pub use radix::{format_ada_literal, format_prefixed, from_str_radix, parse_ada_literal, parse_prefixed, to_str_radix,
+                MAX_RADIX};
+pub use record::RecordCodec;
+pub use scheme::{CodeScheme, CodeSchemeBuilder};
+pub use styled::{dec2seq_styled, diff_digits, StyledFragment};
+pub use system::{representation_fingerprint, try_display, DisplayValue, NumeralSystem, RepresentationHasher};
+pub use tagged::{decode_tagged, encode_tagged, TagOptions, TaggedValue, TAG_CHARS};
+pub use tokens::{decode_tokens, decode_tokens_iter, DecodeTokens};
+pub use url::{from_url_safe, to_url_safe};
+pub use version::{cmp_fields, format_fields, parse_fields, MissingFields};
+pub use zigzag::{from_zigzag, from_zigzag_seq, to_zigzag, to_zigzag_seq};
+#[cfg(any(test, feature = "verify"))]
+pub use verify::{verify_all_decode_paths, verify_all_paths, DecodePath, EncodePath, PathReport, DECODE_PATHS,
+                 ENCODE_PATHS};
+
+
+mod statics {
+    use std::sync::LazyLock;
+
+    use AlphabetBuilder;
+
+    fn preset(builder: AlphabetBuilder) -> Vec<char> {
+        builder.build().expect("Preset MUST be valid").chars().to_vec()
+    }
 
-lazy_static! {
     /// `Vector` of `char` containing digits from `0` to `9` (included)
-    pub static ref DIGITS: Vec<char> = (b'0'..b'9'+1).map(|x| x as char).collect();
+    pub static DIGITS: LazyLock<Vec<char>> = LazyLock::new(|| preset(AlphabetBuilder::new().range('0'..='9')));
     /// `Vector` of `char` containing upper ASCII letters
-    pub static ref UPPER_AZ: Vec<char> = (b'A'..b'Z'+1).map(|x| x as char).collect();
+    pub static UPPER_AZ: LazyLock<Vec<char>> = LazyLock::new(|| preset(AlphabetBuilder::new().range('A'..='Z')));
     /// `Vector` of `char` containing sum of `DIGITS` and `UPPER_AZ`
-    pub static ref DIGITS_UPPER_AZ: Vec<char> = [&DIGITS[..], &UPPER_AZ[..]].concat();
+    pub static DIGITS_UPPER_AZ: LazyLock<Vec<char>> =
+        LazyLock::new(|| preset(AlphabetBuilder::new().range('0'..='9').range('A'..='Z')));
     /// Length of `DIGITS_UPPER_AZ` as `usize`
-    pub static ref D_UAZ_LEN: usize = DIGITS_UPPER_AZ.len();
+    pub static D_UAZ_LEN: LazyLock<usize> = LazyLock::new(|| DIGITS_UPPER_AZ.len());
+    /// `Vector` of `char` containing lower ASCII letters
+    pub static LOWER_AZ: LazyLock<Vec<char>> = LazyLock::new(|| preset(AlphabetBuilder::new().range('a'..='z')));
+    /// `Vector` of `char` containing sum of `DIGITS`, `UPPER_AZ` and `LOWER_AZ`
+    pub static DIGITS_UPPER_LOWER_AZ: LazyLock<Vec<char>> =
+        LazyLock::new(|| preset(AlphabetBuilder::new().range('0'..='9').range('A'..='Z').range('a'..='z')));
+    /// `Vector` of `char` containing URL unreserved chars (RFC 3986) in ASCII order
+    pub static URL_SAFE: LazyLock<Vec<char>> = LazyLock::new(|| {
+        preset(
+            AlphabetBuilder::new().chars("-.").range('0'..='9').range('A'..='Z').chars("_")
+                .range('a'..='z').chars("~"),
+        )
+    });
 }
+pub use statics::*;
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Error {
     BaseTooSmall(String),
     BaseTooBig(String),
     DictEmpty,
     MultipleChar(String),
     MissingChar(String),
+    OutOfRange(String),
+    Overflow(String),
+    LengthMismatch(String),
+    InvalidDenominations(String),
+    InvalidPoint(String),
+    SequenceEmpty,
+    InvalidRange(String),
+    WhitespaceChar(String),
+    InputTooLong(String),
+    Callback(String),
+    NonCanonical(String),
+    OutOfDomain { value: usize, max: usize },
+    ChecksumMismatch(String),
+    IncompatibleBases(String),
+    CaseFoldCollision(String),
+    DisallowedDigit(String),
+    InvalidPermutation(String),
+    InvalidBytes(String),
+    UnsupportedVersion(String),
+    TooLong(String),
+    InvalidMarker(String),
+    InvalidLayout(String),
+    ForbiddenCodePoint(String),
+    InvalidLiteral(String),
+    AlphabetTooLarge(String),
+}
+impl Error {
+    /// Returns name of the variant and its message, `OutOfDomain` has fields instead.
+    fn name_and_message(&self) -> (&'static str, Option<&str>) {
+        match *self {
+            Error::BaseTooSmall(ref v) => ("BaseTooSmall", Some(v)),
+            Error::BaseTooBig(ref v) => ("BaseTooBig", Some(v)),
+            Error::DictEmpty => ("DictEmpty", None),
+            Error::MultipleChar(ref v) => ("MultipleChar", Some(v)),
+            Error::MissingChar(ref v) => ("MissingChar", Some(v)),
+            Error::OutOfRange(ref v) => ("OutOfRange", Some(v)),
+            Error::Overflow(ref v) => ("Overflow", Some(v)),
+            Error::LengthMismatch(ref v) => ("LengthMismatch", Some(v)),
+            Error::InvalidDenominations(ref v) => ("InvalidDenominations", Some(v)),
+            Error::InvalidPoint(ref v) => ("InvalidPoint", Some(v)),
+            Error::SequenceEmpty => ("SequenceEmpty", None),
+            Error::InvalidRange(ref v) => ("InvalidRange", Some(v)),
+            Error::WhitespaceChar(ref v) => ("WhitespaceChar", Some(v)),
+            Error::InputTooLong(ref v) => ("InputTooLong", Some(v)),
+            Error::Callback(ref v) => ("Callback", Some(v)),
+            Error::NonCanonical(ref v) => ("NonCanonical", Some(v)),
+            Error::OutOfDomain { .. } => ("OutOfDomain", None),
+            Error::ChecksumMismatch(ref v) => ("ChecksumMismatch", Some(v)),
+            Error::IncompatibleBases(ref v) => ("IncompatibleBases", Some(v)),
+            Error::CaseFoldCollision(ref v) => ("CaseFoldCollision", Some(v)),
+            Error::DisallowedDigit(ref v) => ("DisallowedDigit", Some(v)),
+            Error::InvalidPermutation(ref v) => ("InvalidPermutation", Some(v)),
+            Error::InvalidBytes(ref v) => ("InvalidBytes", Some(v)),
+            Error::UnsupportedVersion(ref v) => ("UnsupportedVersion", Some(v)),
+            Error::TooLong(ref v) => ("TooLong", Some(v)),
+            Error::InvalidMarker(ref v) => ("InvalidMarker", Some(v)),
+            Error::InvalidLayout(ref v) => ("InvalidLayout", Some(v)),
+            Error::ForbiddenCodePoint(ref v) => ("ForbiddenCodePoint", Some(v)),
+            Error::InvalidLiteral(ref v) => ("InvalidLiteral", Some(v)),
+            Error::AlphabetTooLarge(ref v) => ("AlphabetTooLarge", Some(v)),
+        }
+    }
 }
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Error::BaseTooSmall(ref v) => write!(f, "BaseTooSmall: {}", v),
-            Error::BaseTooBig(ref v) => write!(f, "BaseTooBig: {}", v),
-            Error::DictEmpty => write!(f, "DictEmpty"),
-            Error::MultipleChar(ref v) => write!(f, "MultipleChar: {}", v),
-            Error::MissingChar(ref v) => write!(f, "MissingChar: {}", v),
+        match (self, self.name_and_message()) {
+            (&Error::OutOfDomain { value, max }, _) => write!(f, "OutOfDomain: Value {} MUST be at most {}", value, max),
+            (_, (name, Some(message))) => write!(f, "{}: {}", name, message),
+            (_, (name, None)) => f.write_str(name),
+        }
+    }
+}
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Error {
+    fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<W>) -> Result<(), W::Error> {
+        match (self, self.name_and_message()) {
+            (&Error::OutOfDomain { value, max }, _) => {
+                ufmt::uwrite!(f, "OutOfDomain: Value {} MUST be at most {}", value, max)
+            }
+            (_, (name, Some(message))) => ufmt::uwrite!(f, "{}: {}", name, message),
+            (_, (name, None)) => f.write_str(name),
+        }
+    }
+}
+#[cfg(feature = "defmt")]
+impl defmt::Format for Error {
+    fn format(&self, f: defmt::Formatter) {
+        match (self, self.name_and_message()) {
+            (&Error::OutOfDomain { value, max }, _) => {
+                defmt::write!(f, "OutOfDomain: Value {=usize} MUST be at most {=usize}", value, max)
+            }
+            (_, (name, Some(message))) => defmt::write!(f, "{=str}: {=str}", name, message),
+            (_, (name, None)) => defmt::write!(f, "{=str}", name),
         }
     }
 }
@@ -57,6 +282,31 @@ impl StdErr for Error {
             Error::DictEmpty => "Dict MUST include chars",
             Error::MultipleChar(_) => "Dict values MUST be unique",
             Error::MissingChar(_) => "All chars MUST be included",
+            Error::OutOfRange(_) => "Value MUST be in range",
+            Error::Overflow(_) => "Value MUST fit in usize",
+            Error::LengthMismatch(_) => "Length MUST match",
+            Error::InvalidDenominations(_) => "Denominations MUST be nonzero and decreasing",
+            Error::InvalidPoint(_) => "Point MUST be single and between digits",
+            Error::SequenceEmpty => "Sequence MUST include chars",
+            Error::InvalidRange(_) => "Range start MUST NOT be after its end",
+            Error::WhitespaceChar(_) => "Dict values MUST NOT be whitespace",
+            Error::InputTooLong(_) => "Sequence MUST be shorter",
+            Error::Callback(_) => "Callback MUST succeed",
+            Error::NonCanonical(_) => "Sequence MUST be in canonical form",
+            Error::OutOfDomain { .. } => "Value MUST be in domain of the system",
+            Error::ChecksumMismatch(_) => "Check digit MUST match its digits",
+            Error::IncompatibleBases(_) => "Bases MUST be compatible",
+            Error::CaseFoldCollision(_) => "Chars MUST stay distinct digits when case is ignored",
+            Error::DisallowedDigit(_) => "Digit value MUST be allowed at its position",
+            Error::InvalidPermutation(_) => "Permutation MUST contain each value of 0..n once",
+            Error::InvalidBytes(_) => "Bytes MUST be in the expected format",
+            Error::UnsupportedVersion(_) => "Format version MUST be supported",
+            Error::TooLong(_) => "Representation MUST fit the visual length",
+            Error::InvalidMarker(_) => "Marker MUST NOT be an alphabet char",
+            Error::InvalidLayout(_) => "Text MUST match the chunked layout",
+            Error::ForbiddenCodePoint(_) => "Dict values MUST NOT be noncharacters or private use code points",
+            Error::InvalidLiteral(_) => "Literal MUST follow its syntax",
+            Error::AlphabetTooLarge(_) => "Alphabet MUST be smaller",
         }
     }
 }
@@ -102,18 +352,7 @@ impl StdErr for Error {
 /// assert_eq!(switch_dec_base(10, 37), Err(Error::BaseTooBig(msg)));
 /// ```
 pub fn switch_dec_base(decimal: usize, base: usize) -> Result<String, Error> {
-    if base < 2 {
-        return Err(Error::BaseTooSmall(
-            format!("Base MUST be 2 or higer, given {}", base),
-        ));
-    };
-    if base > *D_UAZ_LEN {
-        return Err(Error::BaseTooBig(format!(
-            "Base MUST be at most {}, given {}",
-            *D_UAZ_LEN,
-            base
-        )));
-    };
+    check_base(base)?;
     if decimal == 0 {
         return Ok("0".into());
     }
@@ -132,6 +371,55 @@ pub fn switch_dec_base(decimal: usize, base: usize) -> Result<String, Error> {
 }
 
 
+/// Converts base of `decimal` to both `base_a` and `base_b`, like `switch_dec_base`, for side by side display.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::compare_bases;
+///
+/// assert_eq!(compare_bases(255, 2, 16), Ok(("11111111".to_string(), "FF".to_string())));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base_a` or `base_b` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base_a` or `base_b` is greater then 36
+///
+/// ```
+/// use numsys::compare_bases;
+/// use numsys::Error;
+///
+/// let msg = "Base MUST be at most 36, given 37".to_string();
+/// assert_eq!(compare_bases(10, 37, 2), Err(Error::BaseTooBig(msg.clone())));
+/// assert_eq!(compare_bases(10, 2, 37), Err(Error::BaseTooBig(msg)));
+/// ```
+pub fn compare_bases(decimal: usize, base_a: usize, base_b: usize) -> Result<(String, String), Error> {
+    check_base(base_a)?;
+    check_base(base_b)?;
+    Ok((switch_dec_base(decimal, base_a)?, switch_dec_base(decimal, base_b)?))
+}
+
+
+/// Validates `base` is supported by `DIGITS_UPPER_AZ` (from 2 to 36).
+pub(crate) fn check_base(base: usize) -> Result<(), Error> {
+    if base < 2 {
+        return Err(Error::BaseTooSmall(
+            format!("Base MUST be 2 or higer, given {}", base),
+        ));
+    };
+    if base > *D_UAZ_LEN {
+        return Err(Error::BaseTooBig(format!(
+            "Base MUST be at most {}, given {}",
+            *D_UAZ_LEN,
+            base
+        )));
+    };
+    Ok(())
+}
+
+
 /// Converts `sequence` to decimal using `char2val` translation.
 ///
 /// # Examples
@@ -174,41 +462,132 @@ pub fn switch_dec_base(decimal: usize, base: usize) -> Result<String, Error> {
 /// let detailed_msg = "Char \'2\' not found in: [\'0\']".to_string();
 /// assert_eq!(seq2dec("20", &['0']), Err(Error::MissingChar(detailed_msg)));
 /// ```
-///
 pub fn seq2dec<S: AsRef<str>>(sequence: S, char2val: &[char]) -> Result<usize, Error> {
     let from_base = char2val.len();
     if from_base == 0 {
         return Err(Error::DictEmpty);
     }
-    let single_char_sequence = {
-        let uniques: HashMap<_, _> = sequence.as_ref().chars().map(|c| (c, 0)).collect();
-        uniques.len() == 1
-    };
-    if from_base == 1 && single_char_sequence {
-        return Ok(sequence.as_ref().len());
+    if from_base == 1 {
+        let single_char_sequence = {
+            let uniques: HashMap<_, _> = sequence.as_ref().chars().map(|c| (c, 0)).collect();
+            uniques.len() == 1
+        };
+        if single_char_sequence {
+            return Ok(sequence.as_ref().len());
+        }
     }
-    let mut _char2val = {
-        let mut hm: HashMap<char, usize> = HashMap::new();
-        for (idx, elem) in char2val.iter().enumerate() {
-            if hm.insert(*elem, idx).is_some() {
-                let msg = format!(
-                    "Chars MUST be unique, duplicated: {:?} in {:?}",
-                    elem,
-                    char2val
-                );
-                return Err(Error::MultipleChar(msg));
-            }
+    with_cached_chars(char2val, |_char2val| {
+        let mut dec: usize = 0;
+        for (idx, glyph) in sequence.as_ref().chars().rev().enumerate() {
+            let value = value_at(glyph, None, _char2val, char2val)?;
+            dec += value * from_base.pow(idx as u32);
         }
-        hm
-    };
-    let mut dec: usize = 0;
-    for (idx, glyph) in sequence.as_ref().chars().rev().enumerate() {
-        let value = _char2val.get(&glyph).ok_or_else(|| {
-            Error::MissingChar(format!("Char {:?} not found in: {:?}", glyph, char2val))
-        })?;
-        dec += value * from_base.pow(idx as u32);
+        Ok(dec)
+    })?
+}
+
+
+/// Converts `sequence` to decimal like `seq2dec`, refusing sequences longer than `max_chars`.
+///
+/// Length is checked before any conversion work, and at most `max_chars + 1`
+/// chars are inspected, so it's safe for untrusted input.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2dec_limited;
+///
+/// assert_eq!(seq2dec_limited("1010", &['0', '1'], 4), Ok(10));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InputTooLong` when `sequence` has more than `max_chars` chars
+///
+/// ```
+/// use numsys::seq2dec_limited;
+/// use numsys::Error;
+///
+/// let msg = "Sequence MUST have at most 3 chars".to_string();
+/// assert_eq!(seq2dec_limited("1010", &['0', '1'], 3), Err(Error::InputTooLong(msg)));
+/// ```
+///
+/// * Returns the errors of `seq2dec` otherwise
+pub fn seq2dec_limited<S: AsRef<str>>(sequence: S, char2val: &[char], max_chars: usize) -> Result<usize, Error> {
+    let seq = sequence.as_ref();
+    if seq.len() > max_chars && seq.chars().nth(max_chars).is_some() {
+        return Err(Error::InputTooLong(
+            format!("Sequence MUST have at most {} chars", max_chars),
+        ));
     }
-    Ok(dec)
+    seq2dec(seq, char2val)
+}
+
+
+/// Maps each char of `char2val` to its digit value (its index).
+pub(crate) fn index_chars(char2val: &[char]) -> Result<HashMap<char, usize>, Error> {
+    if char2val.is_empty() {
+        return Err(Error::DictEmpty);
+    }
+    let mut hm: HashMap<char, usize> = HashMap::new();
+    for (idx, elem) in char2val.iter().enumerate() {
+        if hm.insert(*elem, idx).is_some() {
+            let msg = format!(
+                "Chars MUST be unique, duplicated: {:?} in {:?}",
+                elem,
+                char2val
+            );
+            return Err(Error::MultipleChar(msg));
+        }
+    }
+    Ok(hm)
+}
+
+thread_local! {
+    // alphabet of the last `with_cached_chars` call of the thread and its map
+    static CACHED_CHARS: RefCell<(Vec<char>, HashMap<char, usize>)> = RefCell::new((Vec::new(), HashMap::new()));
+}
+
+/// Calls `f` with map of `char2val` like `index_chars`, reusing the map of the previous call with the same chars.
+///
+/// Alphabets are compared by contents, so a changed alphabet is never
+/// served the old map, even at the same address.
+fn with_cached_chars<T, F: FnOnce(&HashMap<char, usize>) -> T>(char2val: &[char], f: F) -> Result<T, Error> {
+    CACHED_CHARS.with(|cached| {
+        let mut cached = cached.borrow_mut();
+        if char2val.is_empty() || cached.0[..] != *char2val {
+            cached.1 = index_chars(char2val)?;
+            cached.0.clear();
+            cached.0.extend_from_slice(char2val);
+        }
+        Ok(f(&cached.1))
+    })
+}
+
+/// Refuses `char2val` of more than `max_alphabet` chars, a guard against huge alphabets passed by mistake.
+pub(crate) fn check_alphabet_cap(char2val: &[char], max_alphabet: usize) -> Result<(), Error> {
+    if char2val.len() > max_alphabet {
+        return Err(Error::AlphabetTooLarge(
+            format!("Alphabet MUST have at most {} chars, given {}", max_alphabet, char2val.len()),
+        ));
+    }
+    Ok(())
+}
+
+/// Maps each char of `alphabet` to its value, requiring at least 2 chars.
+pub(crate) fn positional_alphabet(alphabet: &[char]) -> Result<HashMap<char, usize>, Error> {
+    let char2val = index_chars(alphabet)?;
+    if alphabet.len() < 2 {
+        return Err(Error::BaseTooSmall(
+            format!("Base MUST be 2 or higer, given {}", alphabet.len()),
+        ));
+    }
+    Ok(char2val)
+}
+
+/// Converts `sequence` to decimal like `seq2dec`, but reports overflow.
+pub(crate) fn checked_seq2dec(sequence: &str, char2val: &[char]) -> Result<usize, Error> {
+    decode(sequence, char2val, OverflowMode::Error)
 }
 
 
@@ -234,7 +613,6 @@ pub fn seq2dec<S: AsRef<str>>(sequence: S, char2val: &[char]) -> Result<usize, E
 ///
 /// assert_eq!(dec2seq(10, &[]), Err(Error::DictEmpty));
 /// ```
-///
 pub fn dec2seq(mut decimal: usize, char2val: &[char]) -> Result<String, Error> {
     let base = char2val.len();
     if base == 0 {
@@ -256,12 +634,163 @@ pub fn dec2seq(mut decimal: usize, char2val: &[char]) -> Result<String, Error> {
     Ok(sequence)
 }
 
+
+/// Converts `decimal` like `dec2seq`, refusing `char2val` of more than `max_alphabet` chars.
+///
+/// The alphabet is checked before any conversion work. It's a guard
+/// against a huge alphabet passed by mistake, see
+/// `ParseOptions::max_alphabet` for decoding.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::dec2seq_capped;
+///
+/// assert_eq!(dec2seq_capped(10, &['0', '1'], 2), Ok("1010".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::AlphabetTooLarge` when `char2val` has more than `max_alphabet` chars
+///
+/// ```
+/// use numsys::dec2seq_capped;
+/// use numsys::Error;
+///
+/// let huge: Vec<char> = (0..10_000).filter_map(|code| std::char::from_u32(0x4E00 + code)).collect();
+/// let msg = "Alphabet MUST have at most 1024 chars, given 10000".to_string();
+/// assert_eq!(dec2seq_capped(10_000, &huge, 1024), Err(Error::AlphabetTooLarge(msg)));
+/// ```
+///
+/// * Returns the errors of `dec2seq` otherwise
+pub fn dec2seq_capped(decimal: usize, char2val: &[char], max_alphabet: usize) -> Result<String, Error> {
+    check_alphabet_cap(char2val, max_alphabet)?;
+    dec2seq(decimal, char2val)
+}
+
+
+/// Converts `decimal` to the shortest sequence possible with `allowed` chars.
+///
+/// Every char of `allowed` becomes a digit, so the base equals `allowed.len()`.
+/// A sequence of base `b` needs `floor(log_b(decimal)) + 1` chars, so the
+/// more chars are allowed the shorter the result gets, e.g. `u64::MAX` takes
+/// 16 hex digits but only 11 digits of base 62.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::encode_minimal;
+///
+/// assert_eq!(encode_minimal(255, &['0', '1']), Ok("11111111".to_string()));
+/// assert_eq!(encode_minimal(255, &['0', '1', '2', '3']), Ok("3333".to_string()));
+/// assert_eq!(encode_minimal(0, &['a', 'b', 'c']), Ok("a".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `allowed` length is 0
+///
+/// * Returns `Error::MultipleChar` when `allowed` includes duplicated chars
+///
+/// ```
+/// use numsys::encode_minimal;
+/// use numsys::Error;
+///
+/// let detailed_msg = "Chars MUST be unique, duplicated: \'A\' in [\'A\', \'A\']".to_string();
+/// assert_eq!(encode_minimal(10, &['A', 'A']), Err(Error::MultipleChar(detailed_msg)));
+/// ```
+///
+pub fn encode_minimal(decimal: usize, allowed: &[char]) -> Result<String, Error> {
+    index_chars(allowed)?;
+    if decimal == 0 && allowed.len() > 1 {
+        return Ok(allowed[0].to_string());
+    }
+    dec2seq(decimal, allowed)
+}
+
 #[cfg(test)]
 mod tests {
     use ::*;
 
     // TODO: add tests which shows that places "as u32" are broken
 
+    #[test]
+    fn error_display_names_the_variant() {
+        assert_eq!(Error::DictEmpty.to_string(), "DictEmpty");
+        assert_eq!(Error::Overflow("Too big".into()).to_string(), "Overflow: Too big");
+        assert_eq!(Error::OutOfDomain { value: 4, max: 3 }.to_string(), "OutOfDomain: Value 4 MUST be at most 3");
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn error_ufmt_agrees_with_display() {
+        use testutil::ufmt_string;
+
+        let errors = [
+            Error::DictEmpty,
+            Error::SequenceEmpty,
+            Error::OutOfDomain { value: usize::MAX, max: 0 },
+            ::seq2dec("1a", &DIGITS_UPPER_AZ[..16]).unwrap_err(),
+            ::seq2dec("1", &['0', '0']).unwrap_err(),
+            Error::Callback("Glyph ☆ MUST be known".into()),
+        ];
+        for error in errors.iter() {
+            assert_eq!(ufmt_string(error), error.to_string());
+        }
+    }
+
+    // the output needs a `defmt` logger of a target, so only the impls are checked
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn defmt_formats_the_display_types() {
+        fn formats<T: defmt::Format>() {}
+
+        formats::<Error>();
+        formats::<LazyBased>();
+        formats::<DisplayValue>();
+    }
+
+    #[test]
+    fn wrong_case_char_is_suggested() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let msg = format!("Char 'a' not found in: {:?}, did you mean 'A'?", hex);
+        assert_eq!(::seq2dec("1a", hex), Err(Error::MissingChar(msg)));
+        let msg = format!("Char 'f' at position 1 not found in: {:?}, did you mean 'F'?", hex);
+        assert_eq!(checked_seq2dec("Ff", hex), Err(Error::MissingChar(msg)));
+        // the other way round, and nothing to suggest when neither case is a digit
+        let lower: Vec<char> = "0123456789abcdef".chars().collect();
+        match ::seq2dec("FF", &lower) {
+            Err(Error::MissingChar(ref m)) if m.ends_with(", did you mean 'f'?") => {}
+            other => panic!("{:?}", other),
+        }
+        let msg = format!("Char 'g' not found in: {:?}", hex);
+        assert_eq!(::seq2dec("g", hex), Err(Error::MissingChar(msg)));
+    }
+
+    #[test]
+    fn alphabet_cap_set_low() {
+        let capped = ParseOptions { max_alphabet: Some(16), ..ParseOptions::default() };
+        let msg = "Alphabet MUST have at most 16 chars, given 36".to_string();
+        assert_eq!(dec2seq_capped(35, &DIGITS_UPPER_AZ, 16), Err(Error::AlphabetTooLarge(msg.clone())));
+        assert_eq!(seq2dec_with_options("Z", &DIGITS_UPPER_AZ, &capped), Err(Error::AlphabetTooLarge(msg)));
+        // at the cap is fine
+        assert_eq!(dec2seq_capped(255, &DIGITS_UPPER_AZ[..16], 16), Ok("FF".to_string()));
+        assert_eq!(seq2dec_with_options("FF", &DIGITS_UPPER_AZ[..16], &capped), Ok(255));
+        // the cap is checked first, even a broken alphabet is just too large
+        let msg = "Alphabet MUST have at most 16 chars, given 17".to_string();
+        assert_eq!(dec2seq_capped(1, &['A'; 17], 16), Err(Error::AlphabetTooLarge(msg)));
+        // no cap elsewhere
+        assert_eq!(::dec2seq(35, &DIGITS_UPPER_AZ), Ok("Z".to_string()));
+        assert_eq!(seq2dec_with_options("Z", &DIGITS_UPPER_AZ, &ParseOptions::default()), Ok(35));
+    }
+
+    #[test]
+    fn compare_255_in_binary_and_hex() {
+        assert_eq!(::compare_bases(255, 2, 16), Ok(("11111111".to_string(), "FF".to_string())));
+        assert_eq!(::compare_bases(0, 2, 36), Ok(("0".to_string(), "0".to_string())));
+        assert!(::compare_bases(255, 16, 1).is_err());
+    }
+
     #[test]
     fn dec2seq_works_when_dict_has_single_element() {
         let result = dec2seq(10, &['a']);
@@ -275,7 +804,58 @@ mod tests {
         let seq = dec2seq(number, &dict).expect("First conversion failed");
         assert_eq!(seq2dec(seq, &dict), Ok(number));
     }
-}
 
+    #[test]
+    fn encode_minimal_is_shorter_with_bigger_alphabet() {
+        let hex: Vec<char> = DIGITS_UPPER_AZ[..16].to_vec();
+        let base62 = &DIGITS_UPPER_LOWER_AZ[..];
+        let number = usize::MAX;
+        let short = encode_minimal(number, base62).unwrap();
+        let long = encode_minimal(number, &hex).unwrap();
+        assert!(short.len() < long.len());
+        assert_eq!(seq2dec(&short, base62), Ok(number));
+    }
+
+    #[test]
+    fn seq2dec_reuses_map_of_same_alphabet() {
+        let hex = DIGITS_UPPER_AZ[..16].to_vec();
+        let sequences: Vec<String> = (0..1000).map(|value| dec2seq(value * 7919, &hex).unwrap()).collect();
+        assert_eq!(seq2dec(&sequences[1], &hex), Ok(7919));
+        // the map isn't rebuilt, which would allocate
+        let before = testutil::allocations();
+        for (value, sequence) in sequences.iter().enumerate() {
+            assert_eq!(seq2dec(sequence, &hex), Ok(value * 7919));
+        }
+        assert_eq!(testutil::allocations(), before);
+        // changed alphabets get their own map, even in the same place
+        let mut alphabet = hex.clone();
+        alphabet.swap(0, 1);
+        assert_eq!(seq2dec("10", &alphabet), Ok(1));
+        assert_eq!(seq2dec("10", &hex), Ok(16));
+        alphabet[1] = alphabet[0];
+        assert!(seq2dec("10", &alphabet).is_err());
+        assert_eq!(seq2dec("AB", &['A', 'B']), Ok(1));
+        assert_eq!(seq2dec("AB", &['B', 'A']), Ok(2));
+        assert!(seq2dec("AB", &['A']).is_err());
+    }
 
+    #[test]
+    fn seq2dec_limited_accepts_input_at_limit() {
+        assert_eq!(seq2dec_limited("FFFF", &DIGITS_UPPER_AZ[..16], 4), Ok(0xFFFF));
+        // multi byte chars are counted as chars, not bytes
+        assert_eq!(seq2dec_limited("☆★☆★", &['★', '☆'], 4), Ok(10));
+    }
 
+    #[test]
+    fn seq2dec_limited_rejects_input_over_limit() {
+        let msg = "Sequence MUST have at most 4 chars".to_string();
+        let result = seq2dec_limited("FFFFF", &DIGITS_UPPER_AZ[..16], 4);
+        assert_eq!(result, Err(Error::InputTooLong(msg)));
+        // rejected before the chars get validated
+        let huge = "x".repeat(1_000_000);
+        match seq2dec_limited(&huge, &DIGITS, 10) {
+            Err(Error::InputTooLong(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use {cmp_seq, positional_alphabet};
+
+
+/// Splits `s` into runs of digits of `char2val` and runs of other chars, tagged `true` for digits.
+fn segments<'a>(s: &'a str, char2val: &HashMap<char, usize>) -> Vec<(bool, &'a str)> {
+    let mut segments: Vec<(bool, &str)> = Vec::new();
+    let mut start = 0;
+    let mut current = None;
+    for (index, glyph) in s.char_indices() {
+        let is_digit = char2val.contains_key(&glyph);
+        if let Some(previous) = current {
+            if previous != is_digit {
+                segments.push((previous, &s[start..index]));
+                start = index;
+            }
+        }
+        current = Some(is_digit);
+    }
+    if let Some(last) = current {
+        segments.push((last, &s[start..]));
+    }
+    segments
+}
+
+
+/// Compares `a` and `b` in natural order, embedded numbers of `char2val` compared by value.
+///
+/// Both strings are split into runs of digits of `char2val` and runs of
+/// other chars. Runs are compared pairwise: digit runs by the numbers they
+/// denote (via `cmp_seq`, so any length is fine), other runs bytewise, and
+/// a digit run sorts before a text run. A string with fewer runs sorts first
+/// when the common ones are equal. Note letters of `char2val` are digits
+/// wherever they are, e.g. `e` of `item` in hex.
+///
+/// Numbers equal except for leading zeros (`"007"` and `"7"`) only decide
+/// when all runs compare equal: then the first such pair decides, fewer
+/// zeros first (`"7"` before `"007"`). Strings equal in all that are
+/// compared bytewise, so the order is total. An invalid `char2val` (e.g. with
+/// duplicates) has no digits at all, strings compare bytewise.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::natural_cmp;
+/// use std::cmp::Ordering;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(natural_cmp("item2", "item10", &hex), Ordering::Less);
+/// assert_eq!(natural_cmp("itemFF", "item100", &hex), Ordering::Less);
+/// assert_eq!(natural_cmp("x7", "x007", &hex), Ordering::Less);
+/// ```
+pub fn natural_cmp(a: &str, b: &str, char2val: &[char]) -> Ordering {
+    let map = match positional_alphabet(char2val) {
+        Ok(map) => map,
+        Err(_) => return a.cmp(b),
+    };
+    let (a_segments, b_segments) = (segments(a, &map), segments(b, &map));
+    let mut zeros_tie = Ordering::Equal;
+    for (&(a_digits, a_run), &(b_digits, b_run)) in a_segments.iter().zip(&b_segments) {
+        let order = match (a_digits, b_digits) {
+            (true, true) => {
+                let order = cmp_seq(a_run, b_run, char2val).expect("Runs are digits of a valid alphabet");
+                if order == Ordering::Equal && zeros_tie == Ordering::Equal {
+                    zeros_tie = a_run.len().cmp(&b_run.len());
+                }
+                order
+            }
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => a_run.cmp(b_run),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a_segments
+        .len()
+        .cmp(&b_segments.len())
+        .then(zeros_tie)
+        .then_with(|| a.cmp(b))
+}
+
+
+/// Sorts `values` in natural order of `natural_cmp`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::natural_sort;
+///
+/// let mut files = vec!["log10".to_string(), "log9".to_string(), "log1".to_string()];
+/// natural_sort(&mut files, &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']);
+/// assert_eq!(files, ["log1", "log9", "log10"]);
+/// ```
+pub fn natural_sort(values: &mut [String], char2val: &[char]) {
+    values.sort_by(|a, b| natural_cmp(a, b, char2val));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {DIGITS, DIGITS_UPPER_AZ};
+
+    fn sorted(values: &[&str], char2val: &[char]) -> Vec<String> {
+        let mut values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        natural_sort(&mut values, char2val);
+        values
+    }
+
+    #[test]
+    fn interleaved_segments() {
+        let values = ["v1.10.2", "v1.2.10", "v1.2.9", "v10.0", "v2", "v1.2"];
+        assert_eq!(sorted(&values, &DIGITS), ["v1.2", "v1.2.9", "v1.2.10", "v1.10.2", "v2", "v10.0"]);
+        assert_eq!(natural_cmp("a1b2", "a1b10", &DIGITS), Ordering::Less);
+        assert_eq!(natural_cmp("a2b1", "a1b10", &DIGITS), Ordering::Greater);
+        // digit runs sort before text runs
+        assert_eq!(natural_cmp("1", "a", &DIGITS), Ordering::Less);
+        assert_eq!(natural_cmp("x1", "xa", &DIGITS), Ordering::Less);
+    }
+
+    #[test]
+    fn differing_segment_counts() {
+        assert_eq!(natural_cmp("item", "item2", &DIGITS), Ordering::Less);
+        assert_eq!(natural_cmp("item2", "item2b", &DIGITS), Ordering::Less);
+        assert_eq!(natural_cmp("", "0", &DIGITS), Ordering::Less);
+        assert_eq!(natural_cmp("", "", &DIGITS), Ordering::Equal);
+    }
+
+    #[test]
+    fn leading_zeros_tie_is_deterministic() {
+        assert_eq!(natural_cmp("7", "007", &DIGITS), Ordering::Less);
+        assert_eq!(natural_cmp("007", "7", &DIGITS), Ordering::Greater);
+        assert_eq!(natural_cmp("007", "007", &DIGITS), Ordering::Equal);
+        // later runs decide before the zeros
+        assert_eq!(natural_cmp("a007b", "a7c", &DIGITS), Ordering::Less);
+        // the first tie decides
+        assert_eq!(natural_cmp("7x01", "07x1", &DIGITS), Ordering::Less);
+        assert_eq!(sorted(&["x07", "x7", "x007", "x6"], &DIGITS), ["x6", "x7", "x07", "x007"]);
+    }
+
+    #[test]
+    fn hex_runs_beyond_u64() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let big = format!("x{}", "F".repeat(40));
+        let bigger = format!("x1{}", "0".repeat(40));
+        assert_eq!(natural_cmp(&big, &bigger, hex), Ordering::Less);
+        assert_eq!(sorted(&["itemFF", "item10", "item2", &bigger, &big], hex),
+                   ["item2", "item10", "itemFF", &big, &bigger]);
+        // lower case isn't in the alphabet, so "ff" is text
+        assert_eq!(natural_cmp("item1ff", "item10", hex), Ordering::Less);
+        assert_eq!(natural_cmp("b1", "a2", &['0', '0']), Ordering::Greater);
+    }
+}
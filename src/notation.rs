@@ -0,0 +1,692 @@
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+
+use {check_base, checked_seq2dec, dec2digits, digit_count, largest_power_le, positional_alphabet, switch_dec_base, Error, DIGITS_UPPER_AZ};
+
+
+/// Converts `decimal` to `base` in normalized scientific notation `d.ddd × base^e`.
+///
+/// Returns the leading digit, the remaining mantissa digits (without
+/// trailing zeros) and the exponent.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::scientific_base;
+///
+/// assert_eq!(scientific_base(12345, 10), Ok(('1', vec!['2', '3', '4', '5'], 4)));
+/// assert_eq!(scientific_base(4096, 16), Ok(('1', vec![], 3)));
+/// assert_eq!(scientific_base(0, 2), Ok(('0', vec![], 0)));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+pub fn scientific_base(decimal: usize, base: usize) -> Result<(char, Vec<char>, u32), Error> {
+    let mut digits: Vec<char> = switch_dec_base(decimal, base)?.chars().collect();
+    let exponent = largest_power_le(decimal, base)?.0;
+    let leading = digits.remove(0);
+    while digits.last() == Some(&'0') {
+        digits.pop();
+    }
+    Ok((leading, digits, exponent))
+}
+
+
+/// Converts fixed-point `raw` with `frac_bits` fractional bits to `"int.frac"` in `base`.
+///
+/// Fraction is exact for even bases. Other bases can't represent binary
+/// fractions exactly, so there the fraction is truncated to `frac_bits`
+/// digits, which is more precise than the source anyway. Trailing zeros
+/// are skipped, but at least one fractional digit is shown.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_fixed_point_base;
+///
+/// // Q8.8
+/// assert_eq!(to_fixed_point_base(0x0180, 8, 10), Ok("1.5".to_string()));
+/// assert_eq!(to_fixed_point_base(0x0180, 8, 16), Ok("1.8".to_string()));
+/// assert_eq!(to_fixed_point_base(0x0100, 8, 2), Ok("1.0".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::OutOfRange` when `frac_bits` isn't less than `usize` bits
+///
+/// ```
+/// use numsys::to_fixed_point_base;
+/// use numsys::Error;
+///
+/// let bits = usize::MAX.count_ones();
+/// let msg = format!("Fractional bits MUST be less than {}, given {}", bits, bits);
+/// assert_eq!(to_fixed_point_base(1, bits, 10), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn to_fixed_point_base(raw: usize, frac_bits: u32, base: usize) -> Result<String, Error> {
+    let bits = usize::MAX.count_ones();
+    if frac_bits >= bits {
+        return Err(Error::OutOfRange(format!(
+            "Fractional bits MUST be less than {}, given {}",
+            bits,
+            frac_bits
+        )));
+    }
+    let int = switch_dec_base(raw >> frac_bits, base)?;
+    let mask = (1u128 << frac_bits) - 1;
+    let mut frac = raw as u128 & mask;
+    let mut digits = String::new();
+    for _ in 0..frac_bits {
+        if frac == 0 {
+            break;
+        }
+        frac *= base as u128;
+        digits.push(DIGITS_UPPER_AZ[(frac >> frac_bits) as usize]);
+        frac &= mask;
+    }
+    let digits = digits.trim_end_matches('0');
+    Ok(format!("{}.{}", int, if digits.is_empty() { "0" } else { digits }))
+}
+
+
+/// Renders values from `start` to `end` (inclusive) with their `base` representations.
+///
+/// Each line is `value: representation`, both columns right-aligned. Meant
+/// for quick inspection while debugging.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::dump_range;
+///
+/// assert_eq!(dump_range(0, 3, 2), Ok("0:  0\n1:  1\n2: 10\n3: 11".to_string()));
+/// assert_eq!(dump_range(9, 10, 16), Ok(" 9: 9\n10: A".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::InvalidRange` when `start` is greater than `end`
+///
+/// ```
+/// use numsys::dump_range;
+/// use numsys::Error;
+///
+/// let msg = "Range 3..=0 MUST start before its end".to_string();
+/// assert_eq!(dump_range(3, 0, 2), Err(Error::InvalidRange(msg)));
+/// ```
+pub fn dump_range(start: usize, end: usize, base: usize) -> Result<String, Error> {
+    if start > end {
+        return Err(Error::InvalidRange(
+            format!("Range {}..={} MUST start before its end", start, end),
+        ));
+    }
+    let rows = (start..=end)
+        .map(|value| switch_dec_base(value, base).map(|seq| (value.to_string(), seq)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let value_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let seq_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|(value, seq)| format!("{:>2$}: {:>3$}", value, seq, value_width, seq_width))
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+
+/// Converts range `start..end` to compact span `start-end` of `base`, e.g. for pagination cursors.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::encode_range;
+///
+/// assert_eq!(encode_range(100, 200, 36), Ok("2S-5K".to_string()));
+/// assert_eq!(encode_range(0, 0, 2), Ok("0-0".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::InvalidRange` when `start` is greater than `end`
+///
+/// ```
+/// use numsys::encode_range;
+/// use numsys::Error;
+///
+/// let msg = "Range 3..0 MUST start before its end".to_string();
+/// assert_eq!(encode_range(3, 0, 36), Err(Error::InvalidRange(msg)));
+/// ```
+pub fn encode_range(start: usize, end: usize, base: usize) -> Result<String, Error> {
+    check_base(base)?;
+    if start > end {
+        return Err(Error::InvalidRange(
+            format!("Range {}..{} MUST start before its end", start, end),
+        ));
+    }
+    Ok(format!("{}-{}", switch_dec_base(start, base)?, switch_dec_base(end, base)?))
+}
+
+
+/// Converts span `start-end` of `base` back to a range, the inverse of `encode_range`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_range;
+///
+/// assert_eq!(decode_range("2S-5K", 36), Ok(100..200));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::InvalidRange` when `span` isn't two values joined by `-`, or its start is greater than its end
+///
+/// ```
+/// use numsys::decode_range;
+/// use numsys::Error;
+///
+/// let msg = "Span \"2S\" MUST be `start-end`".to_string();
+/// assert_eq!(decode_range("2S", 36), Err(Error::InvalidRange(msg)));
+/// ```
+///
+/// * Returns `Error::SequenceEmpty` when start or end is empty
+///
+/// * Returns `Error::MissingChar` when start or end includes a char which isn't a digit of `base`
+///
+/// * Returns `Error::Overflow` when start or end exceeds `usize`
+pub fn decode_range(span: &str, base: usize) -> Result<Range<usize>, Error> {
+    check_base(base)?;
+    let mut parts = span.split('-');
+    let (start, end) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(start), Some(end), None) => (start, end),
+        _ => return Err(Error::InvalidRange(format!("Span {:?} MUST be `start-end`", span))),
+    };
+    let alphabet = &DIGITS_UPPER_AZ[..base];
+    let value = |part: &str| {
+        if part.is_empty() {
+            return Err(Error::SequenceEmpty);
+        }
+        checked_seq2dec(part, alphabet)
+    };
+    let (start, end) = (value(start)?, value(end)?);
+    if start > end {
+        return Err(Error::InvalidRange(
+            format!("Range {}..{} MUST start before its end", start, end),
+        ));
+    }
+    Ok(start..end)
+}
+
+
+/// Writes `value` of `char2val` right-aligned to `width` with `fill`, digit by digit.
+fn push_aligned(out: &mut String, value: usize, char2val: &[char], width: usize, fill: char) {
+    let base = char2val.len();
+    let (exp, mut power) = largest_power_le(value, base).expect("Alphabet is validated");
+    out.extend(iter::repeat(fill).take(width.saturating_sub(exp as usize + 1)));
+    let mut rest = value;
+    loop {
+        out.push(char2val[rest / power]);
+        rest %= power;
+        if power == 1 {
+            break;
+        }
+        power /= base;
+    }
+}
+
+/// Returns width of the widest of `values`, but at least `min_width`.
+fn column_width(values: &[usize], base: usize, min_width: usize) -> Result<usize, Error> {
+    let widest = values.iter().max().map_or(Ok(0), |max| digit_count(*max, base))?;
+    Ok(widest.max(min_width))
+}
+
+
+/// Converts `values` with `char2val` translation, right-aligned to the widest one.
+///
+/// Width comes from `digit_count` of the biggest value, then each value is
+/// converted once, straight into its padded `String`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::format_column;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(format_column(&[255, 0, 4096], &hex), Ok(vec!["  FF".to_string(), "   0".to_string(), "1000".to_string()]));
+/// ```
+///
+/// # Errors
+///
+/// Same as `format_column_with`.
+pub fn format_column(values: &[usize], char2val: &[char]) -> Result<Vec<String>, Error> {
+    format_column_with(values, char2val, 0, ' ')
+}
+
+
+/// Converts `values` like `format_column`, aligned to at least `min_width` with `fill`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::format_column_with;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(format_column_with(&[255, 10], &hex, 4, '0'), Ok(vec!["00FF".to_string(), "000A".to_string()]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+pub fn format_column_with(values: &[usize], char2val: &[char], min_width: usize, fill: char) -> Result<Vec<String>, Error> {
+    positional_alphabet(char2val)?;
+    let width = column_width(values, char2val.len(), min_width)?;
+    Ok(values
+        .iter()
+        .map(|value| {
+            let mut line = String::with_capacity(width);
+            push_aligned(&mut line, *value, char2val, width, fill);
+            line
+        })
+        .collect())
+}
+
+
+/// Writes `values` aligned like `format_column_with` to `out`, one per line.
+///
+/// Lines are reused from one buffer, so only one `String` is allocated.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::write_column;
+///
+/// let mut out = String::new();
+/// write_column(&mut out, &[7, 100], &['0', '1'], 0, '.').unwrap();
+/// assert_eq!(out, "....111\n1100100\n");
+/// ```
+///
+/// # Errors
+///
+/// Same as `format_column_with`, and `Error::Callback` when writing to `out` fails.
+pub fn write_column<W: fmt::Write>(out: &mut W, values: &[usize], char2val: &[char], min_width: usize,
+                                   fill: char) -> Result<(), Error> {
+    positional_alphabet(char2val)?;
+    let width = column_width(values, char2val.len(), min_width)?;
+    let mut line = String::with_capacity(width + 1);
+    for value in values {
+        line.clear();
+        push_aligned(&mut line, *value, char2val, width, fill);
+        line.push('\n');
+        out.write_str(&line).map_err(|_| Error::Callback("Writing column failed".into()))?;
+    }
+    Ok(())
+}
+
+
+/// Writes each of `values` in `base` to `out`, separated by `separator`, like `switch_dec_base` does.
+///
+/// The base is validated once and there is one digit buffer for all of
+/// them, so memory stays bounded however many values the iterator yields.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::write_all;
+///
+/// let mut out = String::new();
+/// write_all(vec![255, 0, 4096].into_iter(), 16, ',', &mut out).unwrap();
+/// assert_eq!(out, "FF,0,1000");
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::Callback` when writing to `out` fails
+pub fn write_all<W: fmt::Write, I: Iterator<Item = usize>>(values: I, base: usize, separator: char,
+                                                           out: &mut W) -> Result<(), Error> {
+    check_base(base)?;
+    let failed = |_| Error::Callback("Writing values failed".into());
+    let mut digits = Vec::with_capacity(usize::BITS as usize);
+    for (index, value) in values.enumerate() {
+        if index != 0 {
+            out.write_char(separator).map_err(failed)?;
+        }
+        digits.clear();
+        let mut rest = value;
+        loop {
+            digits.push(DIGITS_UPPER_AZ[rest % base]);
+            rest /= base;
+            if rest == 0 {
+                break;
+            }
+        }
+        for glyph in digits.iter().rev() {
+            out.write_char(*glyph).map_err(failed)?;
+        }
+    }
+    Ok(())
+}
+
+
+/// Converts `decimal` to `base` zero padded to exactly `visual_len` digits, e.g. for UI codes.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_fixed_visual;
+///
+/// assert_eq!(to_fixed_visual(255, 16, 6), Ok("0000FF".to_string()));
+/// assert_eq!(to_fixed_visual(0, 36, 3), Ok("000".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::TooLong` when `decimal` needs more than `visual_len` digits
+///
+/// ```
+/// use numsys::to_fixed_visual;
+/// use numsys::Error;
+///
+/// let msg = "Representation \"10000\" MUST have at most 4 chars, given 5".to_string();
+/// assert_eq!(to_fixed_visual(0x10000, 16, 4), Err(Error::TooLong(msg)));
+/// ```
+pub fn to_fixed_visual(decimal: usize, base: usize, visual_len: usize) -> Result<String, Error> {
+    let digits = switch_dec_base(decimal, base)?;
+    if digits.len() > visual_len {
+        return Err(Error::TooLong(format!(
+            "Representation {:?} MUST have at most {} chars, given {}",
+            digits,
+            visual_len,
+            digits.len()
+        )));
+    }
+    Ok(format!("{:0>1$}", digits, visual_len))
+}
+
+
+/// Side of digits kept by `dec2seq_truncated`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TruncateSide {
+    /// Keep the leading digits, telling the magnitude (`12A…`)
+    MostSignificant,
+    /// Keep the trailing digits, telling similar values apart (`…9BZ`)
+    LeastSignificant,
+}
+
+
+/// Converts `decimal` using `char2val` translation, truncated with `marker` to at most `max_chars`.
+///
+/// A sequence which fits is never truncated. Otherwise `max_chars - 1`
+/// digits of the `keep` side are kept and `marker` takes the place of the
+/// others. Lengths are counted in chars, not bytes. Zero is `char2val[0]`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{dec2seq_truncated, TruncateSide};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let most = TruncateSide::MostSignificant;
+/// assert_eq!(dec2seq_truncated(0xABCDEF, &hex, 8, '…', most), Ok("ABCDEF".to_string()));
+/// assert_eq!(dec2seq_truncated(0xABCDEF, &hex, 4, '…', most), Ok("ABC…".to_string()));
+/// let least = TruncateSide::LeastSignificant;
+/// assert_eq!(dec2seq_truncated(0xABCDEF, &hex, 4, '…', least), Ok("…DEF".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::InvalidMarker` when `marker` is in `char2val`
+///
+/// * Returns `Error::OutOfRange` when `max_chars` can't hold the marker and a digit
+///
+/// ```
+/// use numsys::{dec2seq_truncated, Error, TruncateSide};
+///
+/// let msg = "Marker \'1\' MUST NOT be in: [\'0\', \'1\']".to_string();
+/// let result = dec2seq_truncated(5, &['0', '1'], 8, '1', TruncateSide::MostSignificant);
+/// assert_eq!(result, Err(Error::InvalidMarker(msg)));
+/// let msg = "Max chars MUST be at least 2, given 1".to_string();
+/// let result = dec2seq_truncated(5, &['0', '1'], 1, '~', TruncateSide::MostSignificant);
+/// assert_eq!(result, Err(Error::OutOfRange(msg)));
+/// ```
+pub fn dec2seq_truncated(decimal: usize, char2val: &[char], max_chars: usize, marker: char,
+                         keep: TruncateSide) -> Result<String, Error> {
+    positional_alphabet(char2val)?;
+    if char2val.contains(&marker) {
+        return Err(Error::InvalidMarker(format!("Marker {:?} MUST NOT be in: {:?}", marker, char2val)));
+    }
+    if max_chars < 2 {
+        return Err(Error::OutOfRange(format!("Max chars MUST be at least 2, given {}", max_chars)));
+    }
+    let digits: Vec<usize> = dec2digits(decimal, char2val.len())?;
+    if digits.len() <= max_chars {
+        return Ok(digits.iter().map(|digit| char2val[*digit]).collect());
+    }
+    let kept = max_chars - 1;
+    let mut sequence = String::with_capacity(max_chars * 4);
+    match keep {
+        TruncateSide::MostSignificant => {
+            sequence.extend(digits[..kept].iter().map(|digit| char2val[*digit]));
+            sequence.push(marker);
+        }
+        TruncateSide::LeastSignificant => {
+            sequence.push(marker);
+            sequence.extend(digits[digits.len() - kept..].iter().map(|digit| char2val[*digit]));
+        }
+    }
+    Ok(sequence)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dec2seq;
+
+    #[test]
+    fn scientific_base_of_decimal() {
+        assert_eq!(scientific_base(6_022_000, 10), Ok(('6', vec!['0', '2', '2'], 6)));
+        assert_eq!(scientific_base(9, 10), Ok(('9', vec![], 0)));
+        assert_eq!(scientific_base(10, 10), Ok(('1', vec![], 1)));
+    }
+
+    #[test]
+    fn scientific_base_exponent_is_digit_count_minus_one() {
+        for base in 2..37 {
+            for value in (1..1_000_000).step_by(7919) {
+                let (_, _, exponent) = scientific_base(value, base).unwrap();
+                let length = switch_dec_base(value, base).unwrap().len();
+                assert_eq!(exponent as usize, length - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_point_q8_8() {
+        // 0x12.34 = 18 + 52 / 256
+        assert_eq!(to_fixed_point_base(0x1234, 8, 16), Ok("12.34".to_string()));
+        assert_eq!(to_fixed_point_base(0x1234, 8, 10), Ok("18.203125".to_string()));
+        assert_eq!(to_fixed_point_base(0x0001, 8, 10), Ok("0.00390625".to_string()));
+        assert_eq!(to_fixed_point_base(0xFFFF, 8, 10), Ok("255.99609375".to_string()));
+        assert_eq!(to_fixed_point_base(0, 8, 10), Ok("0.0".to_string()));
+    }
+
+    #[test]
+    fn fixed_point_in_odd_base_is_truncated() {
+        // 1/2 in base 3 is 0.1111...
+        assert_eq!(to_fixed_point_base(0x80, 8, 3), Ok("0.11111111".to_string()));
+        assert_eq!(to_fixed_point_base(5, 0, 3), Ok("12.0".to_string()));
+    }
+
+    #[test]
+    fn write_all_streams_values() {
+        let mut out = String::new();
+        assert_eq!(write_all([10, 255, 4096].iter().cloned(), 16, ',', &mut out), Ok(()));
+        assert_eq!(out, "A,FF,1000");
+        let mut out = String::new();
+        write_all((0..40).map(|value| value * 7), 36, ' ', &mut out).unwrap();
+        let expected: Vec<String> = (0..40).map(|value| switch_dec_base(value * 7, 36).unwrap()).collect();
+        assert_eq!(out, expected.join(" "));
+        let mut out = String::new();
+        write_all(Some(usize::MAX).into_iter(), 2, ',', &mut out).unwrap();
+        assert_eq!(out, "1".repeat(64));
+        write_all(None.into_iter(), 16, ',', &mut out).unwrap();
+        assert_eq!(out.len(), 64);
+        assert!(write_all(None.into_iter(), 37, ',', &mut out).is_err());
+    }
+
+    #[test]
+    fn dump_range_of_binary() {
+        let dump = dump_range(0, 3, 2).unwrap();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines, vec!["0:  0", "1:  1", "2: 10", "3: 11"]);
+        assert_eq!(dump_range(7, 7, 8), Ok("7: 7".to_string()));
+        assert!(dump_range(0, 3, 1).is_err());
+    }
+
+    #[test]
+    fn range_round_trip_in_base_36() {
+        let mut rng = ::testutil::XorShift::new(2272);
+        for _ in 0..1000 {
+            let (a, b) = (rng.next_u64() as usize >> rng.below(64), rng.next_u64() as usize >> rng.below(64));
+            let (start, end) = (a.min(b), a.max(b));
+            let span = encode_range(start, end, 36).unwrap();
+            assert_eq!(decode_range(&span, 36), Ok(start..end), "{}", span);
+        }
+        let span = encode_range(0, usize::MAX, 36).unwrap();
+        assert_eq!(span, "0-3W5E11264SGSF");
+        assert_eq!(decode_range(&span, 36), Ok(0..usize::MAX));
+    }
+
+    #[test]
+    fn malformed_spans() {
+        assert!(decode_range("5K-2S", 36).is_err());
+        assert!(decode_range("1-2-3", 36).is_err());
+        assert_eq!(decode_range("-2", 36), Err(Error::SequenceEmpty));
+        assert!(decode_range("1-z", 36).is_err());
+        assert!(decode_range("1-2", 1).is_err());
+        assert!(encode_range(1, 2, 37).is_err());
+    }
+
+    #[test]
+    fn column_shares_widest_length() {
+        let mut rng = ::testutil::XorShift::new(2282);
+        for &base in [2, 7, 16, 36].iter() {
+            let alphabet = &DIGITS_UPPER_AZ[..base];
+            let values: Vec<usize> = (0..200).map(|_| rng.next_u64() as usize >> rng.below(64)).collect();
+            let column = format_column(&values, alphabet).unwrap();
+            let width = values.iter().map(|value| switch_dec_base(*value, base).unwrap().len()).max().unwrap();
+            for (line, value) in column.iter().zip(&values) {
+                assert_eq!(line.len(), width);
+                assert_eq!(*line, format!("{:>1$}", switch_dec_base(*value, base).unwrap(), width));
+            }
+            let mut out = String::new();
+            write_column(&mut out, &values, alphabet, 0, ' ').unwrap();
+            assert_eq!(out, column.iter().map(|line| format!("{}\n", line)).collect::<String>());
+        }
+    }
+
+    #[test]
+    fn column_with_outlier_and_minimum_width() {
+        let mut values = vec![1; 10_000];
+        values.push(usize::MAX);
+        let column = format_column_with(&values, &['0', '1'], 0, '0').unwrap();
+        assert_eq!(column[0], format!("{}1", "0".repeat(63)));
+        assert_eq!(column[10_000], "1".repeat(64));
+        // the minimum only widens
+        assert_eq!(format_column_with(&[255], &DIGITS_UPPER_AZ[..16], 1, '_'), Ok(vec!["FF".to_string()]));
+        assert_eq!(format_column_with(&[0], &DIGITS_UPPER_AZ[..16], 3, '_'), Ok(vec!["__0".to_string()]));
+        assert_eq!(format_column(&[], &DIGITS_UPPER_AZ[..16]), Ok(vec![]));
+        assert!(format_column(&[1], &['0']).is_err());
+    }
+
+    #[test]
+    fn fixed_visual_length() {
+        assert_eq!(to_fixed_visual(42, 10, 5), Ok("00042".to_string()));
+        assert_eq!(to_fixed_visual(99_999, 10, 5), Ok("99999".to_string()));
+        match to_fixed_visual(100_000, 10, 5) {
+            Err(Error::TooLong(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        for base in 2..37 {
+            let width = switch_dec_base(usize::MAX, base).unwrap().len();
+            assert_eq!(to_fixed_visual(1, base, width).map(|code| code.len()), Ok(width));
+            assert_eq!(to_fixed_visual(usize::MAX, base, width), switch_dec_base(usize::MAX, base));
+            assert!(to_fixed_visual(base, base, 1).is_err());
+        }
+        assert!(to_fixed_visual(0, 10, 0).is_err());
+    }
+
+    #[test]
+    fn truncated_exact_fit_and_one_over() {
+        let base36 = &DIGITS_UPPER_AZ[..36];
+        let value = 36_usize.pow(7) * 5 + 123_456;
+        let full = switch_dec_base(value, 36).unwrap();
+        assert_eq!(full.len(), 8);
+        for &side in [TruncateSide::MostSignificant, TruncateSide::LeastSignificant].iter() {
+            assert_eq!(dec2seq_truncated(value, base36, 8, '~', side), Ok(full.clone()));
+            assert_eq!(dec2seq_truncated(0, base36, 2, '~', side), Ok("0".to_string()));
+        }
+        assert_eq!(dec2seq_truncated(value, base36, 7, '~', TruncateSide::MostSignificant), Ok(format!("{}~", &full[..6])));
+        assert_eq!(dec2seq_truncated(value, base36, 7, '~', TruncateSide::LeastSignificant), Ok(format!("~{}", &full[2..])));
+        assert_eq!(dec2seq_truncated(value, base36, 2, '~', TruncateSide::MostSignificant), Ok("5~".to_string()));
+    }
+
+    #[test]
+    fn truncated_counts_chars_of_multibyte_glyphs() {
+        let glyphs = ['○', '◔', '◑', '◕', '●'];
+        // usize::MAX has 28 digits of base 5
+        for max_chars in 2..28 {
+            for &side in [TruncateSide::MostSignificant, TruncateSide::LeastSignificant].iter() {
+                let full = dec2seq(usize::MAX, &glyphs).unwrap();
+                let truncated = dec2seq_truncated(usize::MAX, &glyphs, max_chars, '…', side).unwrap();
+                assert_eq!(truncated.chars().count(), max_chars);
+                assert!(truncated.len() > max_chars);
+                let kept = truncated.replace('…', "");
+                match side {
+                    TruncateSide::MostSignificant => assert!(full.starts_with(&kept)),
+                    TruncateSide::LeastSignificant => assert!(full.ends_with(&kept)),
+                }
+            }
+        }
+        let fit = dec2seq_truncated(usize::MAX, &glyphs, 28, '…', TruncateSide::MostSignificant);
+        assert_eq!(fit, dec2seq(usize::MAX, &glyphs));
+        assert!(dec2seq_truncated(1, &glyphs, 5, '●', TruncateSide::MostSignificant).is_err());
+        assert!(dec2seq_truncated(1, &glyphs, 0, '…', TruncateSide::MostSignificant).is_err());
+    }
+}
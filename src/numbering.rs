@@ -0,0 +1,249 @@
+use {Error, NumeralSystem};
+
+
+/// Numbering system converting values to sequences and back.
+///
+/// Lets code be generic over positional systems (`NumeralSystem`) and
+/// other schemes, like `Roman` numerals or `Tally` marks.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{Error, Numbering, NumeralSystem, Roman};
+///
+/// fn next<N: Numbering>(system: &N, sequence: &str) -> Result<String, Error> {
+///     system.encode(system.decode(sequence)? + 1)
+/// }
+///
+/// assert_eq!(next(&Roman, "XIX"), Ok("XX".to_string()));
+/// assert_eq!(next(&NumeralSystem::new(&['0', '1']).unwrap(), "1011"), Ok("1100".to_string()));
+/// ```
+pub trait Numbering {
+    /// Converts `n` to its sequence.
+    fn encode(&self, n: usize) -> Result<String, Error>;
+
+    /// Converts sequence `s` back to its value.
+    fn decode(&self, s: &str) -> Result<usize, Error>;
+}
+
+impl Numbering for NumeralSystem {
+    fn encode(&self, n: usize) -> Result<String, Error> {
+        self.format(n)
+    }
+
+    fn decode(&self, s: &str) -> Result<usize, Error> {
+        self.parse(s)
+    }
+}
+
+
+const ROMAN: [(usize, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+
+/// Roman numerals from `I` (1) to `MMMCMXCIX` (3999), in canonical subtractive form.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{Numbering, Roman};
+///
+/// assert_eq!(Roman.encode(1994), Ok("MCMXCIV".to_string()));
+/// assert_eq!(Roman.decode("MCMXCIV"), Ok(1994));
+/// assert!(Roman.encode(0).is_err());
+/// assert!(Roman.decode("IIII").is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Roman;
+
+impl Numbering for Roman {
+    /// # Errors
+    ///
+    /// * Returns `Error::OutOfRange` when `n` is 0 or greater than 3999
+    fn encode(&self, n: usize) -> Result<String, Error> {
+        if n == 0 || n > 3999 {
+            return Err(Error::OutOfRange(format!("Roman numeral MUST be from 1 to 3999, given {}", n)));
+        }
+        let mut rest = n;
+        let mut numeral = String::new();
+        for &(value, symbol) in ROMAN.iter() {
+            while rest >= value {
+                numeral.push_str(symbol);
+                rest -= value;
+            }
+        }
+        Ok(numeral)
+    }
+
+    /// # Errors
+    ///
+    /// * Returns `Error::SequenceEmpty` when `s` is empty
+    ///
+    /// * Returns `Error::MissingChar` when `s` includes a char other than `IVXLCDM`
+    ///
+    /// * Returns `Error::NonCanonical` when `s` isn't the canonical numeral of its value (e.g. `IIII`)
+    fn decode(&self, s: &str) -> Result<usize, Error> {
+        if s.is_empty() {
+            return Err(Error::SequenceEmpty);
+        }
+        let mut values = Vec::with_capacity(s.len());
+        for (position, glyph) in s.chars().enumerate() {
+            let value = match glyph {
+                'I' => 1,
+                'V' => 5,
+                'X' => 10,
+                'L' => 50,
+                'C' => 100,
+                'D' => 500,
+                'M' => 1000,
+                _ => {
+                    return Err(Error::MissingChar(
+                        format!("Char {:?} at position {} not found in: \"IVXLCDM\"", glyph, position),
+                    ))
+                }
+            };
+            values.push(value);
+        }
+        let mut n: usize = 0;
+        for (index, value) in values.iter().enumerate() {
+            match values.get(index + 1) {
+                Some(next) if next > value => n = n.wrapping_sub(*value),
+                _ => n = n.wrapping_add(*value),
+            }
+        }
+        // any other spelling than the canonical one is rejected
+        match self.encode(n) {
+            Ok(ref canonical) if canonical == s => Ok(n),
+            _ => Err(Error::NonCanonical(format!("Roman numeral {:?} MUST be canonical", s))),
+        }
+    }
+}
+
+
+/// Most bytes of marks `Tally` encodes, 1 MiB.
+const TALLY_MAX_BYTES: usize = 1 << 20;
+
+
+/// Tally marks, `n` is `n` repetitions of the mark (zero is empty).
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{Numbering, Tally};
+///
+/// let tally = Tally { mark: '|' };
+/// assert_eq!(tally.encode(4), Ok("||||".to_string()));
+/// assert_eq!(tally.decode("|||"), Ok(3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tally {
+    pub mark: char,
+}
+
+impl Numbering for Tally {
+    /// # Errors
+    ///
+    /// * Returns `Error::OutOfRange` when the marks take more than 1 MiB (1_048_576 bytes)
+    ///
+    /// ```
+    /// use numsys::{Error, Numbering, Tally};
+    ///
+    /// let msg = "Tally of 1048577 marks of 1 bytes MUST take at most 1048576 bytes".to_string();
+    /// assert_eq!(Tally { mark: '|' }.encode((1 << 20) + 1), Err(Error::OutOfRange(msg)));
+    /// ```
+    fn encode(&self, n: usize) -> Result<String, Error> {
+        let width = self.mark.len_utf8();
+        match n.checked_mul(width) {
+            Some(bytes) if bytes <= TALLY_MAX_BYTES => Ok(self.mark.to_string().repeat(n)),
+            _ => Err(Error::OutOfRange(format!(
+                "Tally of {} marks of {} bytes MUST take at most {} bytes",
+                n,
+                width,
+                TALLY_MAX_BYTES
+            ))),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// * Returns `Error::MissingChar` when `s` includes a char other than the mark
+    fn decode(&self, s: &str) -> Result<usize, Error> {
+        let mut n = 0;
+        for (position, glyph) in s.chars().enumerate() {
+            if glyph != self.mark {
+                return Err(Error::MissingChar(
+                    format!("Char {:?} at position {} not found in: {:?}", glyph, position, [self.mark]),
+                ));
+            }
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use DIGITS_UPPER_AZ;
+
+    fn round_trip_all<N: Numbering>(system: &N, values: &[usize]) {
+        for value in values {
+            let encoded = system.encode(*value).unwrap();
+            assert_eq!(system.decode(&encoded), Ok(*value), "{}", encoded);
+        }
+    }
+
+    #[test]
+    fn generic_over_implementations() {
+        let values: Vec<usize> = (1..4000).collect();
+        round_trip_all(&Roman, &values);
+        round_trip_all(&NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap(), &values);
+        round_trip_all(&Tally { mark: '卌' }, &values[..100]);
+        round_trip_all(&Tally { mark: '|' }, &[0]);
+    }
+
+    #[test]
+    fn roman_known_numerals() {
+        assert_eq!(Roman.encode(3999), Ok("MMMCMXCIX".to_string()));
+        assert_eq!(Roman.encode(4), Ok("IV".to_string()));
+        assert_eq!(Roman.decode("XLII"), Ok(42));
+        for spelling in ["IIII", "VX", "IC", "XXXXX", "MMMM", "IIV"] {
+            match Roman.decode(spelling) {
+                Err(Error::NonCanonical(_)) => (),
+                other => panic!("unexpected {:?} for {}", other, spelling),
+            }
+        }
+        assert_eq!(Roman.decode(""), Err(Error::SequenceEmpty));
+        assert!(Roman.decode("xlii").is_err());
+        assert!(Roman.encode(4000).is_err());
+        assert!(Tally { mark: '|' }.decode("||/").is_err());
+    }
+
+    #[test]
+    fn tally_length_is_bounded() {
+        assert_eq!(Tally { mark: '|' }.encode(1 << 20).map(|s| s.len()), Ok(1 << 20));
+        // 3 bytes each
+        assert!(Tally { mark: '卌' }.encode((1 << 20) / 3).is_ok());
+        assert!(Tally { mark: '卌' }.encode((1 << 20) / 3 + 1).is_err());
+        for mark in ['|', '卌', '𝍸'].iter() {
+            match (Tally { mark: *mark }).encode(usize::MAX) {
+                Err(Error::OutOfRange(_)) => (),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use {check_base, checked_seq2dec, switch_dec_base, Error, DIGITS_UPPER_AZ};
+
+
+const MUL_A: u64 = 0x9E37_79B9_7F4A_7C15;
+const MUL_B: u64 = 0xBF58_4776_1CE4_E5B9;
+const HALF: u32 = usize::MAX.count_ones() / 2;
+
+/// Multiplicative inverse of odd `value` modulo `2^usize::BITS`.
+fn inverse(value: usize) -> usize {
+    // Newton's iteration doubles count of correct low bits on each step
+    let mut inv = value;
+    for _ in 0..6 {
+        inv = inv.wrapping_mul(2usize.wrapping_sub(value.wrapping_mul(inv)));
+    }
+    inv
+}
+
+fn scramble(mut value: usize, key: u64) -> usize {
+    value ^= key as usize;
+    value = value.wrapping_mul(MUL_A as usize);
+    value ^= value >> HALF;
+    value ^= key.rotate_left(32) as usize;
+    value = value.wrapping_mul(MUL_B as usize);
+    value ^ (value >> HALF)
+}
+
+fn unscramble(mut value: usize, key: u64) -> usize {
+    value ^= value >> HALF;
+    value = value.wrapping_mul(inverse(MUL_B as usize));
+    value ^= key.rotate_left(32) as usize;
+    value ^= value >> HALF;
+    value = value.wrapping_mul(inverse(MUL_A as usize));
+    value ^ key as usize
+}
+
+
+/// Converts `decimal` to `base` after scrambling it with `key`.
+///
+/// Scrambling is a bijection over `usize` (xors, odd multiplications and
+/// xor-shifts), so every value gets a distinct, random looking code.
+/// It's not encryption, codes are only hard to guess without `key`.
+/// Revert operation is `from_obfuscated`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{from_obfuscated, to_obfuscated};
+///
+/// let code = to_obfuscated(1, 36, 42).unwrap();
+/// assert_ne!(code, "1");
+/// assert_eq!(from_obfuscated(&code, 36, 42), Ok(1));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+pub fn to_obfuscated(decimal: usize, base: usize, key: u64) -> Result<String, Error> {
+    switch_dec_base(scramble(decimal, key), base)
+}
+
+
+/// Converts `sequence` of `base` made by `to_obfuscated` back to decimal.
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 36
+///
+/// * Returns `Error::MissingChar` when `sequence` includes a char out of `base`
+///
+/// * Returns `Error::Overflow` when `sequence` exceeds `usize`
+pub fn from_obfuscated(sequence: &str, base: usize, key: u64) -> Result<usize, Error> {
+    check_base(base)?;
+    let scrambled = checked_seq2dec(sequence, &DIGITS_UPPER_AZ[..base])?;
+    Ok(unscramble(scrambled, key))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_is_exact() {
+        for value in [1, 3, MUL_A as usize, MUL_B as usize, usize::MAX].iter() {
+            assert_eq!(value.wrapping_mul(inverse(*value)), 1);
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for key in [0, 1, 42, u64::MAX].iter() {
+            for value in (0..5_000).chain(usize::MAX - 100..usize::MAX) {
+                let code = to_obfuscated(value, 36, *key).unwrap();
+                assert_eq!(from_obfuscated(&code, 36, *key), Ok(value));
+            }
+        }
+    }
+
+    #[test]
+    fn consecutive_inputs_are_not_consecutive() {
+        for value in 0..1000 {
+            let a = scramble(value, 7);
+            let b = scramble(value + 1, 7);
+            assert!(a.wrapping_sub(b) > 1000 && b.wrapping_sub(a) > 1000);
+        }
+        assert_ne!(to_obfuscated(5, 16, 7), to_obfuscated(5, 16, 8));
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use digits::value_at;
+use {positional_alphabet, Error};
+
+
+/// Negates least significant first `digits` modulo `base^digits.len()` (complement plus one).
+fn negate_lsb(digits: &mut [usize], base: usize) {
+    let mut carry = 1;
+    for digit in digits.iter_mut() {
+        let total = base - 1 - *digit + carry;
+        *digit = total % base;
+        carry = total / base;
+    }
+}
+
+/// Returns value of least significant first `digits`, or `None` when it exceeds `u128`.
+fn value_lsb(digits: &[usize], base: usize) -> Option<u128> {
+    digits
+        .iter()
+        .rev()
+        .try_fold(0_u128, |value, digit| value.checked_mul(base as u128)?.checked_add(*digit as u128))
+}
+
+fn residue_digits(sequence: &str, char2val: &[char], map: &HashMap<char, usize>) -> Result<Vec<usize>, Error> {
+    let mut digits = sequence
+        .chars()
+        .enumerate()
+        .map(|(position, glyph)| value_at(glyph, Some(position), map, char2val))
+        .collect::<Result<Vec<_>, _>>()?;
+    digits.reverse();
+    Ok(digits)
+}
+
+
+/// Converts `value` to its residue modulo `base^k`, as exactly `k` digits of `char2val`.
+///
+/// It's the truncated p-adic expansion: negative values are their positive
+/// residue, so `-1` is all the highest digits. Digits are computed one by
+/// one, `base^k` is never needed, so any `k` is fine.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::int2padic;
+///
+/// let dec: Vec<char> = "0123456789".chars().collect();
+/// assert_eq!(int2padic(42, 10, 4, &dec), Ok("0042".to_string()));
+/// assert_eq!(int2padic(-1, 10, 4, &dec), Ok("9999".to_string()));
+/// assert_eq!(int2padic(-42, 10, 4, &dec), Ok("9958".to_string()));
+/// assert_eq!(int2padic(12345, 10, 3, &dec), Ok("345".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::LengthMismatch` when `char2val` length isn't `base`
+pub fn int2padic(value: i64, base: usize, k: usize, char2val: &[char]) -> Result<String, Error> {
+    positional_alphabet(char2val)?;
+    if char2val.len() != base {
+        return Err(Error::LengthMismatch(
+            format!("Alphabet MUST have {} chars, given {}", base, char2val.len()),
+        ));
+    }
+    let mut rest = value.unsigned_abs();
+    let mut digits = Vec::with_capacity(k);
+    for _ in 0..k {
+        digits.push((rest % base as u64) as usize);
+        rest /= base as u64;
+    }
+    if value < 0 {
+        negate_lsb(&mut digits, base);
+    }
+    Ok(digits.iter().rev().map(|digit| char2val[*digit]).collect())
+}
+
+
+/// Converts residue `sequence` of `char2val` back to an integer, the inverse of `int2padic`.
+///
+/// Modulo is `base^k`, with `k` the length of `sequence`. With `signed`
+/// set the residue is symmetric: the upper half of residues is negative,
+/// from `-base^k / 2` up to `(base^k - 1) / 2`. Otherwise all are positive.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::padic2int;
+///
+/// let dec: Vec<char> = "0123456789".chars().collect();
+/// assert_eq!(padic2int("9958", &dec, true), Ok(-42));
+/// assert_eq!(padic2int("9958", &dec, false), Ok(9958));
+/// assert_eq!(padic2int("5000", &dec, true), Ok(-5000));
+/// assert_eq!(padic2int("4999", &dec, true), Ok(4999));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// * Returns `Error::Overflow` when the integer exceeds `i64`
+pub fn padic2int(sequence: &str, char2val: &[char], signed: bool) -> Result<i64, Error> {
+    let map = positional_alphabet(char2val)?;
+    let base = char2val.len();
+    let residue = residue_digits(sequence, char2val, &map)?;
+    let overflow = || Error::Overflow(format!("Value of {:?} exceeds i64", sequence));
+    let mut negated = residue.clone();
+    negate_lsb(&mut negated, base);
+    // the smaller of residue and its negation is the magnitude, a tie is negative
+    if signed && negated.iter().rev().le(residue.iter().rev()) && residue.iter().any(|digit| *digit != 0) {
+        let magnitude = value_lsb(&negated, base).ok_or_else(overflow)?;
+        if magnitude > i64::MIN.unsigned_abs() as u128 {
+            return Err(overflow());
+        }
+        return Ok((magnitude as i128).wrapping_neg() as i64);
+    }
+    let magnitude = value_lsb(&residue, base).ok_or_else(overflow)?;
+    if magnitude > i64::MAX as u128 {
+        return Err(overflow());
+    }
+    Ok(magnitude as i64)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use {add_in_base, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn minus_one_is_all_max_digits() {
+        for base in 2..37 {
+            let alphabet = &DIGITS_UPPER_AZ[..base];
+            for &k in [1, 5, 100].iter() {
+                let expected: String = ::std::iter::repeat(alphabet[base - 1]).take(k).collect();
+                assert_eq!(int2padic(-1, base, k, alphabet), Ok(expected.clone()));
+                assert_eq!(padic2int(&expected, alphabet, true), Ok(-1));
+            }
+        }
+        assert_eq!(int2padic(0, 10, 0, &DIGITS_UPPER_AZ[..10]), Ok(String::new()));
+        assert!(int2padic(1, 10, 3, &DIGITS_UPPER_AZ[..16]).is_err());
+    }
+
+    #[test]
+    fn addition_of_representations_is_addition_mod_power() {
+        let mut rng = XorShift::new(235);
+        for _ in 0..2000 {
+            let base = 2 + rng.below(35);
+            let alphabet = &DIGITS_UPPER_AZ[..base];
+            let k = 1 + rng.below(30);
+            let (a, b) = ((rng.next_u64() >> 34) as i64 - (1 << 29), (rng.next_u64() >> 34) as i64 - (1 << 29));
+            let (a_seq, b_seq) = (int2padic(a, base, k, alphabet).unwrap(), int2padic(b, base, k, alphabet).unwrap());
+            let (sum, _) = add_in_base(&a_seq, &b_seq, alphabet).unwrap();
+            // the sum modulo base^k is its lowest k digits
+            let padded = format!("{}{}", alphabet[0].to_string().repeat(k), sum);
+            let truncated: String = padded.chars().skip(padded.chars().count() - k).collect();
+            assert_eq!(truncated, int2padic(a + b, base, k, alphabet).unwrap(), "{} + {} base {}", a, b, base);
+        }
+    }
+
+    #[test]
+    fn signed_round_trip_and_limits() {
+        let mut rng = XorShift::new(2350);
+        for base in 2..37 {
+            let alphabet = &DIGITS_UPPER_AZ[..base];
+            for _ in 0..100 {
+                let value = rng.next_u64() as i64 >> rng.below(64);
+                // 64 digits hold any i64 in any base
+                let residue = int2padic(value, base, 64, alphabet).unwrap();
+                assert_eq!(padic2int(&residue, alphabet, true), Ok(value));
+            }
+        }
+        let binary = ['0', '1'];
+        assert_eq!(padic2int(&int2padic(i64::MIN, 2, 64, &binary).unwrap(), &binary, true), Ok(i64::MIN));
+        assert_eq!(padic2int(&int2padic(i64::MAX, 2, 64, &binary).unwrap(), &binary, true), Ok(i64::MAX));
+        assert!(padic2int(&"1".repeat(64), &binary, false).is_err());
+        assert!(padic2int(&format!("1{}", "0".repeat(64)), &binary, true).is_err());
+        assert_eq!(padic2int("", &binary, true), Ok(0));
+        assert_eq!(padic2int("10", &binary, true), Ok(-2));
+    }
+}
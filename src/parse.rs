@@ -0,0 +1,882 @@
+use alphabet::case_folded_chars;
+use digits::{missing_char, value_at};
+use {check_alphabet_cap, positional_alphabet, Error};
+
+
+/// Options of `seq2dec_with_options`, all disabled by default.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::ParseOptions;
+///
+/// let options = ParseOptions { normalize: true, ..ParseOptions::default() };
+/// assert!(options.normalize);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseOptions {
+    /// Replaces compatibility forms of digits and letters (fullwidth,
+    /// superscript, subscript, circled and mathematical ones) with their
+    /// ASCII counterparts before the lookup, like NFKC does for them.
+    /// E.g. `'１'` becomes `'1'` and `'⑫'` becomes `"12"`.
+    pub normalize: bool,
+    /// Rejects sequences of more than one digit starting with the zero
+    /// glyph, so each value has exactly one accepted form. The first digit
+    /// is checked after normalization.
+    pub reject_leading_zeros: bool,
+    /// Accepts both cases of each char. Alphabets where it would make two
+    /// chars one digit (e.g. base 62) are rejected up front with
+    /// `Error::CaseFoldCollision`, see `Alphabet::case_fold_safety`.
+    pub case_insensitive: bool,
+    /// Rejects sequences of more than `max_len` chars, before any char is
+    /// looked up, so the work and the error messages are bounded by it.
+    pub max_len: Option<usize>,
+    /// Rejects the empty sequence instead of decoding it to 0.
+    pub reject_empty: bool,
+    /// Rejects alphabets of more than `max_alphabet` chars with
+    /// `Error::AlphabetTooLarge`, before their map is built. It's a guard
+    /// against a huge alphabet passed by mistake, see `dec2seq_capped`.
+    pub max_alphabet: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Returns options for untrusted input, the profile of `parse_hardened`.
+    ///
+    /// The contract, kept by every option (each new one MUST state how it
+    /// interacts with this profile):
+    ///
+    /// * sequences of more than `max_len` chars are rejected, found in at most `max_len + 1` chars,
+    /// * overflow is an error, never saturated or wrapped,
+    /// * leading zeros are rejected, so each value has exactly one accepted form,
+    /// * no normalization and no case folding, only the chars of the alphabet are digits,
+    /// * no separator or whitespace is tolerated (see `seq2dec_sanitized` for that),
+    /// * the empty sequence is rejected,
+    /// * time is linear in the sequence length, the first invalid char ends it,
+    /// * allocations are the alphabet map, regardless of the sequence length; only an error allocates its message.
+    /// * error messages quote at most 32 chars of the sequence, so they stay small too,
+    /// * the alphabet is the caller's, not the input, so `max_alphabet` is left off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use numsys::ParseOptions;
+    ///
+    /// let options = ParseOptions::hardened(64);
+    /// assert_eq!(options.max_len, Some(64));
+    /// assert!(options.reject_leading_zeros && options.reject_empty);
+    /// assert!(!options.normalize && !options.case_insensitive);
+    /// ```
+    pub fn hardened(max_len: usize) -> ParseOptions {
+        ParseOptions {
+            normalize: false,
+            reject_leading_zeros: true,
+            case_insensitive: false,
+            max_len: Some(max_len),
+            reject_empty: true,
+            max_alphabet: None,
+        }
+    }
+}
+
+
+/// What `decode` does when the value exceeds `usize`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{decode, OverflowMode};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert!(decode("10000000000000000", &hex, OverflowMode::Error).is_err());
+/// assert_eq!(decode("10000000000000000", &hex, OverflowMode::Saturate), Ok(usize::MAX));
+/// assert_eq!(decode("10000000000000001", &hex, OverflowMode::Wrap), Ok(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowMode {
+    /// Returns `Error::Overflow`.
+    Error,
+    /// Clamps the value to `usize::MAX`.
+    Saturate,
+    /// Keeps the value modulo `usize::MAX + 1`, like `wrapping_mul` and `wrapping_add` do.
+    Wrap,
+}
+
+
+/// Returns `dec` with `digit` appended in `base`, handling overflow as `mode` says.
+///
+/// It's the one overflow check of all decoding, `overflow` makes the
+/// error of `OverflowMode::Error`, usually `value_overflow`. A saturated
+/// value stays `usize::MAX` as more digits are appended.
+pub(crate) fn accumulate<F: FnOnce() -> Error>(dec: usize, base: usize, digit: usize, mode: OverflowMode, overflow: F)
+    -> Result<usize, Error> {
+    match dec.checked_mul(base).and_then(|d| d.checked_add(digit)) {
+        Some(d) => Ok(d),
+        None => match mode {
+            OverflowMode::Error => Err(overflow()),
+            OverflowMode::Saturate => Ok(usize::MAX),
+            OverflowMode::Wrap => Ok(dec.wrapping_mul(base).wrapping_add(digit)),
+        },
+    }
+}
+
+
+/// Returns `Error::Overflow` of `sequence` whose value exceeds `usize`.
+pub(crate) fn value_overflow(sequence: &str) -> Error {
+    Error::Overflow(format!("Value of {} exceeds usize", echo(sequence)))
+}
+
+
+/// Most chars of an input quoted by `echo`.
+const ECHOED_CHARS: usize = 32;
+
+/// Returns `sequence` quoted for an error message, cut to its first 32 chars and its length in bytes if longer.
+///
+/// Untrusted input may be huge, so a message quoting it stays bounded.
+pub(crate) fn echo(sequence: &str) -> String {
+    match sequence.char_indices().nth(ECHOED_CHARS) {
+        Some((end, _)) => format!("{:?}… ({} bytes)", &sequence[..end], sequence.len()),
+        None => format!("{:?}", sequence),
+    }
+}
+
+
+/// Returns `chars` of an input, counted by `testutil::inspected` in tests.
+#[cfg(test)]
+fn counted<I: Iterator>(chars: I) -> impl Iterator<Item = I::Item> {
+    chars.inspect(|_| ::testutil::count_inspected())
+}
+
+#[cfg(not(test))]
+fn counted<I: Iterator>(chars: I) -> I {
+    chars
+}
+
+
+/// Appends NFKC form of `glyph` to `out`, for digit-relevant chars only.
+fn push_normalized(glyph: char, out: &mut Vec<char>) {
+    let code = glyph as u32;
+    let shift = |base: u32, first: char| {
+        ::std::char::from_u32(first as u32 + code - base).expect("ASCII offset")
+    };
+    match code {
+        // fullwidth digits and letters
+        0xFF10..=0xFF19 => out.push(shift(0xFF10, '0')),
+        0xFF21..=0xFF3A => out.push(shift(0xFF21, 'A')),
+        0xFF41..=0xFF5A => out.push(shift(0xFF41, 'a')),
+        // superscripts and subscripts
+        0x2070 => out.push('0'),
+        0x00B9 => out.push('1'),
+        0x00B2 => out.push('2'),
+        0x00B3 => out.push('3'),
+        0x2074..=0x2079 => out.push(shift(0x2074, '4')),
+        0x2080..=0x2089 => out.push(shift(0x2080, '0')),
+        // circled numbers from 1 to 20
+        0x2460..=0x2473 => out.extend((code - 0x2460 + 1).to_string().chars()),
+        // mathematical bold, double-struck, sans-serif and monospace digits
+        0x1D7CE..=0x1D7FF => out.push(shift(0x1D7CE + (code - 0x1D7CE) / 10 * 10, '0')),
+        _ => out.push(glyph),
+    }
+}
+
+
+/// Converts `sequence` to decimal using `char2val` translation and `options`.
+///
+/// Unlike `seq2dec` overflow is reported, and error positions always refer
+/// to chars of the original `sequence`, even if normalization changed its length.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{seq2dec_with_options, ParseOptions};
+///
+/// let dec = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+/// let options = ParseOptions { normalize: true, ..ParseOptions::default() };
+/// assert_eq!(seq2dec_with_options("１２３", &dec, &options), Ok(123));
+/// assert_eq!(seq2dec_with_options("⑫3", &dec, &options), Ok(123));
+/// assert_eq!(seq2dec_with_options("123", &dec, &ParseOptions::default()), Ok(123));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// ```
+/// use numsys::{seq2dec_with_options, Error, ParseOptions};
+///
+/// let msg = "Char \'²\' at position 1 not found in: [\'0\', \'1\', \'2\']".to_string();
+/// let result = seq2dec_with_options("1²", &['0', '1', '2'], &ParseOptions::default());
+/// assert_eq!(result, Err(Error::MissingChar(msg)));
+/// ```
+///
+/// * Returns `Error::NonCanonical` when `reject_leading_zeros` is set and `sequence` has a leading zero
+///
+/// ```
+/// use numsys::{seq2dec_with_options, Error, ParseOptions};
+///
+/// let options = ParseOptions { reject_leading_zeros: true, ..ParseOptions::default() };
+/// let msg = "Leading zero \'0\' at position 0 MUST NOT be in: \"0FF\"".to_string();
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(seq2dec_with_options("0FF", &hex, &options), Err(Error::NonCanonical(msg)));
+/// assert_eq!(seq2dec_with_options("FF", &hex, &options), Ok(255));
+/// assert_eq!(seq2dec_with_options("0", &hex, &options), Ok(0));
+/// ```
+///
+/// * Returns `Error::CaseFoldCollision` when `case_insensitive` is set and `char2val` has chars differing only by case
+///
+/// ```
+/// use numsys::{seq2dec_with_options, ParseOptions};
+///
+/// let options = ParseOptions { case_insensitive: true, ..ParseOptions::default() };
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(seq2dec_with_options("ff", &hex, &options), Ok(255));
+/// let base62: Vec<char> = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz".chars().collect();
+/// assert!(seq2dec_with_options("1", &base62, &options).is_err());
+/// ```
+///
+/// * Returns `Error::InputTooLong` when `max_len` is set and `sequence` has more chars
+///
+/// ```
+/// use numsys::{seq2dec_with_options, Error, ParseOptions};
+///
+/// let options = ParseOptions { max_len: Some(3), ..ParseOptions::default() };
+/// let msg = "Sequence MUST have at most 3 chars".to_string();
+/// assert_eq!(seq2dec_with_options("1000", &['0', '1'], &options), Err(Error::InputTooLong(msg)));
+/// ```
+///
+/// * Returns `Error::SequenceEmpty` when `reject_empty` is set and `sequence` is empty
+///
+/// * Returns `Error::AlphabetTooLarge` when `max_alphabet` is set and `char2val` has more chars
+///
+/// ```
+/// use numsys::{seq2dec_with_options, Error, ParseOptions};
+///
+/// let options = ParseOptions { max_alphabet: Some(16), ..ParseOptions::default() };
+/// let base36: Vec<char> = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect();
+/// let msg = "Alphabet MUST have at most 16 chars, given 36".to_string();
+/// assert_eq!(seq2dec_with_options("Z", &base36, &options), Err(Error::AlphabetTooLarge(msg)));
+/// assert_eq!(seq2dec_with_options("F", &base36[..16], &options), Ok(15));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn seq2dec_with_options(sequence: &str, char2val: &[char], options: &ParseOptions) -> Result<usize, Error> {
+    if let Some(max_alphabet) = options.max_alphabet {
+        check_alphabet_cap(char2val, max_alphabet)?;
+    }
+    let mut map = positional_alphabet(char2val)?;
+    if options.case_insensitive {
+        map = case_folded_chars(char2val)?;
+    }
+    if let Some(max_len) = options.max_len {
+        // chars are never more than bytes, so only longer ones are counted
+        if sequence.len() > max_len && counted(sequence.chars()).nth(max_len).is_some() {
+            return Err(Error::InputTooLong(format!("Sequence MUST have at most {} chars", max_len)));
+        }
+    }
+    if options.reject_empty && sequence.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    let base = char2val.len();
+    let mut normalized = Vec::new();
+    let mut dec: usize = 0;
+    let mut digits = 0;
+    let mut leading_zero = None;
+    for (position, glyph) in counted(sequence.chars()).enumerate() {
+        normalized.clear();
+        if options.normalize {
+            push_normalized(glyph, &mut normalized);
+        } else {
+            normalized.push(glyph);
+        }
+        for digit in &normalized {
+            // reported as the original char, which normalization may have split
+            let value = map.get(digit).ok_or_else(|| missing_char(glyph, Some(position), char2val))?;
+            if digits == 0 && *value == 0 {
+                leading_zero = Some((position, glyph));
+            }
+            digits += 1;
+            if options.reject_leading_zeros && digits > 1 {
+                if let Some((position, glyph)) = leading_zero {
+                    return Err(Error::NonCanonical(format!(
+                        "Leading zero {:?} at position {} MUST NOT be in: {}",
+                        glyph,
+                        position,
+                        echo(sequence)
+                    )));
+                }
+            }
+            dec = accumulate(dec, base, *value, OverflowMode::Error, || value_overflow(sequence))?;
+        }
+    }
+    Ok(dec)
+}
+
+
+/// Converts untrusted `sequence` to decimal using `char2val` translation, with `ParseOptions::hardened`.
+///
+/// See `ParseOptions::hardened` for the guarantees.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::parse_hardened;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(parse_hardened("FF", &hex, 16), Ok(255));
+/// assert!(parse_hardened("0FF", &hex, 16).is_err());
+/// assert!(parse_hardened("ff", &hex, 16).is_err());
+/// assert!(parse_hardened("F F", &hex, 16).is_err());
+/// assert!(parse_hardened("", &hex, 16).is_err());
+/// assert!(parse_hardened(&"F".repeat(17), &hex, 16).is_err());
+/// ```
+///
+/// # Errors
+///
+/// * Returns errors of `seq2dec_with_options`
+pub fn parse_hardened(sequence: &str, char2val: &[char], max_len: usize) -> Result<usize, Error> {
+    seq2dec_with_options(sequence, char2val, &ParseOptions::hardened(max_len))
+}
+
+
+/// Converts `sequence` to decimal using `char2val` translation, accepting only the canonical form.
+///
+/// Each value has exactly one accepted sequence, zero being a single zero
+/// glyph. So overlong forms with leading zero glyphs and the empty sequence
+/// are rejected, which matters when the sequence is signed or hashed.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2dec_strict_canonical;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(seq2dec_strict_canonical("FF", &hex), Ok(255));
+/// assert_eq!(seq2dec_strict_canonical("0", &hex), Ok(0));
+/// assert!(seq2dec_strict_canonical("00FF", &hex).is_err());
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// * Returns `Error::NonCanonical` when `sequence` is empty or has a leading zero glyph
+///
+/// ```
+/// use numsys::{seq2dec_strict_canonical, Error};
+///
+/// let msg = "Leading zero \'0\' at position 0 MUST NOT be in: \"00FF\"".to_string();
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(seq2dec_strict_canonical("00FF", &hex), Err(Error::NonCanonical(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn seq2dec_strict_canonical(sequence: &str, char2val: &[char]) -> Result<usize, Error> {
+    let options = ParseOptions { reject_leading_zeros: true, ..ParseOptions::default() };
+    let dec = seq2dec_with_options(sequence, char2val, &options)?;
+    if sequence.is_empty() {
+        return Err(Error::NonCanonical("Sequence MUST have at least 1 char, given 0".into()));
+    }
+    Ok(dec)
+}
+
+
+/// Converts `sequence` to decimal using `char2val` translation, handling overflow as `mode` says.
+///
+/// All chars are checked even after an overflow, so an invalid sequence is
+/// never accepted, whatever the mode.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{decode, OverflowMode};
+///
+/// assert_eq!(decode("1010", &['0', '1'], OverflowMode::Error), Ok(10));
+/// assert_eq!(decode(&"1".repeat(65), &['0', '1'], OverflowMode::Saturate), Ok(usize::MAX));
+/// assert_eq!(decode(&"1".repeat(65), &['0', '1'], OverflowMode::Wrap), Ok(usize::MAX));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// * Returns `Error::Overflow` when `mode` is `OverflowMode::Error` and value exceeds `usize`
+///
+/// ```
+/// use numsys::{decode, Error, OverflowMode};
+///
+/// let msg = "Value of \"10000000000000000\" exceeds usize".to_string();
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(decode("10000000000000000", &hex, OverflowMode::Error), Err(Error::Overflow(msg)));
+/// ```
+pub fn decode(sequence: &str, char2val: &[char], mode: OverflowMode) -> Result<usize, Error> {
+    let map = positional_alphabet(char2val)?;
+    let base = char2val.len();
+    let mut dec: usize = 0;
+    for (position, glyph) in sequence.chars().enumerate() {
+        let value = value_at(glyph, Some(position), &map, char2val)?;
+        dec = accumulate(dec, base, value, mode, || value_overflow(sequence))?;
+    }
+    Ok(dec)
+}
+
+
+/// Converts `sequence` to decimal using `char2val` translation, best effort: it never fails.
+///
+/// It's lossy, for display only: unknown chars count as a digit of
+/// `fallback_value`, the value saturates at `usize::MAX`, and a char
+/// duplicated in `char2val` has the value of its first occurrence. An
+/// alphabet of less than 2 chars has no positional value, so it gives
+/// `fallback_value`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2dec_lossy;
+///
+/// let dec: Vec<char> = "0123456789".chars().collect();
+/// assert_eq!(seq2dec_lossy("1?3", &dec, 0), 103);
+/// assert_eq!(seq2dec_lossy("1?3", &dec, 9), 193);
+/// assert_eq!(seq2dec_lossy(&"9".repeat(30), &dec, 0), usize::MAX);
+/// ```
+pub fn seq2dec_lossy(sequence: &str, char2val: &[char], fallback_value: usize) -> usize {
+    let base = char2val.len();
+    if base < 2 {
+        return fallback_value;
+    }
+    sequence.chars().fold(0_usize, |dec, glyph| {
+        let value = char2val.iter().position(|g| *g == glyph).unwrap_or(fallback_value);
+        dec.saturating_mul(base).saturating_add(value)
+    })
+}
+
+
+/// Tells whether `glyph` is one of the invisible chars removed by `sanitize_input`.
+fn is_invisible(glyph: char) -> bool {
+    match glyph {
+        // soft hyphen and Mongolian vowel separator
+        '\u{AD}' | '\u{180E}' => true,
+        // zero width space, non-joiner, joiner, left-to-right and right-to-left marks
+        '\u{200B}'..='\u{200F}' => true,
+        // bidi embeddings and overrides
+        '\u{202A}'..='\u{202E}' => true,
+        // word joiner and invisible operators
+        '\u{2060}'..='\u{2064}' => true,
+        // bidi isolates
+        '\u{2066}'..='\u{2069}' => true,
+        // byte order mark (zero width no-break space)
+        '\u{FEFF}' => true,
+        _ => false,
+    }
+}
+
+
+/// Removes byte order mark and other invisible chars from `s`, wherever they are.
+///
+/// Removed code points are:
+///
+/// * `U+00AD` soft hyphen
+/// * `U+180E` Mongolian vowel separator
+/// * `U+200B`..=`U+200F` zero width space, non-joiner, joiner and directional marks
+/// * `U+202A`..=`U+202E` directional embeddings and overrides
+/// * `U+2060`..=`U+2064` word joiner and invisible operators
+/// * `U+2066`..=`U+2069` directional isolates
+/// * `U+FEFF` byte order mark
+///
+/// Other chars, whitespace included, are kept.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::sanitize_input;
+///
+/// assert_eq!(sanitize_input("\u{FEFF}12\u{200B}3"), "123");
+/// assert_eq!(sanitize_input(" 1 "), " 1 ");
+/// ```
+pub fn sanitize_input(s: &str) -> String {
+    s.chars().filter(|glyph| !is_invisible(*glyph)).collect()
+}
+
+
+/// Converts `sequence` to decimal using `char2val` translation, after `sanitize_input`.
+///
+/// Error positions refer to chars of the sanitized sequence. Invisible
+/// chars are removed even if `char2val` includes them.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2dec_sanitized;
+///
+/// let dec = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+/// assert_eq!(seq2dec_sanitized("\u{FEFF}42", &dec), Ok(42));
+/// ```
+///
+/// # Errors
+///
+/// Same as `seq2dec_with_options`.
+pub fn seq2dec_sanitized(sequence: &str, char2val: &[char]) -> Result<usize, Error> {
+    seq2dec_with_options(&sanitize_input(sequence), char2val, &ParseOptions::default())
+}
+
+
+/// Converts `sequence` to decimal, allowing only some digit values at each position.
+///
+/// `allowed_per_position` lists digit values allowed at each position of
+/// `sequence`, the most significant first, e.g. a version nibble allowing
+/// only `1` to `5`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_with_allowed;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let any: Vec<usize> = (0..16).collect();
+/// let allowed: [&[usize]; 3] = [&[1, 2, 3, 4, 5], &any, &any];
+/// assert_eq!(decode_with_allowed("4FF", &hex, &allowed), Ok(0x4FF));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::LengthMismatch` when `sequence` length differs from `allowed_per_position` one
+///
+/// * Returns `Error::MissingChar` when `alphabet` missing a char or more
+///
+/// * Returns `Error::DisallowedDigit` when a digit value isn't allowed at its position
+///
+/// ```
+/// use numsys::{decode_with_allowed, Error};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let allowed: [&[usize]; 2] = [&[1, 2, 3, 4, 5], &[0, 15]];
+/// let msg = "Char \'7\' at position 0 has digit 7 which MUST be one of [1, 2, 3, 4, 5]".to_string();
+/// assert_eq!(decode_with_allowed("7F", &hex, &allowed), Err(Error::DisallowedDigit(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn decode_with_allowed(sequence: &str, alphabet: &[char], allowed_per_position: &[&[usize]]) -> Result<usize, Error> {
+    let map = positional_alphabet(alphabet)?;
+    let length = sequence.chars().count();
+    if length != allowed_per_position.len() {
+        return Err(Error::LengthMismatch(format!(
+            "Sequence MUST have {} chars, given {}",
+            allowed_per_position.len(),
+            length
+        )));
+    }
+    let base = alphabet.len();
+    let mut dec: usize = 0;
+    for ((position, glyph), allowed) in sequence.chars().enumerate().zip(allowed_per_position) {
+        let value = value_at(glyph, Some(position), &map, alphabet)?;
+        if !allowed.contains(&value) {
+            return Err(Error::DisallowedDigit(format!(
+                "Char {:?} at position {} has digit {} which MUST be one of {:?}",
+                glyph,
+                position,
+                value,
+                allowed
+            )));
+        }
+        dec = accumulate(dec, base, value, OverflowMode::Error, || value_overflow(sequence))?;
+    }
+    Ok(dec)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::{allocations, inspected};
+    use {DIGITS, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn hardened_profile_sets_every_field() {
+        // destructured without `..`, so a new field fails to compile here until its
+        // interaction with the hardened profile is decided and asserted
+        let ParseOptions { normalize, reject_leading_zeros, case_insensitive, max_len, reject_empty, max_alphabet } =
+            ParseOptions::hardened(100);
+        assert!(!normalize);
+        assert!(reject_leading_zeros);
+        assert!(!case_insensitive);
+        assert_eq!(max_len, Some(100));
+        assert!(reject_empty);
+        assert_eq!(max_alphabet, None);
+    }
+
+    #[test]
+    fn hardened_guarantees() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        assert_eq!(parse_hardened("FFFFFFFFFFFFFFFF", hex, 16), Ok(usize::MAX));
+        assert_eq!(parse_hardened("0", hex, 16), Ok(0));
+        let too_long = Err(Error::InputTooLong("Sequence MUST have at most 16 chars".into()));
+        assert_eq!(parse_hardened("10000000000000000", hex, 16), too_long);
+        assert!(parse_hardened("10000000000000000", hex, 17).is_err());
+        assert_eq!(parse_hardened("", hex, 16), Err(Error::SequenceEmpty));
+        assert!(parse_hardened("00", hex, 16).is_err());
+        assert!(parse_hardened("ff", hex, 16).is_err());
+        assert!(parse_hardened("ＦＦ", hex, 16).is_err());
+        for separated in ["F_F", "F F", "F-F", "F,F", " FF", "FF\n"].iter() {
+            assert!(parse_hardened(separated, hex, 16).is_err(), "{:?}", separated);
+        }
+        // the cap counts chars, not bytes
+        assert_eq!(parse_hardened("☆", &['★', '☆'], 1), Ok(1));
+        assert!(parse_hardened("☆★", &['★', '☆'], 1).is_err());
+    }
+
+    #[test]
+    fn hardened_allocations_do_not_grow_with_input() {
+        let allocations_of = |sequence: &str| {
+            let before = allocations();
+            let result = parse_hardened(sequence, &DIGITS[..2], 1_000_000);
+            (allocations() - before, result.is_ok())
+        };
+        let short = allocations_of("101");
+        assert!(short.1);
+        assert_eq!(allocations_of(&"1".repeat(64)), short);
+        // invalid ones only add their message
+        let invalid = allocations_of("12");
+        assert_eq!(allocations_of(&format!("{}2", "1".repeat(60))), invalid);
+    }
+
+    #[test]
+    fn hardened_rejection_work_is_bounded() {
+        let inspected_by = |sequence: &str, max_len: usize| {
+            let before = inspected();
+            let result = parse_hardened(sequence, &DIGITS, max_len);
+            (inspected() - before, result.is_ok())
+        };
+        let huge = "1".repeat(4_000_000);
+        // too long is found in `max_len + 1` chars
+        assert_eq!(inspected_by(&huge, 64), (65, false));
+        // within the cap the first bad char ends it: a separator, overflow or a leading zero
+        assert_eq!(inspected_by(&"_".repeat(4_000_000), 8_000_000), (1, false));
+        assert_eq!(inspected_by(&format!("{}_{}", "1".repeat(9), huge), 8_000_000), (10, false));
+        assert_eq!(inspected_by(&huge, 8_000_000), (21, false));
+        assert_eq!(inspected_by(&"0".repeat(4_000_000), 8_000_000), (2, false));
+        // a sequence within the cap by bytes is read once
+        assert_eq!(inspected_by("255", 3), (3, true));
+        assert_eq!(inspected_by("255", 2), (3, false));
+    }
+
+    fn normalizing() -> ParseOptions {
+        ParseOptions { normalize: true, ..ParseOptions::default() }
+    }
+
+    #[test]
+    fn fullwidth_digits_parse_as_decimal() {
+        assert_eq!(seq2dec_with_options("０１２３４５６７８９", &DIGITS, &normalizing()), Ok(123_456_789));
+        assert!(seq2dec_with_options("ｆＦ", &DIGITS_UPPER_AZ[..16], &normalizing()).is_err());
+        assert_eq!(seq2dec_with_options("ＦＦ", &DIGITS_UPPER_AZ[..16], &normalizing()), Ok(255));
+    }
+
+    #[test]
+    fn superscripts_and_math_digits() {
+        assert_eq!(seq2dec_with_options("¹²³⁴⁵⁶⁷⁸⁹⁰", &DIGITS, &normalizing()), Ok(1_234_567_890));
+        assert_eq!(seq2dec_with_options("₄₂", &DIGITS, &normalizing()), Ok(42));
+        assert_eq!(seq2dec_with_options("𝟏𝟘𝟫𝟿", &DIGITS, &normalizing()), Ok(1099));
+    }
+
+    #[test]
+    fn superscript_rejected_without_normalization() {
+        let result = seq2dec_with_options("2³", &DIGITS, &ParseOptions::default());
+        let msg = format!("Char '³' at position 1 not found in: {:?}", &DIGITS[..]);
+        assert_eq!(result, Err(Error::MissingChar(msg)));
+    }
+
+    #[test]
+    fn positions_refer_to_original_string() {
+        // '⑳' expands to two digits, the bad char is still at position 2
+        let result = seq2dec_with_options("⑳１x", &DIGITS, &normalizing());
+        let msg = format!("Char 'x' at position 2 not found in: {:?}", &DIGITS[..]);
+        assert_eq!(result, Err(Error::MissingChar(msg)));
+        // a normalized char which isn't a digit is reported in its original form
+        let result = seq2dec_with_options("1ｚ", &DIGITS, &normalizing());
+        let msg = format!("Char 'ｚ' at position 1 not found in: {:?}", &DIGITS[..]);
+        assert_eq!(result, Err(Error::MissingChar(msg)));
+        assert_eq!(seq2dec_with_options("⑳⑩", &DIGITS, &normalizing()), Ok(2010));
+    }
+
+    #[test]
+    fn leading_bom_is_stripped() {
+        let pasted = "\u{FEFF}FF";
+        assert!(seq2dec_with_options(pasted, &DIGITS_UPPER_AZ[..16], &ParseOptions::default()).is_err());
+        assert_eq!(seq2dec_sanitized(pasted, &DIGITS_UPPER_AZ[..16]), Ok(255));
+        assert_eq!(seq2dec_sanitized("\u{200D}1\u{2060}0\u{AD}\u{200E}", &DIGITS), Ok(10));
+        let msg = format!("Char ' ' at position 1 not found in: {:?}", &DIGITS[..]);
+        assert_eq!(seq2dec_sanitized("\u{FEFF}1 2", &DIGITS), Err(Error::MissingChar(msg)));
+    }
+
+    #[test]
+    fn leading_zeros_rejected_on_request() {
+        let strict = ParseOptions { reject_leading_zeros: true, ..ParseOptions::default() };
+        assert_eq!(seq2dec_with_options("0", &DIGITS, &strict), Ok(0));
+        assert_eq!(seq2dec_with_options("10", &DIGITS, &strict), Ok(10));
+        assert_eq!(seq2dec_with_options("00", &DIGITS, &ParseOptions::default()), Ok(0));
+        let msg = "Leading zero '0' at position 0 MUST NOT be in: \"00\"".to_string();
+        assert_eq!(seq2dec_with_options("00", &DIGITS, &strict), Err(Error::NonCanonical(msg)));
+        // the zero glyph of a custom alphabet isn't '0'
+        let custom = ['a', 'b', 'c'];
+        assert!(seq2dec_with_options("0", &custom, &strict).is_err());
+        assert_eq!(seq2dec_with_options("a", &custom, &strict), Ok(0));
+        assert_eq!(seq2dec_with_options("bac", &custom, &strict), Ok(11));
+        let msg = "Leading zero 'a' at position 0 MUST NOT be in: \"aabc\"".to_string();
+        assert_eq!(seq2dec_with_options("aabc", &custom, &strict), Err(Error::NonCanonical(msg)));
+    }
+
+    #[test]
+    fn strict_canonical_accepts_one_form_per_value() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        assert_eq!(seq2dec_strict_canonical("FF", hex), Ok(255));
+        assert_eq!(seq2dec_strict_canonical("0", hex), Ok(0));
+        for overlong in ["00FF", "0FF", "00", ""].iter() {
+            match seq2dec_strict_canonical(overlong, hex) {
+                Err(Error::NonCanonical(_)) => (),
+                other => panic!("{:?} gave {:?}", overlong, other),
+            }
+        }
+        assert!(seq2dec_strict_canonical("ff", hex).is_err());
+        for value in (1..5000).chain(usize::MAX - 5..usize::MAX) {
+            let sequence = ::dec2seq(value, hex).unwrap();
+            assert_eq!(seq2dec_strict_canonical(&sequence, hex), Ok(value));
+        }
+    }
+
+    #[test]
+    fn overflow_modes_on_overflowing_input() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let max = "FFFFFFFFFFFFFFFF";
+        for mode in [OverflowMode::Error, OverflowMode::Saturate, OverflowMode::Wrap].iter() {
+            assert_eq!(decode(max, hex, *mode), Ok(usize::MAX));
+            assert_eq!(decode("", hex, *mode), Ok(0));
+        }
+        let over = "123456789ABCDEF0123";
+        let msg = format!("Value of {:?} exceeds usize", over);
+        assert_eq!(decode(over, hex, OverflowMode::Error), Err(Error::Overflow(msg)));
+        assert_eq!(decode(over, hex, OverflowMode::Saturate), Ok(usize::MAX));
+        // the lowest 16 hex digits survive wrapping
+        assert_eq!(decode(over, hex, OverflowMode::Wrap), Ok(0x4567_89AB_CDEF_0123));
+        assert_eq!(decode(&format!("{}0", max), hex, OverflowMode::Wrap), Ok(usize::MAX - 15));
+        // saturation sticks, even if later digits would wrap below the max
+        assert_eq!(decode("10000000000000000000", hex, OverflowMode::Saturate), Ok(usize::MAX));
+        for mode in [OverflowMode::Saturate, OverflowMode::Wrap].iter() {
+            assert!(decode(&format!("{}{}G", max, max), hex, *mode).is_err());
+        }
+    }
+
+    #[test]
+    fn decoders_share_the_overflow_check() {
+        let over = "18446744073709551616";
+        let expected = Err(value_overflow(over));
+        let any: Vec<usize> = (0..10).collect();
+        assert_eq!(decode(over, &DIGITS, OverflowMode::Error), expected);
+        assert_eq!(seq2dec_with_options(over, &DIGITS, &ParseOptions::default()), expected);
+        assert_eq!(decode_with_allowed(over, &DIGITS, &vec![&any[..]; 20]), expected);
+        assert_eq!(::seq2ratio(over, &DIGITS, '.').map(|_| 0), expected);
+        assert_eq!(::from_str_radix(over, 10), expected);
+        let nines = "9".repeat(20);
+        assert_eq!(::from_bijective_decimal(&nines), Err(value_overflow(&nines)));
+        assert_eq!(::seq2dec_with(over, 10, |glyph| glyph.to_digit(10).map(|d| d as usize)), expected);
+        assert_eq!(accumulate(usize::MAX, 10, 9, OverflowMode::Saturate, || unreachable!()), Ok(usize::MAX));
+        assert_eq!(accumulate(usize::MAX, 2, 1, OverflowMode::Wrap, || unreachable!()), Ok(usize::MAX));
+    }
+
+    #[test]
+    fn huge_inputs_are_not_echoed_whole() {
+        let huge = format!("0{}", "1".repeat(4_000_000));
+        let options = ParseOptions { reject_leading_zeros: true, ..ParseOptions::default() };
+        let msg = format!("Leading zero '0' at position 0 MUST NOT be in: \"0{}\"… (4000001 bytes)", "1".repeat(31));
+        assert_eq!(seq2dec_with_options(&huge, &DIGITS[..2], &options), Err(Error::NonCanonical(msg)));
+        let msg = format!("Value of \"0{}\"… (4000001 bytes)", "1".repeat(31));
+        match decode(&huge, &DIGITS[..2], OverflowMode::Error) {
+            Err(Error::Overflow(ref m)) if m.starts_with(&msg) && m.len() < 100 => {}
+            other => panic!("{:?}", other.map_err(|e| e.to_string().len())),
+        }
+        // 32 chars are quoted as they are, multi-byte ones are never cut
+        let stars = "★".repeat(32);
+        assert_eq!(echo(&stars), format!("{:?}", stars));
+        assert_eq!(echo(&format!("{}☆", stars)), format!("{:?}… (99 bytes)", stars));
+    }
+
+    #[test]
+    fn lossy_decoding_uses_fallback_for_unknown_chars() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        assert_eq!(seq2dec_lossy("FF", hex, 0), 255);
+        assert_eq!(seq2dec_lossy("F?", hex, 0), 240);
+        assert_eq!(seq2dec_lossy("F?", hex, 5), 245);
+        assert_eq!(seq2dec_lossy("ff", hex, 1), 17);
+        assert_eq!(seq2dec_lossy("", hex, 7), 0);
+        assert_eq!(seq2dec_lossy("1", &['0', '1', '0'], 0), 1);
+        assert_eq!(seq2dec_lossy("10", &[], 3), 3);
+        assert_eq!(seq2dec_lossy("10", &['1'], 3), 3);
+        assert_eq!(seq2dec_lossy("1?", hex, usize::MAX), usize::MAX);
+        for sequence in ["12345", "FACE", "0"].iter() {
+            assert_eq!(Ok(seq2dec_lossy(sequence, hex, 0)), decode(sequence, hex, OverflowMode::Saturate));
+        }
+    }
+
+    #[test]
+    fn leading_zero_checked_after_normalization() {
+        let both = ParseOptions { normalize: true, reject_leading_zeros: true, ..ParseOptions::default() };
+        // '⑩' expands to "10", its first digit isn't zero
+        assert_eq!(seq2dec_with_options("⑩", &DIGITS, &both), Ok(10));
+        assert_eq!(seq2dec_with_options("０", &DIGITS, &both), Ok(0));
+        let msg = "Leading zero '０' at position 0 MUST NOT be in: \"０7\"".to_string();
+        assert_eq!(seq2dec_with_options("０7", &DIGITS, &both), Err(Error::NonCanonical(msg)));
+    }
+
+    #[test]
+    fn case_insensitive_is_checked_up_front() {
+        let folding = ParseOptions { case_insensitive: true, ..ParseOptions::default() };
+        assert_eq!(seq2dec_with_options("zZ", &DIGITS_UPPER_AZ, &folding), Ok(36 * 35 + 35));
+        // base 62 fails even for sequences without letters
+        match seq2dec_with_options("0", &::DIGITS_UPPER_LOWER_AZ, &folding) {
+            Err(Error::CaseFoldCollision(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        let both = ParseOptions { normalize: true, case_insensitive: true, ..ParseOptions::default() };
+        assert_eq!(seq2dec_with_options("ｆF", &DIGITS_UPPER_AZ[..16], &both), Ok(255));
+    }
+
+    #[test]
+    fn allowed_digits_per_position() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        // UUID-like: version nibble 4, variant nibble 8 to B
+        let any: Vec<usize> = (0..16).collect();
+        let allowed: [&[usize]; 4] = [&any, &[4], &[8, 9, 10, 11], &any];
+        assert_eq!(decode_with_allowed("04A0", hex, &allowed), Ok(0x04A0));
+        let msg = "Char 'C' at position 2 has digit 12 which MUST be one of [8, 9, 10, 11]".to_string();
+        assert_eq!(decode_with_allowed("F4CF", hex, &allowed), Err(Error::DisallowedDigit(msg)));
+        match decode_with_allowed("F5AF", hex, &allowed) {
+            Err(Error::DisallowedDigit(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        // nothing is allowed at an empty position
+        assert!(decode_with_allowed("0", hex, &[&[]]).is_err());
+        let msg = "Sequence MUST have 4 chars, given 3".to_string();
+        assert_eq!(decode_with_allowed("4A0", hex, &allowed), Err(Error::LengthMismatch(msg)));
+        assert_eq!(decode_with_allowed("", hex, &[]), Ok(0));
+    }
+}
@@ -0,0 +1,229 @@
+use {positional_alphabet, values_of, Error};
+
+
+/// Returns `n!`, or `None` when it exceeds `usize`.
+fn checked_factorial(n: usize) -> Option<usize> {
+    (2..=n).try_fold(1_usize, |product, k| product.checked_mul(k))
+}
+
+
+/// Returns index of `perm` among permutations of `0..n` in lexicographic order (its Lehmer code).
+///
+/// Digits of the index in the factorial number system are the Lehmer code:
+/// for each element the count of smaller elements after it.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::permutation_index;
+///
+/// assert_eq!(permutation_index(&[0, 1, 2]), Ok(0));
+/// assert_eq!(permutation_index(&[1, 0, 2]), Ok(2));
+/// assert_eq!(permutation_index(&[2, 1, 0]), Ok(5));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InvalidPermutation` when `perm` isn't a permutation of `0..perm.len()`
+///
+/// ```
+/// use numsys::permutation_index;
+/// use numsys::Error;
+///
+/// let msg = "Permutation MUST contain each of 0..3 once, given [0, 2, 2]".to_string();
+/// assert_eq!(permutation_index(&[0, 2, 2]), Err(Error::InvalidPermutation(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when index exceeds `usize`
+pub fn permutation_index(perm: &[usize]) -> Result<usize, Error> {
+    let n = perm.len();
+    let mut seen = vec![false; n];
+    for value in perm {
+        if *value >= n || seen[*value] {
+            return Err(Error::InvalidPermutation(
+                format!("Permutation MUST contain each of 0..{} once, given {:?}", n, perm),
+            ));
+        }
+        seen[*value] = true;
+    }
+    let mut index: usize = 0;
+    for (position, value) in perm.iter().enumerate() {
+        let lehmer = perm[position + 1..].iter().filter(|later| *later < value).count();
+        // Horner's scheme with radices n, n - 1, ..., 1
+        index = index
+            .checked_mul(n - position)
+            .and_then(|i| i.checked_add(lehmer))
+            .ok_or_else(|| Error::Overflow(format!("Index of permutation {:?} exceeds usize", perm)))?;
+    }
+    Ok(index)
+}
+
+
+/// Returns permutation of `0..n` at `index` in lexicographic order, the inverse of `permutation_index`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::index_to_permutation;
+///
+/// assert_eq!(index_to_permutation(0, 3), Ok(vec![0, 1, 2]));
+/// assert_eq!(index_to_permutation(5, 3), Ok(vec![2, 1, 0]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfRange` when `index` isn't less than `n!`
+///
+/// ```
+/// use numsys::index_to_permutation;
+/// use numsys::Error;
+///
+/// let msg = "Index MUST be less than 3! (6), given 6".to_string();
+/// assert_eq!(index_to_permutation(6, 3), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn index_to_permutation(index: usize, n: usize) -> Result<Vec<usize>, Error> {
+    if let Some(count) = checked_factorial(n) {
+        if index >= count {
+            return Err(Error::OutOfRange(
+                format!("Index MUST be less than {}! ({}), given {}", n, count, index),
+            ));
+        }
+    }
+    let mut unused: Vec<usize> = (0..n).collect();
+    let mut rest = index;
+    let mut perm = Vec::with_capacity(n);
+    for position in 0..n {
+        // factorials above `usize` exceed any `rest`, so the digit is 0
+        let (lehmer, remainder) = match checked_factorial(n - 1 - position) {
+            Some(weight) => (rest / weight, rest % weight),
+            None => (0, rest),
+        };
+        perm.push(unused.remove(lehmer));
+        rest = remainder;
+    }
+    Ok(perm)
+}
+
+
+/// Tells whether cyclic `s` contains every length `n` sequence of `alphabet` chars exactly once.
+///
+/// That's a de Bruijn sequence B(k, n), with k the base: counting to
+/// `k^n` in base k visits the same windows, just not overlapped.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::is_de_bruijn_representation;
+///
+/// assert_eq!(is_de_bruijn_representation("0011", &['0', '1'], 2), Ok(true));
+/// assert_eq!(is_de_bruijn_representation("0101", &['0', '1'], 2), Ok(false));
+/// assert_eq!(is_de_bruijn_representation("00010111", &['0', '1'], 3), Ok(true));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `alphabet` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+///
+/// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `alphabet` missing a char or more
+///
+/// * Returns `Error::OutOfRange` when `n` is 0
+pub fn is_de_bruijn_representation(s: &str, alphabet: &[char], n: usize) -> Result<bool, Error> {
+    positional_alphabet(alphabet)?;
+    let digits = values_of(s, alphabet)?;
+    if n == 0 {
+        return Err(Error::OutOfRange("Window length MUST be at least 1, given 0".into()));
+    }
+    let base = alphabet.len();
+    // every window is a distinct value below base^n, so there are as many as chars
+    let count = (0..n).try_fold(1_usize, |power, _| power.checked_mul(base));
+    if count != Some(digits.len()) {
+        return Ok(false);
+    }
+    let top = digits.len() / base;
+    let mut seen = vec![false; digits.len()];
+    let mut window = digits.iter().cycle().take(n - 1).fold(0, |value, digit| value * base + digit);
+    for digit in digits.iter().cycle().skip(n - 1).take(digits.len()) {
+        window = window % top * base + digit;
+        if seen[window] {
+            return Ok(false);
+        }
+        seen[window] = true;
+    }
+    Ok(true)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    #[test]
+    fn classic_de_bruijn_sequences() {
+        let binary = ['0', '1'];
+        assert_eq!(is_de_bruijn_representation("0011", &binary, 2), Ok(true));
+        // cyclic, so any rotation is one too
+        for rotation in ["0110", "1100", "1001"].iter() {
+            assert_eq!(is_de_bruijn_representation(rotation, &binary, 2), Ok(true));
+        }
+        for not in ["0101", "0000", "001", "00110", ""].iter() {
+            assert_eq!(is_de_bruijn_representation(not, &binary, 2), Ok(false));
+        }
+        assert_eq!(is_de_bruijn_representation("01", &binary, 1), Ok(true));
+        assert_eq!(is_de_bruijn_representation("ABC", &['A', 'B', 'C'], 1), Ok(true));
+        assert_eq!(is_de_bruijn_representation("AABACBBCC", &['A', 'B', 'C'], 2), Ok(true));
+        assert_eq!(is_de_bruijn_representation("AABACBCCB", &['A', 'B', 'C'], 2), Ok(false));
+        assert_eq!(is_de_bruijn_representation("0000000100110101111", &binary, 4), Ok(false));
+        assert_eq!(is_de_bruijn_representation("0000100110101111", &binary, 4), Ok(true));
+        assert_eq!(is_de_bruijn_representation("0011", &binary, 200), Ok(false));
+        assert!(is_de_bruijn_representation("0012", &binary, 2).is_err());
+        assert!(is_de_bruijn_representation("0011", &binary, 0).is_err());
+    }
+
+    #[test]
+    fn identity_and_known_permutation() {
+        for n in 0..10 {
+            let identity: Vec<usize> = (0..n).collect();
+            assert_eq!(permutation_index(&identity), Ok(0));
+            let reversed: Vec<usize> = (0..n).rev().collect();
+            assert_eq!(permutation_index(&reversed), Ok(checked_factorial(n).unwrap() - 1));
+        }
+        // Lehmer code 1, 2, 0, 1, 0 is 1*24 + 2*6 + 0*2 + 1*1
+        assert_eq!(permutation_index(&[1, 3, 0, 4, 2]), Ok(37));
+        assert_eq!(index_to_permutation(37, 5), Ok(vec![1, 3, 0, 4, 2]));
+    }
+
+    #[test]
+    fn index_round_trip_in_lexicographic_order() {
+        let mut previous: Option<Vec<usize>> = None;
+        for index in 0..720 {
+            let perm = index_to_permutation(index, 6).unwrap();
+            assert_eq!(permutation_index(&perm), Ok(index));
+            if let Some(previous) = previous {
+                assert!(previous < perm);
+            }
+            previous = Some(perm);
+        }
+        let mut rng = XorShift::new(230);
+        for _ in 0..200 {
+            let index = rng.next_u64() as usize;
+            let perm = index_to_permutation(index, 30).unwrap();
+            assert_eq!(permutation_index(&perm), Ok(index));
+        }
+    }
+
+    #[test]
+    fn invalid_permutations() {
+        assert!(permutation_index(&[1, 2, 3]).is_err());
+        assert!(permutation_index(&[0, 0]).is_err());
+        assert_eq!(permutation_index(&[]), Ok(0));
+        let reversed: Vec<usize> = (0..21).rev().collect();
+        assert!(permutation_index(&reversed).is_err());
+        assert_eq!(index_to_permutation(0, 0), Ok(vec![]));
+        assert!(index_to_permutation(24, 4).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use digits::value_at;
+use {index_chars, Error};
+
+
+/// Positional system where every position has its own alphabet.
+///
+/// The first alphabet describes the most significant (leftmost) position,
+/// the last one the least significant. The radix of each position is the
+/// length of its alphabet, so a system can encode values from `0` up to
+/// (excluded) the product of all alphabet lengths.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::PositionalSystem;
+///
+/// let system = PositionalSystem::new(&[&['A', 'B'], &['0', '1', '2']]).unwrap();
+/// assert_eq!(system.capacity(), 6);
+/// assert_eq!(system.encode(5), Ok("B2".to_string()));
+/// assert_eq!(system.decode("B2"), Ok(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PositionalSystem {
+    alphabets: Vec<Vec<char>>,
+    char2val: Vec<HashMap<char, usize>>,
+    capacity: usize,
+}
+
+impl PositionalSystem {
+    /// Creates system from alphabets ordered from the most significant position.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when `alphabets` or any of its alphabets is empty
+    ///
+    /// ```
+    /// use numsys::{Error, PositionalSystem};
+    ///
+    /// assert_eq!(PositionalSystem::new(&[]).unwrap_err(), Error::DictEmpty);
+    /// assert_eq!(PositionalSystem::new(&[&['0'], &[]]).unwrap_err(), Error::DictEmpty);
+    /// ```
+    ///
+    /// * Returns `Error::MultipleChar` when any alphabet includes duplicated chars
+    ///
+    /// * Returns `Error::Overflow` when the product of alphabet lengths exceeds `usize`
+    ///
+    /// ```
+    /// use numsys::{Error, PositionalSystem};
+    ///
+    /// let binary: &[char] = &['0', '1'];
+    /// let result = PositionalSystem::new(&vec![binary; 65]);
+    /// let msg = "Capacity of 65 positions exceeds usize".to_string();
+    /// assert_eq!(result.unwrap_err(), Error::Overflow(msg));
+    /// ```
+    pub fn new(alphabets: &[&[char]]) -> Result<PositionalSystem, Error> {
+        if alphabets.is_empty() {
+            return Err(Error::DictEmpty);
+        }
+        let mut char2val = Vec::with_capacity(alphabets.len());
+        let mut capacity: usize = 1;
+        for alphabet in alphabets {
+            char2val.push(index_chars(alphabet)?);
+            capacity = capacity.checked_mul(alphabet.len()).ok_or_else(|| {
+                Error::Overflow(format!(
+                    "Capacity of {} positions exceeds usize",
+                    alphabets.len()
+                ))
+            })?;
+        }
+        Ok(PositionalSystem {
+            alphabets: alphabets.iter().map(|a| a.to_vec()).collect(),
+            char2val,
+            capacity,
+        })
+    }
+
+    /// Returns count of values the system can represent.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns count of positions (chars) of every encoded value.
+    pub fn len(&self) -> usize {
+        self.alphabets.len()
+    }
+
+    /// Always `false`, a system has at least one position.
+    pub fn is_empty(&self) -> bool {
+        self.alphabets.is_empty()
+    }
+
+    /// Converts `value` to a sequence using the alphabet of each position.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::OutOfRange` when `value` is not less than `capacity()`
+    ///
+    /// ```
+    /// use numsys::{Error, PositionalSystem};
+    ///
+    /// let system = PositionalSystem::new(&[&['A', 'B'], &['0', '1', '2']]).unwrap();
+    /// let msg = "Value MUST be less than 6, given 6".to_string();
+    /// assert_eq!(system.encode(6), Err(Error::OutOfRange(msg)));
+    /// ```
+    pub fn encode(&self, mut value: usize) -> Result<String, Error> {
+        if value >= self.capacity {
+            return Err(Error::OutOfRange(format!(
+                "Value MUST be less than {}, given {}",
+                self.capacity,
+                value
+            )));
+        }
+        let mut glyphs = Vec::with_capacity(self.alphabets.len());
+        for alphabet in self.alphabets.iter().rev() {
+            glyphs.push(alphabet[value % alphabet.len()]);
+            value /= alphabet.len();
+        }
+        Ok(glyphs.into_iter().rev().collect())
+    }
+
+    /// Converts `sequence` to a value validating each char against its position's alphabet.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::LengthMismatch` when `sequence` char count differs from `len()`
+    ///
+    /// ```
+    /// use numsys::{Error, PositionalSystem};
+    ///
+    /// let system = PositionalSystem::new(&[&['A', 'B'], &['0', '1', '2']]).unwrap();
+    /// let msg = "Sequence MUST have 2 chars, given 3".to_string();
+    /// assert_eq!(system.decode("A00"), Err(Error::LengthMismatch(msg)));
+    /// ```
+    ///
+    /// * Returns `Error::MissingChar` when a char is missing in its position's alphabet
+    ///
+    /// ```
+    /// use numsys::{Error, PositionalSystem};
+    ///
+    /// let system = PositionalSystem::new(&[&['A', 'B'], &['0', '1', '2']]).unwrap();
+    /// let msg = "Char \'A\' at position 1 not found in: [\'0\', \'1\', \'2\']".to_string();
+    /// assert_eq!(system.decode("AA"), Err(Error::MissingChar(msg)));
+    /// ```
+    pub fn decode<S: AsRef<str>>(&self, sequence: S) -> Result<usize, Error> {
+        let sequence = sequence.as_ref();
+        let length = sequence.chars().count();
+        if length != self.alphabets.len() {
+            return Err(Error::LengthMismatch(format!(
+                "Sequence MUST have {} chars, given {}",
+                self.alphabets.len(),
+                length
+            )));
+        }
+        let mut value: usize = 0;
+        for (position, glyph) in sequence.chars().enumerate() {
+            let digit = value_at(glyph, Some(position), &self.char2val[position], &self.alphabets[position])?;
+            value = value * self.alphabets[position].len() + digit;
+        }
+        Ok(value)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_value_of_small_system() {
+        let system = PositionalSystem::new(&[&['a', 'b'], &['0', '1', '2'], &['x', 'y']]).unwrap();
+        assert_eq!(system.capacity(), 12);
+        for value in 0..system.capacity() {
+            let seq = system.encode(value).expect("encode failed");
+            assert_eq!(seq.chars().count(), 3);
+            assert_eq!(system.decode(&seq), Ok(value));
+        }
+        assert!(system.encode(12).is_err());
+    }
+
+    #[test]
+    fn license_key_layout() {
+        let consonants: Vec<char> = "BCDFGHJKLMNPQRSTVWXZ".chars().collect();
+        let digits: Vec<char> = "0123456789".chars().collect();
+        let base32: Vec<char> = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567".chars().collect();
+        let mut layout: Vec<&[char]> = vec![&consonants; 4];
+        layout.push(&digits);
+        layout.extend(vec![&base32[..]; 4]);
+        let system = PositionalSystem::new(&layout).unwrap();
+
+        assert_eq!(system.capacity(), 20 * 20 * 20 * 20 * 10 * 32 * 32 * 32 * 32);
+        assert_eq!(system.encode(0), Ok("BBBB0AAAA".to_string()));
+        assert_eq!(system.encode(1), Ok("BBBB0AAAB".to_string()));
+        assert_eq!(system.encode(32 * 32 * 32 * 32), Ok("BBBB1AAAA".to_string()));
+        let last = system.capacity() - 1;
+        assert_eq!(system.encode(last), Ok("ZZZZ97777".to_string()));
+        assert_eq!(system.decode("ZZZZ97777"), Ok(last));
+        assert_eq!(system.decode("BBBC0AAAA"), Ok(10 * 32 * 32 * 32 * 32));
+
+        let msg = "Char 'A' at position 3 not found in: ".to_string();
+        match system.decode("BBBA0AAAA") {
+            Err(Error::MissingChar(detail)) => assert!(detail.starts_with(&msg)),
+            other => panic!("unexpected {:?}", other),
+        }
+        match system.decode("BBBB0AAA1") {
+            Err(Error::MissingChar(detail)) => assert!(detail.contains("position 8")),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
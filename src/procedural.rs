@@ -0,0 +1,470 @@
+use digits::check_radix;
+use parse::{accumulate, value_overflow};
+use {Error, OverflowMode};
+
+
+/// Checks `radices` of a mixed radix system, returning count of values it represents.
+fn mixed_capacity(radices: &[usize]) -> Result<usize, Error> {
+    if radices.is_empty() {
+        return Err(Error::DictEmpty);
+    }
+    let mut capacity: usize = 1;
+    for (position, radix) in radices.iter().enumerate() {
+        if *radix == 0 {
+            return Err(Error::BaseTooSmall(format!("Radix at position {} MUST be 1 or higher, given 0", position)));
+        }
+        capacity = capacity
+            .checked_mul(*radix)
+            .ok_or_else(|| Error::Overflow(format!("Capacity of {} positions exceeds usize", radices.len())))?;
+    }
+    Ok(capacity)
+}
+
+
+/// Reads the digit at byte `at` of `sequence` with `next_digit`, returning its value and byte count.
+fn read_digit<F>(sequence: &str, at: usize, radix: usize, next_digit: F) -> Result<(usize, usize), Error>
+where
+    F: FnOnce(&str) -> Result<(usize, usize), String>,
+{
+    let (digit, length) =
+        next_digit(&sequence[at..]).map_err(|msg| Error::Callback(format!("{} at byte {}", msg, at)))?;
+    let rest = sequence.len() - at;
+    if length == 0 || length > rest || !sequence.is_char_boundary(at + length) {
+        return Err(Error::Callback(format!(
+            "Digit at byte {} MUST take from 1 to {} bytes ending at char boundary, given {}",
+            at,
+            rest,
+            length
+        )));
+    }
+    if digit >= radix {
+        return Err(Error::OutOfRange(format!(
+            "Digit at byte {} MUST be less than {}, given {}",
+            at,
+            radix,
+            digit
+        )));
+    }
+    Ok((digit, length))
+}
+
+
+/// Converts `decimal` to `base` rendering each digit value with `glyph_for`.
+///
+/// Useful for bases too big for a `&[char]` alphabet, or glyphs longer than
+/// one char. Zero is rendered as `glyph_for(0)`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::dec2seq_procedural;
+///
+/// let bracketed = |digit: usize| format!("[{}]", digit);
+/// assert_eq!(dec2seq_procedural(27, 1000, bracketed), Ok("[27]".to_string()));
+/// assert_eq!(dec2seq_procedural(1_000_027, 1000, bracketed), Ok("[1][0][27]".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn dec2seq_procedural<F: Fn(usize) -> String>(mut decimal: usize, base: usize, glyph_for: F) -> Result<String, Error> {
+    check_radix(base)?;
+    let mut glyphs = vec![glyph_for(decimal % base)];
+    decimal /= base;
+    while decimal != 0 {
+        glyphs.push(glyph_for(decimal % base));
+        decimal /= base;
+    }
+    glyphs.reverse();
+    Ok(glyphs.concat())
+}
+
+
+/// Converts `decimal` to `base` with single char glyphs produced by `glyph_for`.
+///
+/// Like `dec2seq_procedural`, but no `String` is allocated per digit, so
+/// it suits computed alphabets (e.g. `char::from_digit` based ones).
+///
+/// # Examples
+///
+/// ```
+/// use numsys::dec2seq_with;
+///
+/// let dots = |digit: usize| if digit == 0 { '.' } else { '#' };
+/// assert_eq!(dec2seq_with(5, 2, dots), Ok("#.#".to_string()));
+/// let hex = |digit: usize| std::char::from_digit(digit as u32, 16).unwrap();
+/// assert_eq!(dec2seq_with(255, 16, hex), Ok("ff".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn dec2seq_with<F: Fn(usize) -> char>(mut decimal: usize, base: usize, glyph_for: F) -> Result<String, Error> {
+    check_radix(base)?;
+    let mut glyphs = vec![glyph_for(decimal % base)];
+    decimal /= base;
+    while decimal != 0 {
+        glyphs.push(glyph_for(decimal % base));
+        decimal /= base;
+    }
+    Ok(glyphs.iter().rev().collect())
+}
+
+
+/// Converts `sequence` of `base` to decimal with digit values given by `value_of`.
+///
+/// The inverse of `dec2seq_with`, `value_of` returns `None` for chars
+/// which aren't digits.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2dec_with;
+///
+/// let dots = |glyph: char| match glyph {
+///     '.' => Some(0),
+///     '#' => Some(1),
+///     _ => None,
+/// };
+/// assert_eq!(seq2dec_with("#.#", 2, dots), Ok(5));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::MissingChar` when `value_of` gives no value for a char
+///
+/// * Returns `Error::OutOfRange` when `value_of` gives a value not less than `base`
+///
+/// ```
+/// use numsys::seq2dec_with;
+/// use numsys::Error;
+///
+/// let digit = |glyph: char| glyph.to_digit(10).map(|d| d as usize);
+/// let msg = "Char \'x\' at position 1 has no digit value".to_string();
+/// assert_eq!(seq2dec_with("1x", 10, digit), Err(Error::MissingChar(msg)));
+/// let msg = "Char \'9\' at position 0 MUST be less than 8, given 9".to_string();
+/// assert_eq!(seq2dec_with("9", 8, digit), Err(Error::OutOfRange(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn seq2dec_with<F: Fn(char) -> Option<usize>>(sequence: &str, base: usize, value_of: F) -> Result<usize, Error> {
+    check_radix(base)?;
+    let mut dec: usize = 0;
+    for (position, glyph) in sequence.chars().enumerate() {
+        let digit = value_of(glyph).ok_or_else(|| {
+            Error::MissingChar(format!("Char {:?} at position {} has no digit value", glyph, position))
+        })?;
+        if digit >= base {
+            return Err(Error::OutOfRange(format!(
+                "Char {:?} at position {} MUST be less than {}, given {}",
+                glyph,
+                position,
+                base,
+                digit
+            )));
+        }
+        dec = accumulate(dec, base, digit, OverflowMode::Error, || value_overflow(sequence))?;
+    }
+    Ok(dec)
+}
+
+
+/// Converts `sequence` of `base` to decimal reading digits with `next_digit`.
+///
+/// `next_digit` gets the not yet consumed rest of `sequence` and returns
+/// value of its first digit and count of bytes the digit takes, or a message
+/// why the digit is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::seq2dec_procedural;
+///
+/// let bracketed = |rest: &str| {
+///     let end = rest.find(']').ok_or("Missing ']'")?;
+///     let digit = rest[1..end].parse::<usize>().map_err(|e| e.to_string())?;
+///     Ok((digit, end + 1))
+/// };
+/// assert_eq!(seq2dec_procedural("[1][0][27]", 1000, bracketed), Ok(1_000_027));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::Callback` when `next_digit` fails or consumes nothing
+///
+/// ```
+/// use numsys::seq2dec_procedural;
+/// use numsys::Error;
+///
+/// let failing = |_: &str| Err("Unknown digit".to_string());
+/// let msg = "Unknown digit at byte 0".to_string();
+/// assert_eq!(seq2dec_procedural("x", 10, failing), Err(Error::Callback(msg)));
+/// ```
+///
+/// * Returns `Error::OutOfRange` when a digit value isn't less than `base`
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn seq2dec_procedural<F>(sequence: &str, base: usize, next_digit: F) -> Result<usize, Error>
+where
+    F: Fn(&str) -> Result<(usize, usize), String>,
+{
+    check_radix(base)?;
+    let mut dec: usize = 0;
+    let mut at = 0;
+    while at < sequence.len() {
+        let (digit, length) = read_digit(sequence, at, base, &next_digit)?;
+        dec = accumulate(dec, base, digit, OverflowMode::Error, || value_overflow(sequence))?;
+        at += length;
+    }
+    Ok(dec)
+}
+
+
+/// Converts `value` to mixed radix `radices` rendering each digit with `glyph_for`.
+///
+/// `radices` go from the most significant position, like alphabets of
+/// `PositionalSystem`, and every position gets a digit. `glyph_for` gets
+/// the position and the digit value, so e.g. factoradic digits of any size
+/// can be rendered.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::encode_mixed_procedural;
+///
+/// let bracketed = |_: usize, digit: usize| format!("[{}]", digit);
+/// // factoradic: 3! * 3 + 2! * 2 + 1! * 1 = 23
+/// assert_eq!(encode_mixed_procedural(23, &[4, 3, 2, 1], bracketed), Ok("[3][2][1][0]".to_string()));
+/// // days, hours, minutes of 100_000 minutes
+/// assert_eq!(encode_mixed_procedural(100_000, &[1000, 24, 60], bracketed), Ok("[69][10][40]".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `radices` is empty
+///
+/// * Returns `Error::BaseTooSmall` when a radix is 0
+///
+/// * Returns `Error::Overflow` when the product of `radices` exceeds `usize`
+///
+/// * Returns `Error::OutOfRange` when `value` is not less than the product of `radices`
+///
+/// ```
+/// use numsys::encode_mixed_procedural;
+/// use numsys::Error;
+///
+/// let msg = "Value MUST be less than 24, given 24".to_string();
+/// let bracketed = |_: usize, digit: usize| format!("[{}]", digit);
+/// assert_eq!(encode_mixed_procedural(24, &[4, 3, 2, 1], bracketed), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn encode_mixed_procedural<F>(mut value: usize, radices: &[usize], glyph_for: F) -> Result<String, Error>
+where
+    F: Fn(usize, usize) -> String,
+{
+    let capacity = mixed_capacity(radices)?;
+    if value >= capacity {
+        return Err(Error::OutOfRange(format!("Value MUST be less than {}, given {}", capacity, value)));
+    }
+    let mut digits = vec![0; radices.len()];
+    for (digit, radix) in digits.iter_mut().zip(radices).rev() {
+        *digit = value % radix;
+        value /= radix;
+    }
+    Ok(digits.iter().enumerate().map(|(position, digit)| glyph_for(position, *digit)).collect())
+}
+
+
+/// Converts `sequence` of mixed radix `radices` to decimal reading digits with `next_digit`.
+///
+/// The inverse of `encode_mixed_procedural`. `next_digit` gets the
+/// position and the not yet consumed rest of `sequence`, and returns like
+/// the one of `seq2dec_procedural`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_mixed_procedural;
+///
+/// let bracketed = |_: usize, rest: &str| {
+///     let end = rest.find(']').ok_or("Missing ']'")?;
+///     let digit = rest[1..end].parse::<usize>().map_err(|e| e.to_string())?;
+///     Ok((digit, end + 1))
+/// };
+/// assert_eq!(decode_mixed_procedural("[3][2][1][0]", &[4, 3, 2, 1], bracketed), Ok(23));
+/// ```
+///
+/// # Errors
+///
+/// * Returns errors of `encode_mixed_procedural` for `radices`
+///
+/// * Returns `Error::Callback` when `next_digit` fails or consumes nothing
+///
+/// * Returns `Error::OutOfRange` when a digit value isn't less than the radix of its position
+///
+/// ```
+/// use numsys::decode_mixed_procedural;
+/// use numsys::Error;
+///
+/// let digit = |_: usize, rest: &str| rest[..1].parse::<usize>().map(|d| (d, 1)).map_err(|e| e.to_string());
+/// let msg = "Digit at byte 1 MUST be less than 3, given 3".to_string();
+/// assert_eq!(decode_mixed_procedural("330", &[4, 3, 2], digit), Err(Error::OutOfRange(msg)));
+/// ```
+///
+/// * Returns `Error::LengthMismatch` when `sequence` has other count of digits than `radices`
+pub fn decode_mixed_procedural<F>(sequence: &str, radices: &[usize], next_digit: F) -> Result<usize, Error>
+where
+    F: Fn(usize, &str) -> Result<(usize, usize), String>,
+{
+    mixed_capacity(radices)?;
+    let mut value: usize = 0;
+    let mut at = 0;
+    for (position, radix) in radices.iter().enumerate() {
+        if at == sequence.len() {
+            return Err(Error::LengthMismatch(
+                format!("Sequence MUST have {} digits, given {}", radices.len(), position),
+            ));
+        }
+        let (digit, length) = read_digit(sequence, at, *radix, |rest| next_digit(position, rest))?;
+        // less than the capacity, which fits
+        value = value * radix + digit;
+        at += length;
+    }
+    if at != sequence.len() {
+        return Err(Error::LengthMismatch(format!(
+            "Sequence MUST have {} digits, given more from byte {}",
+            radices.len(),
+            at
+        )));
+    }
+    Ok(value)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bracketed(digit: usize) -> String {
+        format!("[{}]", digit)
+    }
+
+    fn parse_bracketed(rest: &str) -> Result<(usize, usize), String> {
+        if !rest.starts_with('[') {
+            return Err("Expected '['".to_string());
+        }
+        let end = rest.find(']').ok_or("Missing ']'")?;
+        let digit = rest[1..end].parse::<usize>().map_err(|e| e.to_string())?;
+        Ok((digit, end + 1))
+    }
+
+    #[test]
+    fn bracketed_decimal_digits_round_trip() {
+        assert_eq!(dec2seq_procedural(0, 1000, bracketed), Ok("[0]".to_string()));
+        for value in (0..10_000_000).step_by(99_991).chain(vec![usize::MAX]) {
+            let seq = dec2seq_procedural(value, 1000, bracketed).unwrap();
+            assert_eq!(seq2dec_procedural(&seq, 1000, parse_bracketed), Ok(value));
+        }
+    }
+
+    #[test]
+    fn adjacent_multi_digit_glyphs() {
+        let seq = dec2seq_procedural(999 * 1000 * 1000 + 100 * 1000 + 10, 1000, bracketed).unwrap();
+        assert_eq!(seq, "[999][100][10]");
+        assert_eq!(seq2dec_procedural(&seq, 1000, parse_bracketed), Ok(999_100_010));
+    }
+
+    #[test]
+    fn callback_errors_propagate() {
+        let msg = "Expected '[' at byte 4".to_string();
+        assert_eq!(seq2dec_procedural("[12]x", 1000, parse_bracketed), Err(Error::Callback(msg)));
+        let msg = "Digit at byte 4 MUST be less than 1000, given 1000".to_string();
+        assert_eq!(seq2dec_procedural("[12][1000]", 1000, parse_bracketed), Err(Error::OutOfRange(msg)));
+        let stuck = |_: &str| Ok((1, 0));
+        match seq2dec_procedural("1", 10, stuck) {
+            Err(Error::Callback(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        let greedy = |_: &str| Ok((1, 100));
+        match seq2dec_procedural("1", 10, greedy) {
+            Err(Error::Callback(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    fn parse_mixed(_: usize, rest: &str) -> Result<(usize, usize), String> {
+        parse_bracketed(rest)
+    }
+
+    #[test]
+    fn factoradic_round_trip() {
+        let factoradic = [5, 4, 3, 2, 1];
+        for value in 0..120 {
+            let seq = encode_mixed_procedural(value, &factoradic, |_, digit| bracketed(digit)).unwrap();
+            assert_eq!(seq.matches('[').count(), 5);
+            assert_eq!(decode_mixed_procedural(&seq, &factoradic, parse_mixed), Ok(value));
+        }
+        let last = encode_mixed_procedural(119, &factoradic, |_, digit| bracketed(digit));
+        assert_eq!(last, Ok("[4][3][2][1][0]".to_string()));
+        // each position may render its own way
+        let units = |position: usize, digit: usize| format!("{}{}", digit, ["d", "h", "m"][position]);
+        assert_eq!(encode_mixed_procedural(100_000, &[1000, 24, 60], units), Ok("69d10h40m".to_string()));
+    }
+
+    #[test]
+    fn adjacent_multi_digit_mixed_glyphs() {
+        let radices = [1000, 1000, 10];
+        let seq = encode_mixed_procedural((999 * 1000 + 100) * 10 + 5, &radices, |_, digit| bracketed(digit)).unwrap();
+        assert_eq!(seq, "[999][100][5]");
+        assert_eq!(decode_mixed_procedural(&seq, &radices, parse_mixed), Ok((999 * 1000 + 100) * 10 + 5));
+        let widest = [usize::MAX];
+        let seq = encode_mixed_procedural(usize::MAX - 1, &widest, |_, digit| bracketed(digit)).unwrap();
+        assert_eq!(decode_mixed_procedural(&seq, &widest, parse_mixed), Ok(usize::MAX - 1));
+    }
+
+    #[test]
+    fn mixed_callback_and_layout_errors() {
+        let radices = [10, 10];
+        let msg = "Expected '[' at byte 3".to_string();
+        assert_eq!(decode_mixed_procedural("[1]x", &radices, parse_mixed), Err(Error::Callback(msg)));
+        let msg = "Digit at byte 3 MUST be less than 10, given 10".to_string();
+        assert_eq!(decode_mixed_procedural("[1][10]", &radices, parse_mixed), Err(Error::OutOfRange(msg)));
+        let msg = "Sequence MUST have 2 digits, given 1".to_string();
+        assert_eq!(decode_mixed_procedural("[1]", &radices, parse_mixed), Err(Error::LengthMismatch(msg)));
+        let msg = "Sequence MUST have 2 digits, given more from byte 6".to_string();
+        assert_eq!(decode_mixed_procedural("[1][2][3]", &radices, parse_mixed), Err(Error::LengthMismatch(msg)));
+        let stuck = |_: usize, _: &str| Ok((1, 0));
+        match decode_mixed_procedural("1", &[10], stuck) {
+            Err(Error::Callback(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(decode_mixed_procedural("", &[], parse_mixed), Err(Error::DictEmpty));
+        assert!(encode_mixed_procedural(0, &[2, 0], |_, digit| bracketed(digit)).is_err());
+        let msg = "Capacity of 2 positions exceeds usize".to_string();
+        let result = encode_mixed_procedural(0, &[usize::MAX, 2], |_, digit| bracketed(digit));
+        assert_eq!(result, Err(Error::Overflow(msg)));
+        // radix 1 positions always take digit 0
+        assert_eq!(encode_mixed_procedural(0, &[1], |_, digit| bracketed(digit)), Ok("[0]".to_string()));
+    }
+
+    #[test]
+    fn closure_glyphs_for_binary() {
+        let glyph = |digit: usize| if digit == 0 { '.' } else { '#' };
+        let value = |glyph: char| match glyph {
+            '.' => Some(0),
+            '#' => Some(1),
+            _ => None,
+        };
+        assert_eq!(dec2seq_with(0, 2, glyph), Ok(".".to_string()));
+        assert_eq!(dec2seq_with(10, 2, glyph), Ok("#.#.".to_string()));
+        for decimal in (0..1_000_000).step_by(7_919).chain(vec![usize::MAX]) {
+            let seq = dec2seq_with(decimal, 2, glyph).unwrap();
+            assert_eq!(seq, format!("{:b}", decimal).replace('0', ".").replace('1', "#"));
+            assert_eq!(seq2dec_with(&seq, 2, value), Ok(decimal));
+        }
+        assert!(seq2dec_with(&"#".repeat(65), 2, value).is_err());
+    }
+}
@@ -0,0 +1,544 @@
+use parse::{accumulate, value_overflow};
+use {dec2seq, switch_dec_base, Error, OverflowMode, DIGITS_UPPER_LOWER_AZ};
+
+
+/// Highest radix supported by `from_str_radix` and `to_str_radix`.
+pub const MAX_RADIX: u32 = 62;
+
+fn check_radix(radix: u32) -> Result<(), Error> {
+    if radix < 2 {
+        return Err(Error::BaseTooSmall(
+            format!("Base MUST be 2 or higer, given {}", radix),
+        ));
+    }
+    if radix > MAX_RADIX {
+        return Err(Error::BaseTooBig(
+            format!("Base MUST be at most {}, given {}", MAX_RADIX, radix),
+        ));
+    }
+    Ok(())
+}
+
+fn digit_value(glyph: char, radix: u32) -> Option<usize> {
+    if radix <= 36 {
+        return glyph.to_digit(radix).map(|d| d as usize);
+    }
+    let value = match glyph {
+        '0'..='9' => glyph as usize - '0' as usize,
+        'A'..='Z' => glyph as usize - 'A' as usize + 10,
+        'a'..='z' => glyph as usize - 'a' as usize + 36,
+        _ => return None,
+    };
+    if value < radix as usize { Some(value) } else { None }
+}
+
+
+/// Converts `src` of `radix` to decimal, a drop-in for [`usize::from_str_radix`](
+/// https://doc.rust-lang.org/stable/std/primitive.usize.html#method.from_str_radix).
+///
+/// For `radix` up to 36 semantics are the same as std's: optional leading
+/// `+` (a `-` is an invalid digit, as for any unsigned type) and case
+/// insensitive letters. Additionally `radix` up to 62 is supported, then
+/// digits are `0-9`, `A-Z`, `a-z` in that order and letters are case sensitive.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_str_radix;
+///
+/// assert_eq!(from_str_radix("ff", 16), Ok(255));
+/// assert_eq!(from_str_radix("+FF", 16), Ok(255));
+/// assert_eq!(from_str_radix("z", 62), Ok(61));
+/// assert_eq!(from_str_radix("Z", 62), Ok(35));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `radix` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `radix` is greater then 62
+///
+/// * Returns `Error::SequenceEmpty` when `src` is empty
+///
+/// * Returns `Error::MissingChar` when `src` includes a char which isn't a digit
+///
+/// ```
+/// use numsys::from_str_radix;
+/// use numsys::Error;
+///
+/// let msg = "Char \'-\' at position 0 is not a digit of radix 10".to_string();
+/// assert_eq!(from_str_radix("-1", 10), Err(Error::MissingChar(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn from_str_radix(src: &str, radix: u32) -> Result<usize, Error> {
+    check_radix(radix)?;
+    if src.is_empty() {
+        return Err(Error::SequenceEmpty);
+    }
+    let (skip, digits) = match (src.chars().next(), src.len()) {
+        (Some('+'), 1) | (Some('-'), 1) => (0, src),
+        (Some('+'), _) => (1, &src[1..]),
+        _ => (0, src),
+    };
+    let mut dec: usize = 0;
+    for (position, glyph) in digits.chars().enumerate() {
+        let value = digit_value(glyph, radix).ok_or_else(|| {
+            Error::MissingChar(format!(
+                "Char {:?} at position {} is not a digit of radix {}",
+                glyph,
+                position + skip,
+                radix
+            ))
+        })?;
+        dec = accumulate(dec, radix as usize, value, OverflowMode::Error, || value_overflow(src))?;
+    }
+    Ok(dec)
+}
+
+
+/// Converts `value` to `radix`, the counterpart of `from_str_radix`.
+///
+/// Letters are upper case up to radix 36, above it digits are `0-9`, `A-Z`, `a-z`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_str_radix;
+///
+/// assert_eq!(to_str_radix(255, 16), Ok("FF".to_string()));
+/// assert_eq!(to_str_radix(61, 62), Ok("z".to_string()));
+/// assert_eq!(to_str_radix(0, 62), Ok("0".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `radix` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `radix` is greater then 62
+pub fn to_str_radix(value: usize, radix: u32) -> Result<String, Error> {
+    check_radix(radix)?;
+    if radix <= 36 {
+        return switch_dec_base(value, radix as usize);
+    }
+    if value == 0 {
+        return Ok("0".into());
+    }
+    dec2seq(value, &DIGITS_UPPER_LOWER_AZ[..radix as usize])
+}
+
+
+/// Highest base of Ada based literals.
+const ADA_MAX_BASE: usize = 16;
+
+/// Returns values of `digits` of `base`, which start at char `offset` of `literal`.
+///
+/// Underscores are allowed between digits only, one at a time, like Ada does.
+fn ada_numeral(literal: &str, digits: &str, offset: usize, base: u32) -> Result<Vec<usize>, Error> {
+    let mut values = Vec::new();
+    let mut previous = None;
+    let count = digits.chars().count();
+    for (index, glyph) in digits.chars().enumerate() {
+        if glyph == '_' {
+            if previous.unwrap_or('_') == '_' || index + 1 == count {
+                return Err(Error::InvalidLiteral(
+                    format!("Underscore at position {} of {:?} MUST separate digits", offset + index, literal),
+                ));
+            }
+        } else {
+            let value = glyph.to_digit(base).ok_or_else(|| {
+                Error::MissingChar(format!(
+                    "Char {:?} at position {} is not a digit of base {}",
+                    glyph,
+                    offset + index,
+                    base
+                ))
+            })?;
+            values.push(value as usize);
+        }
+        previous = Some(glyph);
+    }
+    if values.is_empty() {
+        return Err(Error::InvalidLiteral(format!("Literal {:?} MUST have digits at position {}", literal, offset)));
+    }
+    Ok(values)
+}
+
+fn ada_value(literal: &str, values: &[usize], base: usize) -> Result<usize, Error> {
+    values.iter().try_fold(0_usize, |dec, value| accumulate(dec, base, *value, OverflowMode::Error, || value_overflow(literal)))
+}
+
+fn check_ada_base(base: usize) -> Result<(), Error> {
+    check_radix(base.min(u32::MAX as usize) as u32)?;
+    if base > ADA_MAX_BASE {
+        return Err(Error::BaseTooBig(
+            format!("Base MUST be at most {}, given {}", ADA_MAX_BASE, base),
+        ));
+    }
+    Ok(())
+}
+
+
+/// Converts Ada (and VHDL) based integer literal `s`, like `16#FF#`, to decimal.
+///
+/// Syntax is `base#digits#` with an optional exponent `E[+]n`, value being
+/// `digits * base^n`. Base is decimal from 2 to 16, digit letters are case
+/// insensitive and single underscores may separate digits of each part.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::parse_ada_literal;
+///
+/// assert_eq!(parse_ada_literal("2#1010#"), Ok(10));
+/// assert_eq!(parse_ada_literal("16#ff#"), Ok(255));
+/// assert_eq!(parse_ada_literal("2#1111_0000#"), Ok(0xF0));
+/// assert_eq!(parse_ada_literal("16#F#E2"), Ok(0xF00));
+/// assert_eq!(parse_ada_literal("8#7#e+1"), Ok(56));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InvalidLiteral` when `s` doesn't follow the syntax, a fraction
+///   or a negative exponent included
+///
+/// ```
+/// use numsys::parse_ada_literal;
+/// use numsys::Error;
+///
+/// let msg = "Literal \"16#FF\" MUST be base#digits# with an optional exponent".to_string();
+/// assert_eq!(parse_ada_literal("16#FF"), Err(Error::InvalidLiteral(msg)));
+/// ```
+///
+/// * Returns `Error::BaseTooSmall` when base is less then 2
+///
+/// * Returns `Error::BaseTooBig` when base is greater then 16
+///
+/// * Returns `Error::MissingChar` when a char isn't a digit of its part
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn parse_ada_literal(s: &str) -> Result<usize, Error> {
+    let parts: Vec<&str> = s.splitn(3, '#').collect();
+    if parts.len() != 3 {
+        return Err(Error::InvalidLiteral(
+            format!("Literal {:?} MUST be base#digits# with an optional exponent", s),
+        ));
+    }
+    let (base_part, digits_part, exponent_part) = (parts[0], parts[1], parts[2]);
+    let base = ada_value(s, &ada_numeral(s, base_part, 0, 10)?, 10)?;
+    check_ada_base(base)?;
+    let digits_offset = base_part.chars().count() + 1;
+    if let Some(point) = digits_part.chars().position(|glyph| glyph == '.') {
+        return Err(Error::InvalidLiteral(
+            format!("Literal {:?} MUST be an integer, given point at position {}", s, digits_offset + point),
+        ));
+    }
+    let value = ada_value(s, &ada_numeral(s, digits_part, digits_offset, base as u32)?, base)?;
+    let mut exponent = exponent_part.chars();
+    let exponent_offset = digits_offset + digits_part.chars().count() + 1;
+    match exponent.next() {
+        None => return Ok(value),
+        Some('E') | Some('e') => (),
+        Some(_) => {
+            return Err(Error::InvalidLiteral(
+                format!("Literal {:?} MUST have exponent E at position {}", s, exponent_offset),
+            ))
+        }
+    }
+    let mut power = exponent.as_str();
+    let mut power_offset = exponent_offset + 1;
+    if power.starts_with('-') {
+        return Err(Error::InvalidLiteral(format!("Exponent of integer literal {:?} MUST NOT be negative", s)));
+    }
+    if power.starts_with('+') {
+        power = &power[1..];
+        power_offset += 1;
+    }
+    let power = ada_value(s, &ada_numeral(s, power, power_offset, 10)?, 10)?;
+    if value == 0 {
+        return Ok(0);
+    }
+    // base is 2 at least, so any power above 64 overflows
+    if power > 64 {
+        return Err(value_overflow(s));
+    }
+    base.checked_pow(power as u32).and_then(|scale| value.checked_mul(scale)).ok_or_else(|| value_overflow(s))
+}
+
+
+/// Converts `decimal` to an Ada (and VHDL) based literal of `base`, like `16#FF#`.
+///
+/// Digit letters are upper case, no underscores nor exponent are used.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::format_ada_literal;
+///
+/// assert_eq!(format_ada_literal(10, 2), Ok("2#1010#".to_string()));
+/// assert_eq!(format_ada_literal(255, 16), Ok("16#FF#".to_string()));
+/// assert_eq!(format_ada_literal(0, 8), Ok("8#0#".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 16
+pub fn format_ada_literal(decimal: usize, base: usize) -> Result<String, Error> {
+    check_ada_base(base)?;
+    Ok(format!("{}#{}#", base, switch_dec_base(decimal, base)?))
+}
+
+
+/// Converts `value` to `base` with its Rust literal prefix, `0b`, `0o`, `0x` or none for decimal.
+///
+/// With `c_octal` set octal gets the C prefix `0` instead (`017`), zero
+/// being just `0`. Letters are upper case.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::format_prefixed;
+///
+/// assert_eq!(format_prefixed(15, 8, false), Ok("0o17".to_string()));
+/// assert_eq!(format_prefixed(15, 8, true), Ok("017".to_string()));
+/// assert_eq!(format_prefixed(255, 16, true), Ok("0xFF".to_string()));
+/// assert_eq!(format_prefixed(5, 2, false), Ok("0b101".to_string()));
+/// assert_eq!(format_prefixed(15, 10, false), Ok("15".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfRange` when `base` isn't 2, 8, 10 or 16
+///
+/// ```
+/// use numsys::format_prefixed;
+/// use numsys::Error;
+///
+/// let msg = "Base MUST be 2, 8, 10 or 16, given 36".to_string();
+/// assert_eq!(format_prefixed(15, 36, false), Err(Error::OutOfRange(msg)));
+/// ```
+pub fn format_prefixed(value: usize, base: usize, c_octal: bool) -> Result<String, Error> {
+    let prefix = match base {
+        2 => "0b",
+        8 if c_octal && value == 0 => "",
+        8 if c_octal => "0",
+        8 => "0o",
+        10 => "",
+        16 => "0x",
+        _ => return Err(Error::OutOfRange(format!("Base MUST be 2, 8, 10 or 16, given {}", base))),
+    };
+    Ok(format!("{}{}", prefix, switch_dec_base(value, base)?))
+}
+
+
+/// Converts integer literal `s` prefixed like `format_prefixed` makes it to decimal.
+///
+/// Both octal conventions are accepted, Rust's `0o17` and C's `017`: a
+/// leading `0` followed by digits means octal. Prefix and digit letters are
+/// case insensitive and single underscores may separate digits.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::parse_prefixed;
+///
+/// assert_eq!(parse_prefixed("017"), Ok(15));
+/// assert_eq!(parse_prefixed("0o17"), Ok(15));
+/// assert_eq!(parse_prefixed("0xff"), Ok(255));
+/// assert_eq!(parse_prefixed("0b1010"), Ok(10));
+/// assert_eq!(parse_prefixed("1_000"), Ok(1000));
+/// assert_eq!(parse_prefixed("0"), Ok(0));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InvalidLiteral` when there are no digits after the prefix, or an underscore doesn't separate them
+///
+/// * Returns `Error::MissingChar` when a char isn't a digit of the base
+///
+/// ```
+/// use numsys::parse_prefixed;
+/// use numsys::Error;
+///
+/// let msg = "Char \'8\' at position 1 is not a digit of base 8".to_string();
+/// assert_eq!(parse_prefixed("08"), Err(Error::MissingChar(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn parse_prefixed(s: &str) -> Result<usize, Error> {
+    let lower = s.get(..2).map(|prefix| prefix.to_ascii_lowercase());
+    let (base, offset) = match lower.as_deref() {
+        Some("0b") => (2, 2),
+        Some("0o") => (8, 2),
+        Some("0x") => (16, 2),
+        _ if s.len() > 1 && s.starts_with('0') => (8, 1),
+        _ => (10, 0),
+    };
+    let values = ada_numeral(s, &s[offset..], offset, base)?;
+    ada_value(s, &values, base as usize)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::IntErrorKind;
+    use testutil::XorShift;
+
+    fn same_outcome(src: &str, radix: u32) {
+        let ours = from_str_radix(src, radix);
+        match usize::from_str_radix(src, radix) {
+            Ok(value) => assert_eq!(ours, Ok(value), "{:?} radix {}", src, radix),
+            Err(e) => match (e.kind(), ours) {
+                (&IntErrorKind::Empty, Err(Error::SequenceEmpty)) => (),
+                (&IntErrorKind::InvalidDigit, Err(Error::MissingChar(_))) => (),
+                (&IntErrorKind::PosOverflow, Err(Error::Overflow(_))) => (),
+                (kind, ours) => panic!("{:?} radix {}: {:?} vs {:?}", src, radix, kind, ours),
+            },
+        }
+    }
+
+    #[test]
+    fn octal_prefix_conventions() {
+        assert_eq!(format_prefixed(15, 8, true), Ok("017".to_string()));
+        assert_eq!(parse_prefixed("017"), Ok(15));
+        assert_eq!(format_prefixed(0, 8, true), Ok("0".to_string()));
+        assert_eq!(format_prefixed(0, 8, false), Ok("0o0".to_string()));
+        let mut rng = XorShift::new(252);
+        for &base in [2, 8, 10, 16].iter() {
+            for &c_octal in [false, true].iter() {
+                for _ in 0..200 {
+                    let value = rng.next_u64() as usize >> rng.below(64);
+                    let literal = format_prefixed(value, base, c_octal).unwrap();
+                    assert_eq!(parse_prefixed(&literal), Ok(value), "{}", literal);
+                    assert_eq!(parse_prefixed(&literal.to_lowercase()), Ok(value), "{}", literal);
+                }
+            }
+        }
+        assert_eq!(parse_prefixed("0O17"), Ok(15));
+        assert_eq!(parse_prefixed("0XfF"), Ok(255));
+        assert_eq!(parse_prefixed("00"), Ok(0));
+        for invalid in ["", "0x", "0b", "0_1", "1__0", "-1", "+1", "0b2", "019"].iter() {
+            assert!(parse_prefixed(invalid).is_err(), "{:?}", invalid);
+        }
+        assert!(parse_prefixed("0o2000000000000000000000").is_err());
+        assert!(format_prefixed(1, 3, true).is_err());
+    }
+
+    #[test]
+    fn ada_literals_round_trip() {
+        let mut rng = XorShift::new(239);
+        for base in 2..17 {
+            for _ in 0..200 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let literal = format_ada_literal(value, base).unwrap();
+                assert_eq!(parse_ada_literal(&literal), Ok(value));
+                assert_eq!(parse_ada_literal(&literal.to_lowercase()), Ok(value));
+            }
+        }
+        assert_eq!(parse_ada_literal("2#1010#"), Ok(10));
+        assert_eq!(format_ada_literal(10, 2), Ok("2#1010#".to_string()));
+        assert_eq!(parse_ada_literal("1_6#F_F#E0_1"), Ok(0xFF0));
+        assert_eq!(parse_ada_literal("16#1#E15"), Ok(1 << 60));
+        assert!(format_ada_literal(1, 17).is_err());
+        assert!(format_ada_literal(1, 1).is_err());
+    }
+
+    #[test]
+    fn zero_with_huge_exponent_is_quick() {
+        assert_eq!(parse_ada_literal("2#0#E10000000000000"), Ok(0));
+        assert_eq!(parse_ada_literal("16#0_0#E18446744073709551615"), Ok(0));
+        // the exponent itself still has to fit
+        assert!(parse_ada_literal("2#0#E18446744073709551616").is_err());
+        let long = format!("2#{}#", vec!["1"; 32].join("_"));
+        assert_eq!(parse_ada_literal(&long), Ok(u32::MAX as usize));
+    }
+
+    #[test]
+    fn malformed_ada_literals() {
+        let cases = [
+            ("", "Literal \"\" MUST be base#digits# with an optional exponent"),
+            ("#1#", "Literal \"#1#\" MUST have digits at position 0"),
+            ("2##", "Literal \"2##\" MUST have digits at position 2"),
+            ("2#_1#", "Underscore at position 2 of \"2#_1#\" MUST separate digits"),
+            ("2#1_#", "Underscore at position 3 of \"2#1_#\" MUST separate digits"),
+            ("2#1__0#", "Underscore at position 4 of \"2#1__0#\" MUST separate digits"),
+            ("2#1.1#", "Literal \"2#1.1#\" MUST be an integer, given point at position 3"),
+            ("2#1#F", "Literal \"2#1#F\" MUST have exponent E at position 4"),
+            ("2#1#E-1", "Exponent of integer literal \"2#1#E-1\" MUST NOT be negative"),
+            ("2#1#E", "Literal \"2#1#E\" MUST have digits at position 5"),
+            ("2#1#E+", "Literal \"2#1#E+\" MUST have digits at position 6"),
+        ];
+        for &(literal, msg) in cases.iter() {
+            assert_eq!(parse_ada_literal(literal), Err(Error::InvalidLiteral(msg.to_string())), "{}", literal);
+        }
+        let msg = "Char '2' at position 2 is not a digit of base 2".to_string();
+        assert_eq!(parse_ada_literal("2#2#"), Err(Error::MissingChar(msg)));
+        let msg = "Base MUST be at most 16, given 17".to_string();
+        assert_eq!(parse_ada_literal("17#1#"), Err(Error::BaseTooBig(msg)));
+        assert!(parse_ada_literal("1#0#").is_err());
+        assert!(parse_ada_literal("99999999999999999999999#0#").is_err());
+        assert!(parse_ada_literal("2#1#E64").is_err());
+        assert!(parse_ada_literal("2#1#E10000000000000").is_err());
+        assert_eq!(parse_ada_literal("2#1#E63"), Ok(1 << 63));
+        assert!(parse_ada_literal("16#1#1").is_err());
+    }
+
+    #[test]
+    fn from_str_radix_matches_std() {
+        let pool: Vec<char> = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFXYZ+-_ ż".chars().collect();
+        let mut rng = XorShift::new(207);
+        for radix in 2..37 {
+            for special in ["", "+", "-", "+0", "-0", "++1", "+-1", "0", "00"].iter() {
+                same_outcome(special, radix);
+            }
+            for _ in 0..2000 {
+                let length = rng.below(25);
+                let src: String = (0..length).map(|_| pool[rng.below(pool.len())]).collect();
+                same_outcome(&src, radix);
+                // mostly valid input exercises the values and overflow
+                let src: String = (0..length)
+                    .map(|_| ::DIGITS_UPPER_AZ[rng.below(radix as usize)])
+                    .collect();
+                same_outcome(&src, radix);
+                same_outcome(&format!("+{}", src.to_lowercase()), radix);
+            }
+        }
+    }
+
+    #[test]
+    fn to_str_radix_round_trips_with_std() {
+        let mut rng = XorShift::new(2072);
+        for radix in 2..37 {
+            for _ in 0..500 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let seq = to_str_radix(value, radix).unwrap();
+                assert_eq!(usize::from_str_radix(&seq, radix), Ok(value));
+            }
+        }
+        assert_eq!(to_str_radix(0xDEAD_BEEF, 16), Ok(format!("{:X}", 0xDEAD_BEEFu32)));
+        assert_eq!(to_str_radix(usize::MAX, 2), Ok(format!("{:b}", usize::MAX)));
+    }
+
+    #[test]
+    fn radix_above_36() {
+        assert_eq!(from_str_radix("10", 62), Ok(62));
+        assert_eq!(from_str_radix("a", 37), Ok(36));
+        assert!(from_str_radix("b", 37).is_err());
+        assert_eq!(from_str_radix("+zz", 62), Ok(62 * 62 - 1));
+        assert_eq!(to_str_radix(62 * 62 - 1, 62), Ok("zz".to_string()));
+        assert_eq!(to_str_radix(36, 37), Ok("a".to_string()));
+        let mut rng = XorShift::new(2073);
+        for radix in 37..63 {
+            for _ in 0..500 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let seq = to_str_radix(value, radix).unwrap();
+                assert_eq!(from_str_radix(&seq, radix), Ok(value));
+            }
+        }
+        let msg = "Base MUST be at most 62, given 63".to_string();
+        assert_eq!(from_str_radix("1", 63), Err(Error::BaseTooBig(msg)));
+    }
+}
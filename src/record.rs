@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::iter;
+
+use digits::value_at;
+use parse::accumulate;
+use {checked_pow_base, positional_alphabet, Error, OverflowMode};
+
+
+/// Packs several values into one sequence of fixed-width fields.
+///
+/// Each field takes exactly its width of digits (zero padded), fields are
+/// concatenated in declaration order.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::RecordCodec;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let codec = RecordCodec::new(&hex, &[2, 4, 1]).unwrap();
+/// assert_eq!(codec.width(), 7);
+/// assert_eq!(codec.encode(&[255, 10, 1]), Ok("FF000A1".to_string()));
+/// assert_eq!(codec.decode("FF000A1"), Ok(vec![255, 10, 1]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordCodec {
+    alphabet: Vec<char>,
+    char2val: HashMap<char, usize>,
+    widths: Vec<usize>,
+    width: usize,
+}
+
+impl RecordCodec {
+    /// Creates codec for fields of `widths` digits of `alphabet`.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when `alphabet` length is 0
+    ///
+    /// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+    ///
+    /// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+    ///
+    /// * Returns `Error::OutOfRange` when a record of `widths` could take more than `isize::MAX` bytes
+    ///
+    /// ```
+    /// use numsys::{Error, RecordCodec};
+    ///
+    /// let msg = format!("Record of widths [{}, 1] MUST take at most {} bytes", usize::MAX, isize::MAX);
+    /// assert_eq!(RecordCodec::new(&['0', '1'], &[usize::MAX, 1]).unwrap_err(), Error::OutOfRange(msg));
+    /// ```
+    pub fn new(alphabet: &[char], widths: &[usize]) -> Result<RecordCodec, Error> {
+        let char2val = positional_alphabet(alphabet)?;
+        let glyph_bytes = alphabet.iter().map(|glyph| glyph.len_utf8()).max().unwrap_or(1);
+        let width = widths
+            .iter()
+            .try_fold(0_usize, |total, width| total.checked_add(*width))
+            .filter(|total| total.checked_mul(glyph_bytes).is_some_and(|bytes| bytes <= isize::MAX as usize))
+            .ok_or_else(|| {
+                Error::OutOfRange(format!("Record of widths {:?} MUST take at most {} bytes", widths, isize::MAX))
+            })?;
+        Ok(RecordCodec {
+            char2val,
+            alphabet: alphabet.to_vec(),
+            widths: widths.to_vec(),
+            width,
+        })
+    }
+
+    /// Returns total width (in chars) of an encoded record.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns width (in digits) of each field.
+    pub fn field_widths(&self) -> &[usize] {
+        &self.widths
+    }
+
+    /// Returns the biggest value each field can hold.
+    ///
+    /// Fields able to hold more than `usize` report `usize::MAX`.
+    ///
+    /// ```
+    /// use numsys::RecordCodec;
+    ///
+    /// let codec = RecordCodec::new(&['0', '1'], &[8, 1, 70]).unwrap();
+    /// assert_eq!(codec.field_max_values(), vec![255, 1, usize::MAX]);
+    /// ```
+    pub fn field_max_values(&self) -> Vec<usize> {
+        self.widths.iter().map(|width| self.field_max(*width)).collect()
+    }
+
+    fn field_max(&self, width: usize) -> usize {
+        // bases are at least 2, so widths beyond u32 overflow anyway
+        let exp = width.min(u32::MAX as usize) as u32;
+        checked_pow_base(self.alphabet.len(), exp).map_or(usize::MAX, |capacity| capacity - 1)
+    }
+
+    /// Converts `values` to a record, one value per field.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::LengthMismatch` when `values` count differs from fields count
+    ///
+    /// * Returns `Error::OutOfRange` when a value needs more digits than its field
+    ///
+    /// ```
+    /// use numsys::{Error, RecordCodec};
+    ///
+    /// let codec = RecordCodec::new(&['0', '1'], &[2, 3]).unwrap();
+    /// let msg = "Value 4 of field 0 MUST be at most 3".to_string();
+    /// assert_eq!(codec.encode(&[4, 0]), Err(Error::OutOfRange(msg)));
+    /// ```
+    pub fn encode(&self, values: &[usize]) -> Result<String, Error> {
+        if values.len() != self.widths.len() {
+            return Err(Error::LengthMismatch(format!(
+                "Values MUST have {} elements, given {}",
+                self.widths.len(),
+                values.len()
+            )));
+        }
+        let base = self.alphabet.len();
+        let mut record = String::new();
+        for (field, (value, width)) in values.iter().zip(&self.widths).enumerate() {
+            let max = self.field_max(*width);
+            if *value > max {
+                return Err(Error::OutOfRange(format!(
+                    "Value {} of field {} MUST be at most {}",
+                    value,
+                    field,
+                    max
+                )));
+            }
+            // enough for any `usize` in base 2, the value fits the width
+            let mut digits = [0; 64];
+            let mut count = 0;
+            let mut rest = *value;
+            while rest != 0 {
+                digits[count] = rest % base;
+                count += 1;
+                rest /= base;
+            }
+            record.extend(iter::repeat(self.alphabet[0]).take(width - count));
+            record.extend(digits[..count].iter().rev().map(|digit| self.alphabet[*digit]));
+        }
+        Ok(record)
+    }
+
+    /// Converts `record` back to its field values.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::LengthMismatch` when `record` char count differs from `width()`
+    ///
+    /// * Returns `Error::MissingChar` when the alphabet missing a char or more
+    ///
+    /// * Returns `Error::Overflow` when a field value exceeds `usize`
+    ///
+    /// ```
+    /// use numsys::{Error, RecordCodec};
+    ///
+    /// let codec = RecordCodec::new(&['0', '1'], &[2, 3]).unwrap();
+    /// let msg = "Record MUST have 5 chars, given 4".to_string();
+    /// assert_eq!(codec.decode("0101"), Err(Error::LengthMismatch(msg)));
+    /// ```
+    pub fn decode(&self, record: &str) -> Result<Vec<usize>, Error> {
+        let length = record.chars().count();
+        if length != self.width() {
+            return Err(Error::LengthMismatch(
+                format!("Record MUST have {} chars, given {}", self.width(), length),
+            ));
+        }
+        let base = self.alphabet.len();
+        let mut glyphs = record.chars().enumerate();
+        let mut values = Vec::with_capacity(self.widths.len());
+        for (field, width) in self.widths.iter().enumerate() {
+            let mut value: usize = 0;
+            for (position, glyph) in glyphs.by_ref().take(*width) {
+                let digit = value_at(glyph, Some(position), &self.char2val, &self.alphabet)?;
+                value = accumulate(value, base, digit, OverflowMode::Error, || {
+                    Error::Overflow(format!("Value of field {} exceeds usize", field))
+                })?;
+            }
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    fn base32() -> Vec<char> {
+        "0123456789ABCDEFGHJKMNPQRSTVWXYZ".chars().collect()
+    }
+
+    #[test]
+    fn boundary_values_per_field() {
+        // u16, u32, u8 in base 32
+        let codec = RecordCodec::new(&base32(), &[4, 7, 2]).unwrap();
+        assert_eq!(codec.field_max_values(), vec![1_048_575, 34_359_738_367, 1023]);
+        let max = codec.field_max_values();
+        let record = codec.encode(&max).unwrap();
+        assert_eq!(record, "ZZZZZZZZZZZZZ");
+        assert_eq!(codec.decode(&record), Ok(max.clone()));
+        assert_eq!(codec.encode(&[0, 0, 0]), Ok("0000000000000".to_string()));
+        for field in 0..3 {
+            let mut values = max.clone();
+            values[field] += 1;
+            match codec.encode(&values) {
+                Err(Error::OutOfRange(msg)) => assert!(msg.contains(&format!("field {}", field))),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_length_is_rejected() {
+        let codec = RecordCodec::new(&base32(), &[4, 7, 2]).unwrap();
+        assert!(codec.decode("000000000000").is_err());
+        assert!(codec.decode("00000000000000").is_err());
+        assert!(codec.encode(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn widths_must_fit_a_record() {
+        for widths in [&[usize::MAX, 1][..], &[usize::MAX / 2 + 1, usize::MAX / 2 + 1], &[usize::MAX]].iter() {
+            match RecordCodec::new(&['0', '1'], widths) {
+                Err(Error::OutOfRange(_)) => (),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        // 4 bytes a glyph
+        let wide = usize::MAX / 4;
+        assert!(RecordCodec::new(&['0', '1'], &[wide]).is_ok());
+        assert!(RecordCodec::new(&['0', '𝍸'], &[wide]).is_err());
+        let codec = RecordCodec::new(&['0', '1'], &[0, 3, 0]).unwrap();
+        assert_eq!(codec.width(), 3);
+        assert_eq!(codec.encode(&[0, 5, 0]), Ok("101".to_string()));
+    }
+
+    #[test]
+    fn multi_byte_glyphs_split_on_chars() {
+        let codec = RecordCodec::new(&['★', '☆', 'ż'], &[2, 3]).unwrap();
+        let record = codec.encode(&[8, 5]).unwrap();
+        assert_eq!(record, "żż★☆ż");
+        assert_eq!(record.chars().count(), 5);
+        assert_eq!(codec.decode(&record), Ok(vec![8, 5]));
+    }
+
+    #[test]
+    fn random_records_round_trip() {
+        let codec = RecordCodec::new(&base32(), &[4, 7, 2, 1]).unwrap();
+        let max = codec.field_max_values();
+        let mut rng = XorShift::new(206);
+        for _ in 0..1000 {
+            let values: Vec<usize> = max.iter().map(|m| rng.below(*m + 1)).collect();
+            let record = codec.encode(&values).unwrap();
+            assert_eq!(record.chars().count(), codec.width());
+            assert_eq!(codec.decode(&record), Ok(values));
+        }
+    }
+}
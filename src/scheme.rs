@@ -0,0 +1,260 @@
+use digits::missing_char;
+use grouped::{digit_at, digit_values, digits_value, join_groups, verify_check};
+use {check_base, Error, DIGITS_UPPER_AZ};
+
+
+/// Check digit of `digits` with the Luhn mod N algorithm, N being `base`.
+///
+/// Any single mistyped digit and most swaps of adjacent digits are detected.
+pub(crate) fn luhn_check(digits: &[usize], base: usize) -> usize {
+    let sum = digits.iter().rev().enumerate().fold(0, |sum, (index, digit)| {
+        // the rightmost digit is doubled, the check digit goes after it
+        let addend = if index % 2 == 0 { digit * 2 } else { *digit };
+        (sum + addend / base + addend % base) % base
+    });
+    (base - sum) % base
+}
+
+
+/// Codes of `CodeScheme`: digits of a base, an optional check digit and grouping.
+///
+/// Build it with `CodeSchemeBuilder`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::CodeSchemeBuilder;
+///
+/// let scheme = CodeSchemeBuilder::new().base(32).group(4).separator('-').checksum(true)
+///     .case_insensitive(true).build().unwrap();
+/// let code = scheme.encode(123_456_789);
+/// assert_eq!(code, "3LNJ-8L1");
+/// assert_eq!(scheme.decode(&code), Ok(123_456_789));
+/// assert_eq!(scheme.decode("3lnj8l1"), Ok(123_456_789));
+/// assert!(scheme.decode("3LNJ-8L2").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeScheme {
+    base: usize,
+    group: usize,
+    separator: char,
+    checksum: bool,
+    case_insensitive: bool,
+}
+
+impl CodeScheme {
+    /// Converts `n` to its code.
+    pub fn encode(&self, n: usize) -> String {
+        let mut digits = digit_values(n, self.base).expect("Base validated by the builder");
+        if self.checksum {
+            let check = luhn_check(&digits, self.base);
+            digits.push(check);
+        }
+        let glyphs: Vec<char> = digits.iter().map(|digit| DIGITS_UPPER_AZ[*digit]).collect();
+        join_groups(&glyphs, self.group, self.separator)
+    }
+
+    /// Converts `code` back to its value, verifying the check digit.
+    ///
+    /// Separators are removed wherever they are, so a code typed without
+    /// them or grouped otherwise is fine too.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::SequenceEmpty` when `code` has no digits (or only the check digit)
+    ///
+    /// * Returns `Error::MissingChar` when `code` includes a char which isn't a digit of the scheme
+    ///
+    /// * Returns `Error::ChecksumMismatch` when the check digit doesn't match the digits
+    ///
+    /// ```
+    /// use numsys::CodeSchemeBuilder;
+    /// use numsys::Error;
+    ///
+    /// let scheme = CodeSchemeBuilder::new().checksum(true).build().unwrap();
+    /// assert_eq!(scheme.encode(1234), "12344");
+    /// let msg = "Check digit \'4\' of \"12434\" MUST be \'5\'".to_string();
+    /// assert_eq!(scheme.decode("12434"), Err(Error::ChecksumMismatch(msg)));
+    /// ```
+    ///
+    /// * Returns `Error::Overflow` when value exceeds `usize`
+    pub fn decode(&self, code: &str) -> Result<usize, Error> {
+        let alphabet = &DIGITS_UPPER_AZ[..self.base];
+        let mut digits = Vec::with_capacity(code.len());
+        for (position, glyph) in code.chars().enumerate() {
+            if self.group != 0 && glyph == self.separator {
+                continue;
+            }
+            let wanted = if self.case_insensitive { glyph.to_ascii_uppercase() } else { glyph };
+            // the error names the glyph as given, not folded
+            let digit = digit_at(wanted, position, alphabet).map_err(|_| missing_char(glyph, Some(position), alphabet))?;
+            digits.push(digit);
+        }
+        let payload = if self.checksum { digits.len().saturating_sub(1) } else { digits.len() };
+        if payload == 0 {
+            return Err(Error::SequenceEmpty);
+        }
+        if self.checksum {
+            let expected = luhn_check(&digits[..payload], self.base);
+            verify_check(digits[payload], expected, alphabet, &format!("{:?}", code))?;
+        }
+        digits_value(&digits[..payload], self.base, code)
+    }
+}
+
+
+/// Builder of `CodeScheme`, by default ungrouped decimal codes without a check digit.
+///
+/// Digits are `DIGITS_UPPER_AZ` up to the base, letters upper case.
+///
+/// # Errors
+///
+/// `build` validates the settings:
+///
+/// * Returns `Error::BaseTooSmall` when base is less then 2
+///
+/// * Returns `Error::BaseTooBig` when base is greater then 36
+///
+/// * Returns `Error::InvalidMarker` when the separator is a digit of the scheme
+///
+/// ```
+/// use numsys::CodeSchemeBuilder;
+/// use numsys::Error;
+///
+/// let msg = "Separator \'a\' MUST NOT be a digit of base 16".to_string();
+/// let builder = CodeSchemeBuilder::new().base(16).group(4).separator('a').case_insensitive(true);
+/// assert_eq!(builder.build(), Err(Error::InvalidMarker(msg)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodeSchemeBuilder {
+    base: usize,
+    group: usize,
+    separator: char,
+    checksum: bool,
+    case_insensitive: bool,
+}
+
+impl Default for CodeSchemeBuilder {
+    fn default() -> CodeSchemeBuilder {
+        CodeSchemeBuilder { base: 10, group: 0, separator: '-', checksum: false, case_insensitive: false }
+    }
+}
+
+impl CodeSchemeBuilder {
+    /// Creates builder of ungrouped decimal codes without a check digit.
+    pub fn new() -> CodeSchemeBuilder {
+        CodeSchemeBuilder::default()
+    }
+
+    /// Sets base of the digits.
+    pub fn base(mut self, base: usize) -> CodeSchemeBuilder {
+        self.base = base;
+        self
+    }
+
+    /// Splits codes in groups of `group` chars counted from the start, 0 keeps them whole.
+    pub fn group(mut self, group: usize) -> CodeSchemeBuilder {
+        self.group = group;
+        self
+    }
+
+    /// Sets char between groups, `-` by default.
+    pub fn separator(mut self, separator: char) -> CodeSchemeBuilder {
+        self.separator = separator;
+        self
+    }
+
+    /// Appends a check digit, Luhn mod N with N being the base.
+    pub fn checksum(mut self, checksum: bool) -> CodeSchemeBuilder {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Accepts lower case letters when decoding, codes are always minted upper case.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> CodeSchemeBuilder {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Validates settings and creates the scheme.
+    pub fn build(&self) -> Result<CodeScheme, Error> {
+        check_base(self.base)?;
+        let folded = if self.case_insensitive { self.separator.to_ascii_uppercase() } else { self.separator };
+        if self.group != 0 && DIGITS_UPPER_AZ[..self.base].contains(&folded) {
+            return Err(Error::InvalidMarker(
+                format!("Separator {:?} MUST NOT be a digit of base {}", self.separator, self.base),
+            ));
+        }
+        Ok(CodeScheme {
+            base: self.base,
+            group: self.group,
+            separator: self.separator,
+            checksum: self.checksum,
+            case_insensitive: self.case_insensitive,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    #[test]
+    fn mint_and_round_trip() {
+        let scheme = CodeSchemeBuilder::new().base(32).group(4).separator('-').checksum(true)
+            .case_insensitive(true).build().unwrap();
+        let mut rng = XorShift::new(246);
+        for _ in 0..2_000 {
+            let n = rng.next_u64() as usize >> rng.below(64);
+            let code = scheme.encode(n);
+            assert!(code.split('-').all(|group| group.len() <= 4));
+            assert!(code.split('-').rev().skip(1).all(|group| group.len() == 4));
+            assert_eq!(scheme.decode(&code), Ok(n));
+            assert_eq!(scheme.decode(&code.to_lowercase()), Ok(n));
+            assert_eq!(scheme.decode(&code.replace('-', "")), Ok(n));
+        }
+        assert_eq!(scheme.encode(0), "00");
+        assert_eq!(scheme.decode("00"), Ok(0));
+        let strict = CodeSchemeBuilder::new().base(32).group(4).checksum(true).build().unwrap();
+        assert_eq!(strict.decode("3LNJ-8L1"), Ok(123_456_789));
+        assert!(strict.decode("3lnj-8l1").is_err());
+    }
+
+    #[test]
+    fn check_digit_catches_typos() {
+        let scheme = CodeSchemeBuilder::new().base(32).checksum(true).build().unwrap();
+        let code: Vec<char> = scheme.encode(0xDEAD_BEEF).chars().collect();
+        let alphabet = &DIGITS_UPPER_AZ[..32];
+        for position in 0..code.len() {
+            for glyph in alphabet.iter().filter(|g| **g != code[position]) {
+                let mut typo = code.clone();
+                typo[position] = *glyph;
+                let typo: String = typo.into_iter().collect();
+                match scheme.decode(&typo) {
+                    Err(Error::ChecksumMismatch(_)) => {}
+                    other => panic!("{} gave {:?}", typo, other),
+                }
+            }
+        }
+        assert_eq!(scheme.decode("0"), Err(Error::SequenceEmpty));
+        assert_eq!(scheme.decode(""), Err(Error::SequenceEmpty));
+    }
+
+    #[test]
+    fn settings() {
+        let plain = CodeSchemeBuilder::new().build().unwrap();
+        assert_eq!(plain.encode(1_234_567), "1234567");
+        assert!(plain.decode("123-4").is_err());
+        let grouped = CodeSchemeBuilder::new().group(3).separator(' ').build().unwrap();
+        assert_eq!(grouped.encode(1_234_567), "123 456 7");
+        assert_eq!(grouped.decode("1 234 567"), Ok(1_234_567));
+        assert!(CodeSchemeBuilder::new().base(37).build().is_err());
+        assert!(CodeSchemeBuilder::new().base(1).build().is_err());
+        assert!(CodeSchemeBuilder::new().group(2).separator('7').build().is_err());
+        // never in codes when ungrouped
+        assert!(CodeSchemeBuilder::new().separator('7').build().is_ok());
+        assert!(CodeSchemeBuilder::new().base(16).group(2).separator('a').build().is_ok());
+    }
+}
@@ -0,0 +1,152 @@
+use {dec2digits, positional_alphabet, Error};
+
+
+/// Text put around one digit glyph by a `dec2seq_styled` hook.
+///
+/// The glyph itself isn't part of the fragment, so styling can't alter digits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StyledFragment {
+    /// Text written before the glyph (e.g. an ANSI color code or `<span>`)
+    pub before: String,
+    /// Text written after the glyph (e.g. an ANSI reset or `</span>`)
+    pub after: String,
+}
+
+impl StyledFragment {
+    /// Creates fragment leaving the glyph as it is.
+    pub fn plain() -> StyledFragment {
+        StyledFragment::default()
+    }
+
+    /// Creates fragment wrapping the glyph between `before` and `after`.
+    pub fn wrap<S: Into<String>, T: Into<String>>(before: S, after: T) -> StyledFragment {
+        StyledFragment { before: before.into(), after: after.into() }
+    }
+}
+
+
+/// Converts `decimal` using `char2val` translation, wrapping each digit as `style` says.
+///
+/// `style` gets position of the digit (0 is the most significant one), its
+/// value and glyph, and returns text to put around the glyph. Zero is one
+/// digit, `char2val[0]`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{dec2seq_styled, StyledFragment};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let alternating = |position: usize, _, _| if position % 2 == 0 {
+///     StyledFragment::wrap("<b>", "</b>")
+/// } else {
+///     StyledFragment::plain()
+/// };
+/// assert_eq!(dec2seq_styled(0xABC, &hex, alternating), Ok("<b>A</b>B<b>C</b>".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+pub fn dec2seq_styled<F>(decimal: usize, char2val: &[char], style: F) -> Result<String, Error>
+where
+    F: Fn(usize, usize, char) -> StyledFragment,
+{
+    positional_alphabet(char2val)?;
+    let mut sequence = String::new();
+    for (position, digit) in dec2digits(decimal, char2val.len())?.into_iter().enumerate() {
+        let glyph = char2val[digit];
+        let fragment = style(position, digit, glyph);
+        sequence.push_str(&fragment.before);
+        sequence.push(glyph);
+        sequence.push_str(&fragment.after);
+    }
+    Ok(sequence)
+}
+
+
+/// Returns positions of digits differing between `a` and `b` in `base`.
+///
+/// The shorter representation is padded with zeros to the longer one, and
+/// positions are counted in it from the most significant digit, so they fit
+/// `dec2seq_styled` of the bigger value.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::diff_digits;
+///
+/// assert_eq!(diff_digits(0xABCD, 0xAB0D, 16), Ok(vec![2]));
+/// assert_eq!(diff_digits(0x1FF, 0xFF, 16), Ok(vec![0]));
+/// assert_eq!(diff_digits(7, 7, 2), Ok(vec![]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+pub fn diff_digits(a: usize, b: usize, base: usize) -> Result<Vec<usize>, Error> {
+    let (a, b) = (dec2digits(a, base)?, dec2digits(b, base)?);
+    let width = a.len().max(b.len());
+    let digit = |digits: &[usize], position: usize| {
+        let padding = width - digits.len();
+        if position < padding { 0 } else { digits[position - padding] }
+    };
+    Ok((0..width).filter(|position| digit(&a, *position) != digit(&b, *position)).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use testutil::XorShift;
+    use {switch_dec_base, DIGITS_UPPER_AZ};
+
+    #[test]
+    fn fragments_in_digit_order() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        let calls = RefCell::new(Vec::new());
+        let styled = dec2seq_styled(0x1F0, hex, |position, digit, glyph| {
+            calls.borrow_mut().push((position, digit, glyph));
+            StyledFragment::wrap(format!("[{}:", position), "]")
+        });
+        assert_eq!(styled, Ok("[0:1][1:F][2:0]".to_string()));
+        assert_eq!(calls.into_inner(), vec![(0, 1, '1'), (1, 15, 'F'), (2, 0, '0')]);
+        assert_eq!(dec2seq_styled(0, hex, |_, _, _| StyledFragment::wrap("<", ">")), Ok("<0>".to_string()));
+        assert!(dec2seq_styled(1, &['0'], |_, _, _| StyledFragment::plain()).is_err());
+    }
+
+    #[test]
+    fn styling_keeps_digits() {
+        let mut rng = XorShift::new(229);
+        let ansi = |position: usize, digit: usize, _| {
+            StyledFragment::wrap(format!("\x1b[{}m", 31 + (position + digit) % 6), "\x1b[0m")
+        };
+        for base in 2..37 {
+            for _ in 0..100 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let styled = dec2seq_styled(value, &DIGITS_UPPER_AZ[..base], ansi).unwrap();
+                let stripped: String = styled.split("\x1b[").map(|part| part.split('m').nth(1).unwrap_or("")).collect();
+                assert_eq!(stripped, switch_dec_base(value, base).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn diff_digits_feeds_highlighting() {
+        let (value, reference) = (0xDEADBEEF, 0xDEAFBEEF);
+        let differing = diff_digits(value, reference, 16).unwrap();
+        assert_eq!(differing, vec![3]);
+        let marked = dec2seq_styled(value, &DIGITS_UPPER_AZ[..16], |position, _, _| {
+            if differing.contains(&position) { StyledFragment::wrap("*", "*") } else { StyledFragment::plain() }
+        });
+        assert_eq!(marked, Ok("DEA*D*BEEF".to_string()));
+        assert_eq!(diff_digits(0, 0b1010, 2), Ok(vec![0, 2]));
+        assert_eq!(diff_digits(0b1010, 0, 2), diff_digits(0, 0b1010, 2));
+        assert!(diff_digits(1, 2, 1).is_err());
+    }
+}
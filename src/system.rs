@@ -0,0 +1,800 @@
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+use digits::value_at;
+use parse::{accumulate, value_overflow};
+use {checked_pow_base, dec2seq, digit_count, positional_alphabet, Error, OverflowMode};
+
+
+/// Positional numeral system with an optional fixed width and value domain.
+///
+/// Values outside of the domain are rejected both when formatting and
+/// parsing, so a system with a width always produces output of that width.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::NumeralSystem;
+///
+/// let base36: Vec<char> = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect();
+/// let tickets = NumeralSystem::new(&base36).unwrap().with_width(3).unwrap();
+/// assert_eq!(tickets.capacity(), Some(46_656));
+/// assert_eq!(tickets.format(35), Ok("00Z".to_string()));
+/// assert_eq!(tickets.parse("00Z"), Ok(35));
+/// assert!(tickets.format(46_656).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct NumeralSystem {
+    alphabet: Vec<char>,
+    char2val: HashMap<char, usize>,
+    width: Option<usize>,
+    max_value: Option<usize>,
+}
+
+impl NumeralSystem {
+    /// Creates system of `alphabet` with the whole `usize` domain and no padding.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::DictEmpty` when `alphabet` length is 0
+    ///
+    /// * Returns `Error::BaseTooSmall` when `alphabet` length is 1
+    ///
+    /// * Returns `Error::MultipleChar` when `alphabet` includes duplicated chars
+    pub fn new(alphabet: &[char]) -> Result<NumeralSystem, Error> {
+        Ok(NumeralSystem {
+            char2val: positional_alphabet(alphabet)?,
+            alphabet: alphabet.to_vec(),
+            width: None,
+            max_value: None,
+        })
+    }
+
+    /// Makes the system pad output to `width` digits, and limits its domain to them.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::OutOfRange` when `width` is more than the digits of `usize::MAX` in the base
+    ///
+    /// ```
+    /// use numsys::{Error, NumeralSystem};
+    ///
+    /// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+    /// let msg = "Width MUST be at most 16 digits of base 16, given 17".to_string();
+    /// assert_eq!(NumeralSystem::new(&hex).unwrap().with_width(17).unwrap_err(), Error::OutOfRange(msg));
+    /// ```
+    pub fn with_width(mut self, width: usize) -> Result<NumeralSystem, Error> {
+        let base = self.alphabet.len();
+        let widest = digit_count(usize::MAX, base)?;
+        if width > widest {
+            return Err(Error::OutOfRange(
+                format!("Width MUST be at most {} digits of base {}, given {}", widest, base, width),
+            ));
+        }
+        self.width = Some(width);
+        Ok(self)
+    }
+
+    /// Limits domain of the system to values up to `max_value` (inclusive).
+    pub fn with_max_value(mut self, max_value: usize) -> NumeralSystem {
+        self.max_value = Some(max_value);
+        self
+    }
+
+    /// Returns alphabet of the system.
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    /// Returns fixed width of the system, if any.
+    pub fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Returns the biggest value of the domain.
+    ///
+    /// It's the lower of `with_max_value` and the biggest value of `with_width`
+    /// digits, `usize::MAX` for an unlimited system.
+    pub fn max_value(&self) -> usize {
+        let width_max = self.width.map_or(usize::MAX, |width| {
+            // `with_width` keeps it at most 64
+            checked_pow_base(self.alphabet.len(), width as u32).map_or(usize::MAX, |capacity| capacity - 1)
+        });
+        self.max_value.map_or(width_max, |max| max.min(width_max))
+    }
+
+    /// Returns count of values in the domain, `None` when it's whole `usize`.
+    ///
+    /// ```
+    /// use numsys::NumeralSystem;
+    ///
+    /// let system = NumeralSystem::new(&['0', '1']).unwrap();
+    /// assert_eq!(system.capacity(), None);
+    /// assert_eq!(system.with_max_value(9).capacity(), Some(10));
+    /// ```
+    pub fn capacity(&self) -> Option<usize> {
+        self.max_value().checked_add(1)
+    }
+
+    fn check_domain(&self, value: usize) -> Result<usize, Error> {
+        let max = self.max_value();
+        if value > max {
+            return Err(Error::OutOfDomain { value, max });
+        }
+        Ok(value)
+    }
+
+    /// Converts `value` to a sequence, padded to the width of the system.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::OutOfDomain` when `value` is greater than `max_value()`
+    ///
+    /// ```
+    /// use numsys::{Error, NumeralSystem};
+    ///
+    /// let system = NumeralSystem::new(&['0', '1']).unwrap().with_width(4).unwrap();
+    /// assert_eq!(system.format(16), Err(Error::OutOfDomain { value: 16, max: 15 }));
+    /// ```
+    pub fn format(&self, value: usize) -> Result<String, Error> {
+        self.check_domain(value)?;
+        let digits: Vec<char> = dec2seq(value, &self.alphabet)?.chars().collect();
+        let minimal = if digits.is_empty() && self.width.is_none() { 1 } else { digits.len() };
+        let width = self.width.unwrap_or(0).max(minimal);
+        let mut sequence = vec![self.alphabet[0]; width - digits.len()];
+        sequence.extend(digits);
+        Ok(sequence.into_iter().collect())
+    }
+
+    /// Converts `sequence` back to a value of the domain.
+    ///
+    /// Sequences shorter than the width are accepted, as if padded.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::LengthMismatch` when `sequence` is wider than the width of the system
+    ///
+    /// * Returns `Error::MissingChar` when the alphabet missing a char or more
+    ///
+    /// * Returns `Error::Overflow` when value exceeds `usize`
+    ///
+    /// * Returns `Error::OutOfDomain` when value is greater than `max_value()`
+    ///
+    /// ```
+    /// use numsys::{Error, NumeralSystem};
+    ///
+    /// let system = NumeralSystem::new(&['0', '1']).unwrap().with_width(4).unwrap().with_max_value(9);
+    /// assert_eq!(system.parse("1010"), Err(Error::OutOfDomain { value: 10, max: 9 }));
+    /// let msg = "Sequence MUST have at most 4 chars, given 5".to_string();
+    /// assert_eq!(system.parse("00001"), Err(Error::LengthMismatch(msg)));
+    /// ```
+    pub fn parse(&self, sequence: &str) -> Result<usize, Error> {
+        let length = sequence.chars().count();
+        if let Some(width) = self.width {
+            if length > width {
+                return Err(Error::LengthMismatch(
+                    format!("Sequence MUST have at most {} chars, given {}", width, length),
+                ));
+            }
+        }
+        let base = self.alphabet.len();
+        let mut dec: usize = 0;
+        for (position, glyph) in sequence.chars().enumerate() {
+            let value = value_at(glyph, Some(position), &self.char2val, &self.alphabet)?;
+            dec = accumulate(dec, base, value, OverflowMode::Error, || value_overflow(sequence))?;
+        }
+        self.check_domain(dec)
+    }
+
+    /// Serializes the system verbatim, see `from_portable_bytes`.
+    ///
+    /// Format version 1 is, integers little endian:
+    ///
+    /// * magic `NSYS` and version byte `1`
+    /// * flags byte, bit 0 set for a width, bit 1 for a max value (other bits 0)
+    /// * `u32` count of alphabet chars, then each char as `u32` code point
+    /// * `u64` width and `u64` max value, when flagged
+    /// * `u32` FNV-1a checksum of all the preceding bytes
+    ///
+    /// Bytes of a version never change. New state makes a new version,
+    /// which readers of older versions reject, while newer readers keep
+    /// reading all older versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use numsys::NumeralSystem;
+    ///
+    /// let system = NumeralSystem::new(&['0', '1']).unwrap().with_width(8).unwrap();
+    /// let bytes = system.to_portable_bytes();
+    /// assert_eq!(&bytes[..5], b"NSYS\x01");
+    /// let loaded = NumeralSystem::from_portable_bytes(&bytes).unwrap();
+    /// assert_eq!(loaded.format(5), Ok("00000101".to_string()));
+    /// ```
+    pub fn to_portable_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PORTABLE_MAGIC.len() + 2 + 4 * (self.alphabet.len() + 2) + 16);
+        bytes.extend_from_slice(PORTABLE_MAGIC);
+        bytes.push(PORTABLE_VERSION);
+        bytes.push(self.width.map_or(0, |_| FLAG_WIDTH) | self.max_value.map_or(0, |_| FLAG_MAX_VALUE));
+        bytes.extend_from_slice(&(self.alphabet.len() as u32).to_le_bytes());
+        for glyph in &self.alphabet {
+            bytes.extend_from_slice(&(*glyph as u32).to_le_bytes());
+        }
+        for value in self.width.iter().chain(self.max_value.iter()) {
+            bytes.extend_from_slice(&(*value as u64).to_le_bytes());
+        }
+        let checksum = fnv1a(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Loads system serialized by `to_portable_bytes`, without deriving anything anew.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::InvalidBytes` when `bytes` are truncated, have trailing bytes,
+    ///   a wrong magic, unknown flags, a char which isn't a code point or a value exceeding `usize`
+    ///
+    /// * Returns `Error::UnsupportedVersion` when format version isn't known to this crate
+    ///
+    /// ```
+    /// use numsys::{Error, NumeralSystem};
+    ///
+    /// let mut bytes = NumeralSystem::new(&['0', '1']).unwrap().to_portable_bytes();
+    /// bytes[4] = 9;
+    /// let msg = "Format version MUST be at most 1, given 9".to_string();
+    /// assert_eq!(NumeralSystem::from_portable_bytes(&bytes).unwrap_err(), Error::UnsupportedVersion(msg));
+    /// ```
+    ///
+    /// * Returns `Error::ChecksumMismatch` when `bytes` were corrupted
+    ///
+    /// * Returns errors of `new` when the alphabet is invalid, and of `with_width` for the width
+    pub fn from_portable_bytes(bytes: &[u8]) -> Result<NumeralSystem, Error> {
+        let mut reader = PortableReader { bytes, position: 0 };
+        if reader.take(PORTABLE_MAGIC.len())? != PORTABLE_MAGIC {
+            return Err(Error::InvalidBytes("Bytes MUST start with magic \"NSYS\"".into()));
+        }
+        let version = reader.take(1)?[0];
+        if version == 0 || version > PORTABLE_VERSION {
+            return Err(Error::UnsupportedVersion(
+                format!("Format version MUST be at most {}, given {}", PORTABLE_VERSION, version),
+            ));
+        }
+        let flags = reader.take(1)?[0];
+        if flags & !(FLAG_WIDTH | FLAG_MAX_VALUE) != 0 {
+            return Err(Error::InvalidBytes(format!("Flags MUST be known, given {:#04x}", flags)));
+        }
+        let length = reader.u32()? as usize;
+        if length > bytes.len() / 4 {
+            return Err(Error::InvalidBytes(format!("Alphabet of {} chars MUST fit the bytes", length)));
+        }
+        let mut alphabet = Vec::with_capacity(length);
+        for _ in 0..length {
+            let code = reader.u32()?;
+            let glyph = ::std::char::from_u32(code)
+                .ok_or_else(|| Error::InvalidBytes(format!("Char MUST be a code point, given {:#x}", code)))?;
+            alphabet.push(glyph);
+        }
+        let width = if flags & FLAG_WIDTH != 0 { Some(reader.usize()?) } else { None };
+        let max_value = if flags & FLAG_MAX_VALUE != 0 { Some(reader.usize()?) } else { None };
+        let checksum = fnv1a(&bytes[..reader.position]);
+        if reader.u32()? != checksum {
+            return Err(Error::ChecksumMismatch("Checksum of portable bytes MUST match".into()));
+        }
+        if reader.position != bytes.len() {
+            return Err(Error::InvalidBytes(
+                format!("Bytes MUST end after {} bytes, given {}", reader.position, bytes.len()),
+            ));
+        }
+        let mut system = NumeralSystem::new(&alphabet)?;
+        if let Some(width) = width {
+            system = system.with_width(width)?;
+        }
+        system.max_value = max_value;
+        Ok(system)
+    }
+}
+
+
+const PORTABLE_MAGIC: &[u8] = b"NSYS";
+const PORTABLE_VERSION: u8 = 1;
+const FLAG_WIDTH: u8 = 1;
+const FLAG_MAX_VALUE: u8 = 2;
+
+/// 32-bit FNV-1a hash, the checksum of portable bytes.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| (hash ^ *byte as u32).wrapping_mul(0x0100_0193))
+}
+
+struct PortableReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PortableReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let end = self.position + count;
+        if end > self.bytes.len() {
+            return Err(Error::InvalidBytes(
+                format!("Bytes MUST have at least {} bytes, given {}", end, self.bytes.len()),
+            ));
+        }
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut le = [0; 4];
+        le.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(le))
+    }
+
+    fn usize(&mut self) -> Result<usize, Error> {
+        let mut le = [0; 8];
+        le.copy_from_slice(self.take(8)?);
+        let value = u64::from_le_bytes(le);
+        if value > usize::MAX as u64 {
+            return Err(Error::InvalidBytes(format!("Value {} MUST fit usize", value)));
+        }
+        Ok(value as usize)
+    }
+}
+
+
+/// Count of digits `DisplayValue` keeps inline, enough for any `usize` (and width) in base 2.
+const INLINE_DIGITS: usize = 64;
+
+/// Value rendered in advance by `try_display`, its `Display` only writes the glyphs.
+#[derive(Debug, Clone)]
+pub struct DisplayValue {
+    inline: [char; INLINE_DIGITS],
+    length: usize,
+}
+
+impl fmt::Display for DisplayValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for glyph in &self.inline[..self.length] {
+            f.write_char(*glyph)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for DisplayValue {
+    fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<W>) -> Result<(), W::Error> {
+        for glyph in &self.inline[..self.length] {
+            f.write_char(*glyph)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DisplayValue {
+    fn format(&self, f: defmt::Formatter) {
+        for glyph in &self.inline[..self.length] {
+            defmt::write!(f, "{=char}", *glyph);
+        }
+    }
+}
+
+
+/// Renders `value` with `system` eagerly, for an infallible `Display`.
+///
+/// All the fallible work (domain check, digits) is done here, formatting the
+/// result can't fail. Digits are kept inline, so it never allocates: no
+/// width of a system is more than the 64 digits of `usize::MAX` in base 2.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{try_display, NumeralSystem};
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let system = NumeralSystem::new(&hex).unwrap().with_width(4).unwrap();
+/// let shown = try_display(255, &system).unwrap();
+/// assert_eq!(format!("id: {}", shown), "id: 00FF");
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::OutOfDomain` when `value` is greater than `system.max_value()`
+///
+/// ```
+/// use numsys::{try_display, Error, NumeralSystem};
+///
+/// let system = NumeralSystem::new(&['0', '1']).unwrap().with_width(2).unwrap();
+/// assert_eq!(try_display(4, &system).unwrap_err(), Error::OutOfDomain { value: 4, max: 3 });
+/// ```
+pub fn try_display(value: usize, system: &NumeralSystem) -> Result<DisplayValue, Error> {
+    system.check_domain(value)?;
+    let base = system.alphabet.len();
+    // like `format`, zero has no digits when padded anyway
+    let count = if value == 0 && system.width.is_some() { 0 } else { digit_count(value, base)? };
+    let width = system.width.map_or(count, |width| width.max(count));
+    let mut shown = DisplayValue {
+        inline: [system.alphabet[0]; INLINE_DIGITS],
+        length: width,
+    };
+    let mut rest = value;
+    for glyph in shown.inline[width - count..width].iter_mut().rev() {
+        *glyph = system.alphabet[rest % base];
+        rest /= base;
+    }
+    Ok(shown)
+}
+
+
+/// 64-bit FNV-1a hash state, of `representation_fingerprint`.
+#[derive(Debug, Clone)]
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Fnv64 {
+        Fnv64(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Starts hashing a representation of `system` with its descriptor.
+    fn of_system(system: &NumeralSystem) -> Fnv64 {
+        let mut hash = Fnv64::new();
+        hash.write(&(system.alphabet.len() as u32).to_le_bytes());
+        for glyph in &system.alphabet {
+            hash.write(&(*glyph as u32).to_le_bytes());
+        }
+        match system.width {
+            Some(width) => {
+                hash.write(&[1]);
+                hash.write(&(width as u64).to_le_bytes());
+            }
+            None => hash.write(&[0]),
+        }
+        hash
+    }
+}
+
+
+/// Returns stable fingerprint of `value` as rendered by `system`, e.g. for cache keys.
+///
+/// It's computed digit by digit, without rendering, and it's frozen: the
+/// same inputs give the same fingerprint in every version of the crate and
+/// on every platform, so it can be recomputed in other languages. It's the
+/// 64-bit FNV-1a hash (offset basis `0xcbf29ce484222325`, prime
+/// `0x100000001b3`) of these bytes, integers little endian:
+///
+/// * `u32` count of alphabet chars, then each char as `u32` code point
+/// * byte `0` without a width, or byte `1` and the width as `u64`
+/// * each rendered digit value as `u32`, most significant first
+/// * `u64` count of rendered digits
+///
+/// Rendered digits are those of `format`, zero padded to the width, and at
+/// least one. The domain isn't checked. So glyphs (case included), width and
+/// value all change it, but `with_max_value` doesn't.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{representation_fingerprint, NumeralSystem};
+///
+/// let upper = NumeralSystem::new(&"0123456789ABCDEF".chars().collect::<Vec<_>>()).unwrap();
+/// let lower = NumeralSystem::new(&"0123456789abcdef".chars().collect::<Vec<_>>()).unwrap();
+/// assert_eq!(representation_fingerprint(255, &upper), 0x1567_97d9_a901_fc7b);
+/// assert_ne!(representation_fingerprint(255, &upper), representation_fingerprint(255, &lower));
+/// let padded = upper.clone().with_width(4).unwrap();
+/// assert_ne!(representation_fingerprint(255, &upper), representation_fingerprint(255, &padded));
+/// ```
+pub fn representation_fingerprint(value: usize, system: &NumeralSystem) -> u64 {
+    let base = system.alphabet.len();
+    let mut digits = [0_u32; INLINE_DIGITS];
+    let mut count = 0;
+    let mut rest = value;
+    while rest != 0 || count == 0 {
+        digits[count] = (rest % base) as u32;
+        rest /= base;
+        count += 1;
+    }
+    let mut hasher = RepresentationHasher { hash: Fnv64::of_system(system), system, digits: 0 };
+    // like `format`, zero has no digits when padded anyway
+    let count = if value == 0 && system.width.is_some() { 0 } else { count };
+    for _ in count..system.width.unwrap_or(0) {
+        hasher.write_digit(0);
+    }
+    for digit in digits[..count].iter().rev() {
+        hasher.write_digit(*digit);
+    }
+    hasher.finish()
+}
+
+
+/// Streaming `representation_fingerprint` of a sequence, for ones too long for `usize`.
+///
+/// Sequence is fingerprinted as given, it's the `representation_fingerprint`
+/// of a value when it's the sequence `format` renders for it.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{representation_fingerprint, NumeralSystem, RepresentationHasher};
+///
+/// let hex = NumeralSystem::new(&"0123456789ABCDEF".chars().collect::<Vec<_>>()).unwrap();
+/// let mut hasher = RepresentationHasher::new(&hex);
+/// hasher.write_str("F").unwrap();
+/// hasher.write_str("F").unwrap();
+/// assert_eq!(hasher.finish(), representation_fingerprint(255, &hex));
+///
+/// let mut long = RepresentationHasher::new(&hex);
+/// long.write_str(&"F".repeat(1000)).unwrap();
+/// assert_eq!(long.finish(), 0x8387_9587_c1b4_dd10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RepresentationHasher<'a> {
+    hash: Fnv64,
+    system: &'a NumeralSystem,
+    digits: u64,
+}
+
+impl<'a> RepresentationHasher<'a> {
+    /// Creates hasher of a sequence of `system`.
+    pub fn new(system: &'a NumeralSystem) -> RepresentationHasher<'a> {
+        RepresentationHasher { hash: Fnv64::of_system(system), system, digits: 0 }
+    }
+
+    fn write_digit(&mut self, digit: u32) {
+        self.hash.write(&digit.to_le_bytes());
+        self.digits += 1;
+    }
+
+    /// Appends glyphs of `chunk` to the sequence.
+    ///
+    /// # Errors
+    ///
+    /// * Returns `Error::MissingChar` when the alphabet missing a char or more, positions
+    ///   count from the beginning of the sequence (nothing of `chunk` is appended then)
+    pub fn write_str(&mut self, chunk: &str) -> Result<(), Error> {
+        let start = self.digits;
+        let mut hash = self.hash.clone();
+        for (position, glyph) in chunk.chars().enumerate() {
+            let position = start as usize + position;
+            let value = value_at(glyph, Some(position), &self.system.char2val, &self.system.alphabet)?;
+            hash.write(&(value as u32).to_le_bytes());
+        }
+        self.hash = hash;
+        self.digits += chunk.chars().count() as u64;
+        Ok(())
+    }
+
+    /// Returns fingerprint of the sequence written so far.
+    pub fn finish(&self) -> u64 {
+        let mut hash = self.hash.clone();
+        hash.write(&self.digits.to_le_bytes());
+        hash.0
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::{allocations, XorShift};
+    use DIGITS_UPPER_AZ;
+
+    #[test]
+    fn fingerprints_are_frozen() {
+        let hex_upper = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap();
+        let hex_lower = NumeralSystem::new(&"0123456789abcdef".chars().collect::<Vec<_>>()).unwrap();
+        let binary = NumeralSystem::new(&['0', '1']).unwrap();
+        let dec = NumeralSystem::new(&DIGITS_UPPER_AZ[..10]).unwrap();
+        let base36 = NumeralSystem::new(&DIGITS_UPPER_AZ).unwrap();
+        let known: [(usize, &NumeralSystem, Option<usize>, u64); 15] = [
+            (255, &hex_upper, None, 0x1567_97d9_a901_fc7b),
+            (255, &hex_lower, None, 0x2b3f_8dad_7173_fbfb),
+            (255, &hex_upper, Some(4), 0xe549_21c8_793c_bfa6),
+            (0, &hex_upper, None, 0x3ad5_8d38_0120_e6b8),
+            (0, &hex_upper, Some(4), 0x0a44_da13_8e31_2476),
+            (0, &binary, None, 0x5b5c_4997_ebe1_3433),
+            (10, &binary, None, 0x904c_c5ca_f704_c116),
+            (10, &binary, Some(8), 0xfdad_13e7_92a2_3805),
+            (usize::MAX, &binary, None, 0xa511_d7bc_66fe_bc32),
+            (usize::MAX, &hex_upper, None, 0x41f5_e2dd_d33a_c159),
+            (123_456_789, &dec, None, 0x2104_3a35_cfd2_7872),
+            (123_456_789, &dec, Some(12), 0x84e0_c431_8c5d_678c),
+            (35, &base36, Some(3), 0x2f52_4f1c_8f73_3b6d),
+            (10, &NumeralSystem::new(&['★', '☆']).unwrap(), None, 0x81c1_6fd3_a412_6420),
+            (5, &NumeralSystem::new(&['ż', '\u{1F005}', 'a']).unwrap(), None, 0xbb3b_fb30_c3ca_1a69),
+        ];
+        for &(value, system, width, fingerprint) in known.iter() {
+            let system = match width {
+                Some(width) => system.clone().with_width(width).unwrap(),
+                None => system.clone(),
+            };
+            assert_eq!(representation_fingerprint(value, &system), fingerprint, "{} {:?}", value, system);
+            // the domain isn't part of it
+            assert_eq!(representation_fingerprint(value, &system.clone().with_max_value(value)), fingerprint);
+            let mut hasher = RepresentationHasher::new(&system);
+            for glyph in system.format(value).unwrap().chars() {
+                hasher.write_str(&glyph.to_string()).unwrap();
+            }
+            assert_eq!(hasher.finish(), fingerprint);
+        }
+        let mut long = RepresentationHasher::new(&hex_upper);
+        long.write_str(&"F".repeat(1000)).unwrap();
+        assert_eq!(long.finish(), 0x8387_9587_c1b4_dd10);
+    }
+
+    #[test]
+    fn hasher_rejects_foreign_chars_atomically() {
+        let hex = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap();
+        let mut hasher = RepresentationHasher::new(&hex);
+        hasher.write_str("F").unwrap();
+        let msg = "Char 'g' at position 2 not found in: ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', \
+                   'A', 'B', 'C', 'D', 'E', 'F']".to_string();
+        assert_eq!(hasher.write_str("Fg"), Err(Error::MissingChar(msg)));
+        hasher.write_str("F").unwrap();
+        assert_eq!(hasher.finish(), representation_fingerprint(255, &hex));
+        assert_eq!(RepresentationHasher::new(&hex.clone().with_width(0).unwrap()).finish(),
+                   representation_fingerprint(0, &hex.with_width(0).unwrap()));
+    }
+
+    #[test]
+    fn ticket_domain_boundary() {
+        let tickets = NumeralSystem::new(&DIGITS_UPPER_AZ).unwrap().with_width(3).unwrap();
+        assert_eq!(tickets.max_value(), 46_655);
+        assert_eq!(tickets.format(46_655), Ok("ZZZ".to_string()));
+        assert_eq!(tickets.parse("ZZZ"), Ok(46_655));
+        assert_eq!(tickets.format(46_656), Err(Error::OutOfDomain { value: 46_656, max: 46_655 }));
+        assert!(tickets.parse("1000").is_err());
+        assert_eq!(tickets.parse("Z"), Ok(35));
+    }
+
+    #[test]
+    fn max_value_narrower_than_width() {
+        let system = NumeralSystem::new(&DIGITS_UPPER_AZ[..10]).unwrap().with_width(4).unwrap().with_max_value(1500);
+        assert_eq!(system.capacity(), Some(1501));
+        assert_eq!(system.format(1500), Ok("1500".to_string()));
+        assert!(system.format(1501).is_err());
+        assert_eq!(system.parse("1500"), Ok(1500));
+        assert_eq!(system.parse("1501"), Err(Error::OutOfDomain { value: 1501, max: 1500 }));
+        // max value wider than width is cut down to the width
+        let system = NumeralSystem::new(&DIGITS_UPPER_AZ[..10]).unwrap().with_max_value(50_000).with_width(2).unwrap();
+        assert_eq!(system.max_value(), 99);
+    }
+
+    #[test]
+    fn domain_respecting_output_is_fixed_width() {
+        let system = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap().with_width(4).unwrap();
+        for value in 0..=system.max_value() {
+            let seq = system.format(value).unwrap();
+            assert_eq!(seq.len(), 4);
+            assert_eq!(system.parse(&seq), Ok(value));
+        }
+        let unbounded = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap();
+        assert_eq!(unbounded.format(0), Ok("0".to_string()));
+        assert_eq!(unbounded.format(usize::MAX), Ok("F".repeat(16)));
+        assert_eq!(unbounded.capacity(), None);
+        let widest = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap().with_width(16).unwrap();
+        assert_eq!(widest.max_value(), usize::MAX);
+        assert_eq!(widest.format(255), Ok(format!("{}FF", "0".repeat(14))));
+        assert_eq!(widest.format(usize::MAX), Ok("F".repeat(16)));
+    }
+
+    #[test]
+    fn width_fits_usize_digits() {
+        for base in 2..37 {
+            let system = NumeralSystem::new(&DIGITS_UPPER_AZ[..base]).unwrap();
+            let widest = digit_count(usize::MAX, base).unwrap();
+            assert_eq!(system.clone().with_width(widest).map(|system| system.max_value()), Ok(usize::MAX));
+            let msg = format!("Width MUST be at most {} digits of base {}, given {}", widest, base, widest + 1);
+            assert_eq!(system.clone().with_width(widest + 1).unwrap_err(), Error::OutOfRange(msg));
+            // too wide to pad, or to hash the padding of
+            assert!(system.with_width(usize::MAX).is_err());
+        }
+    }
+
+    #[test]
+    fn try_display_agrees_with_format() {
+        let mut rng = XorShift::new(225);
+        for base in 2..37 {
+            let widest = digit_count(usize::MAX, base).unwrap();
+            for &width in [None, Some(0), Some(3), Some(widest)].iter() {
+                let mut system = NumeralSystem::new(&DIGITS_UPPER_AZ[..base]).unwrap();
+                if let Some(width) = width {
+                    system = system.with_width(width).unwrap();
+                }
+                for _ in 0..50 {
+                    let value = rng.below(system.max_value().saturating_add(1).max(1));
+                    let shown = try_display(value, &system).unwrap();
+                    assert_eq!(shown.to_string(), system.format(value).unwrap());
+                }
+                assert_eq!(try_display(0, &system).unwrap().to_string(), system.format(0).unwrap());
+            }
+        }
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn try_display_ufmt_agrees_with_display() {
+        use testutil::ufmt_string;
+
+        let stars = NumeralSystem::new(&['★', '☆', '✦']).unwrap();
+        let hex = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap().with_width(6).unwrap();
+        for system in [stars, hex].iter() {
+            for value in (0..500).chain(Some(system.max_value())) {
+                let shown = try_display(value, system).unwrap();
+                assert_eq!(ufmt_string(&shown), shown.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn small_values_display_without_allocations() {
+        let system = NumeralSystem::new(&['0', '1']).unwrap();
+        let mut out = String::with_capacity(1024);
+        let before = allocations();
+        let shown = try_display(usize::MAX, &system).unwrap();
+        write!(out, "{}", shown).unwrap();
+        assert_eq!(allocations(), before);
+        assert_eq!(out, "1".repeat(64));
+        // fails before any formatting
+        let narrow = NumeralSystem::new(&['0', '1']).unwrap().with_width(3).unwrap();
+        assert!(try_display(8, &narrow).is_err());
+    }
+
+    #[test]
+    fn portable_bytes_round_trip() {
+        let shuffled: Vec<char> = "QX7ZK2M9PA".chars().collect();
+        let systems = [
+            NumeralSystem::new(&shuffled).unwrap(),
+            NumeralSystem::new(&shuffled).unwrap().with_width(5).unwrap(),
+            NumeralSystem::new(&['★', '☆']).unwrap().with_max_value(1000),
+            NumeralSystem::new(&DIGITS_UPPER_AZ).unwrap().with_width(0).unwrap().with_max_value(0),
+        ];
+        for system in systems.iter() {
+            let loaded = NumeralSystem::from_portable_bytes(&system.to_portable_bytes()).unwrap();
+            assert_eq!(loaded.alphabet(), system.alphabet());
+            assert_eq!((loaded.width(), loaded.max_value()), (system.width(), system.max_value()));
+            assert_eq!(loaded.max_value, system.max_value);
+            assert_eq!(loaded.to_portable_bytes(), system.to_portable_bytes());
+        }
+    }
+
+    #[test]
+    fn portable_bytes_are_pinned() {
+        // format version 1, MUST NOT change
+        let golden: &[u8] = &[
+            b'N', b'S', b'Y', b'S', 1, 3, 3, 0, 0, 0, b'a', 0, 0, 0, 0xC5, 0, 0, 0, 0x05, 0xF0, 0x01, 0,
+            4, 0, 0, 0, 0, 0, 0, 0, 50, 0, 0, 0, 0, 0, 0, 0, 0x2B, 0xF9, 0xDD, 0x14,
+        ];
+        let system = NumeralSystem::new(&['a', 'Å', '🀅']).unwrap().with_width(4).unwrap().with_max_value(50);
+        assert_eq!(system.to_portable_bytes(), golden);
+        assert_eq!(NumeralSystem::from_portable_bytes(golden).unwrap().format(7), Ok("aa🀅Å".to_string()));
+    }
+
+    #[test]
+    fn corrupted_portable_bytes_are_rejected() {
+        let bytes = NumeralSystem::new(&DIGITS_UPPER_AZ[..16]).unwrap().with_width(4).unwrap().to_portable_bytes();
+        for position in 0..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[position] ^= 0x10;
+            assert!(NumeralSystem::from_portable_bytes(&corrupted).is_err(), "byte {}", position);
+        }
+        for length in 0..bytes.len() {
+            assert!(NumeralSystem::from_portable_bytes(&bytes[..length]).is_err());
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(NumeralSystem::from_portable_bytes(&trailing).is_err());
+        let mut unknown = bytes.clone();
+        unknown[4] = 2;
+        match NumeralSystem::from_portable_bytes(&unknown) {
+            Err(Error::UnsupportedVersion(_)) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
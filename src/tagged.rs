@@ -0,0 +1,247 @@
+use digits::missing_char;
+use parse::{accumulate, value_overflow};
+use scheme::luhn_check;
+use {dec2seq, Error, OverflowMode, DIGITS_UPPER_LOWER_AZ, MAX_RADIX};
+
+
+/// Chars of base tags of `encode_tagged`, none of them a digit of any base.
+///
+/// The format is frozen, so is this table: tag `t` is `TAG_CHARS[t / 16]`
+/// followed by `TAG_CHARS[t % 16]`.
+pub const TAG_CHARS: [char; 16] = ['!', '$', '%', '&', '*', '+', '.', ':', ';', '<', '=', '>', '?', '@', '^', '~'];
+
+
+/// Options of `encode_tagged`, by default no padding and no check digit.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::TagOptions;
+///
+/// let options = TagOptions { checksum: true, ..TagOptions::default() };
+/// assert_eq!(options.width, 0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagOptions {
+    /// Pads digits with leading zeros to at least `width` of them.
+    pub width: usize,
+    /// Appends a check digit, Luhn mod N with N being the base, covering padding too.
+    pub checksum: bool,
+}
+
+
+/// Value decoded by `decode_tagged` with what its tag told.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedValue {
+    /// The value.
+    pub value: usize,
+    /// Base named by the tag.
+    pub base: usize,
+    /// `None` without a check digit, otherwise whether it matches the digits.
+    pub checksum: Option<bool>,
+}
+
+
+/// Converts `value` to `base` digits prefixed with a tag naming the base, to be decoded without knowing it.
+///
+/// The format, frozen between releases: two tag chars of `TAG_CHARS`
+/// encoding `t = base * 2 + c` (`c` is 1 with a check digit), then the
+/// digits of `value`, most significant first, with glyphs `0-9`, `A-Z`,
+/// `a-z` up to the base, then the check digit when requested. Tag chars
+/// are never digits, so where the digits start is never ambiguous.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{encode_tagged, TagOptions};
+///
+/// assert_eq!(encode_tagged(255, 16, &TagOptions::default()), Ok("%!FF".to_string()));
+/// assert_eq!(encode_tagged(255, 2, &TagOptions::default()), Ok("!*11111111".to_string()));
+/// let options = TagOptions { width: 8, checksum: true };
+/// assert_eq!(encode_tagged(1234, 10, &options), Ok("$+000012344".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::BaseTooSmall` when `base` is less then 2
+///
+/// * Returns `Error::BaseTooBig` when `base` is greater then 62
+///
+/// ```
+/// use numsys::{encode_tagged, TagOptions};
+/// use numsys::Error;
+///
+/// let msg = "Base MUST be at most 62, given 63".to_string();
+/// assert_eq!(encode_tagged(1, 63, &TagOptions::default()), Err(Error::BaseTooBig(msg)));
+/// ```
+pub fn encode_tagged(value: usize, base: usize, options: &TagOptions) -> Result<String, Error> {
+    if base < 2 {
+        return Err(Error::BaseTooSmall(format!("Base MUST be 2 or higer, given {}", base)));
+    }
+    if base > MAX_RADIX as usize {
+        return Err(Error::BaseTooBig(format!("Base MUST be at most {}, given {}", MAX_RADIX, base)));
+    }
+    let alphabet = &DIGITS_UPPER_LOWER_AZ[..base];
+    let tag = base * 2 + options.checksum as usize;
+    let mut tagged: String = [TAG_CHARS[tag / 16], TAG_CHARS[tag % 16]].iter().collect();
+    let digits = dec2seq(value, alphabet)?;
+    let count = digits.chars().count();
+    for _ in count..options.width.max(1) {
+        tagged.push(alphabet[0]);
+    }
+    tagged.push_str(&digits);
+    if options.checksum {
+        let values: Vec<usize> = tagged[2..]
+            .chars()
+            .map(|glyph| alphabet.iter().position(|g| *g == glyph).expect("Digit of the base"))
+            .collect();
+        tagged.push(alphabet[luhn_check(&values, base)]);
+    }
+    Ok(tagged)
+}
+
+
+/// Converts `tagged` made by `encode_tagged` back to the value, reading the base from the tag.
+///
+/// A mismatching check digit isn't an error, `TaggedValue::checksum` tells it.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{decode_tagged, TaggedValue};
+///
+/// assert_eq!(decode_tagged("%!FF"), Ok(TaggedValue { value: 255, base: 16, checksum: None }));
+/// assert_eq!(decode_tagged("$+000012344"), Ok(TaggedValue { value: 1234, base: 10, checksum: Some(true) }));
+/// assert_eq!(decode_tagged("$+000012345").unwrap().checksum, Some(false));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::InvalidMarker` when `tagged` doesn't start with a tag of a base from 2 to 62
+///
+/// ```
+/// use numsys::decode_tagged;
+/// use numsys::Error;
+///
+/// let msg = "Tag \"!$\" MUST name a base from 2 to 62, given 0".to_string();
+/// assert_eq!(decode_tagged("!$1"), Err(Error::InvalidMarker(msg)));
+/// ```
+///
+/// * Returns `Error::SequenceEmpty` when there are no digits after the tag (and the check digit)
+///
+/// * Returns `Error::MissingChar` when a char after the tag isn't a digit of the base
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn decode_tagged(tagged: &str) -> Result<TaggedValue, Error> {
+    let mut glyphs = tagged.chars();
+    let tag_digit = |glyph: Option<char>| glyph.and_then(|g| TAG_CHARS.iter().position(|t| *t == g));
+    let tag = match (tag_digit(glyphs.next()), tag_digit(glyphs.next())) {
+        (Some(high), Some(low)) => high * 16 + low,
+        _ => {
+            let prefix: String = tagged.chars().take(2).collect();
+            return Err(Error::InvalidMarker(format!("Tag {:?} MUST be 2 chars of: {:?}", prefix, TAG_CHARS)));
+        }
+    };
+    let (base, checked) = (tag / 2, tag % 2 == 1);
+    if base < 2 || base > MAX_RADIX as usize {
+        return Err(Error::InvalidMarker(
+            format!("Tag {:?} MUST name a base from 2 to {}, given {}", &tagged[..2], MAX_RADIX, base),
+        ));
+    }
+    let alphabet = &DIGITS_UPPER_LOWER_AZ[..base];
+    let values = glyphs
+        .enumerate()
+        .map(|(position, glyph)| {
+            alphabet.iter().position(|g| *g == glyph).ok_or_else(|| missing_char(glyph, Some(position + 2), alphabet))
+        })
+        .collect::<Result<Vec<usize>, Error>>()?;
+    let count = if checked { values.len().saturating_sub(1) } else { values.len() };
+    if count == 0 {
+        return Err(Error::SequenceEmpty);
+    }
+    let value = values[..count]
+        .iter()
+        .try_fold(0_usize, |value, digit| accumulate(value, base, *digit, OverflowMode::Error, || value_overflow(tagged)))?;
+    let checksum = if checked { Some(luhn_check(&values[..count], base) == values[count]) } else { None };
+    Ok(TaggedValue { value, base, checksum })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    #[test]
+    fn golden_strings() {
+        let plain = TagOptions::default();
+        let checked = TagOptions { checksum: true, ..TagOptions::default() };
+        let padded = TagOptions { width: 6, checksum: true };
+        let golden = [
+            (0, 2, &plain, "!*0"),
+            (0, 10, &checked, "$+00"),
+            (255, 16, &plain, "%!FF"),
+            (255, 16, &checked, "%$FF2"),
+            (255, 36, &plain, "*;73"),
+            (61, 62, &plain, ":?z"),
+            (61, 62, &checked, ":@z1"),
+            (1234, 10, &padded, "$+0012344"),
+            (usize::MAX, 62, &plain, ":?LygHa16AHYF"),
+            (usize::MAX, 2, &checked, "!+11111111111111111111111111111111111111111111111111111111111111110"),
+        ];
+        for &(value, base, options, tagged) in golden.iter() {
+            assert_eq!(encode_tagged(value, base, options), Ok(tagged.to_string()));
+            let checksum = if options.checksum { Some(true) } else { None };
+            assert_eq!(decode_tagged(tagged), Ok(TaggedValue { value, base, checksum }));
+        }
+        // table of the tags is frozen too
+        let tags: String = TAG_CHARS.iter().collect();
+        assert_eq!(tags, "!$%&*+.:;<=>?@^~");
+    }
+
+    #[test]
+    fn tags_never_look_like_digits() {
+        for tag in TAG_CHARS.iter() {
+            assert!(!DIGITS_UPPER_LOWER_AZ.contains(tag));
+            assert!(tag.is_ascii_punctuation());
+        }
+        let mut rng = XorShift::new(246);
+        for base in 2..63 {
+            for checksum in [false, true].iter() {
+                for _ in 0..20 {
+                    let value = rng.next_u64() as usize >> rng.below(64);
+                    let options = TagOptions { width: rng.below(5), checksum: *checksum };
+                    let tagged = encode_tagged(value, base, &options).unwrap();
+                    let decoded = decode_tagged(&tagged).unwrap();
+                    assert_eq!((decoded.value, decoded.base), (value, base));
+                    assert_eq!(decoded.checksum, if *checksum { Some(true) } else { None });
+                    // tags are only the first two chars
+                    assert!(tagged.chars().skip(2).all(|glyph| !TAG_CHARS.contains(&glyph)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_tagged_strings() {
+        for tagged in ["", "%", "0FF", "%0FF", "F%!"].iter() {
+            match decode_tagged(tagged) {
+                Err(Error::InvalidMarker(_)) => {}
+                other => panic!("{:?} gave {:?}", tagged, other),
+            }
+        }
+        // bases 0, 1 and above 62 are never tagged
+        assert!(decode_tagged("!!0").is_err());
+        assert!(decode_tagged("!%0").is_err());
+        assert!(decode_tagged("~~0").is_err());
+        assert_eq!(decode_tagged("%!"), Err(Error::SequenceEmpty));
+        assert_eq!(decode_tagged("%$F"), Err(Error::SequenceEmpty));
+        let msg = "Char 'G' at position 3 not found in: ".to_string();
+        match decode_tagged("%!FG") {
+            Err(Error::MissingChar(ref m)) if m.starts_with(&msg) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(decode_tagged("%!10000000000000000").is_err());
+        assert!(encode_tagged(1, 1, &TagOptions::default()).is_err());
+    }
+}
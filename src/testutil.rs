@@ -0,0 +1,89 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+
+/// Deterministic xorshift generator, good enough for test samples.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        XorShift(seed | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns value from `0` to `bound` (excluded).
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// System allocator counting allocations of each thread, for allocation-free checks.
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Returns count of allocations made by the current thread so far.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+
+thread_local! {
+    static INSPECTED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts one char of an input read by the current thread, see `inspected`.
+pub fn count_inspected() {
+    INSPECTED.with(|count| count.set(count.get() + 1));
+}
+
+/// Returns count of input chars read by the current thread so far, for checks of work bounds.
+pub fn inspected() -> usize {
+    INSPECTED.with(|count| count.get())
+}
+
+
+/// Buffer of `ufmt` output, to compare it with `Display`.
+#[cfg(feature = "ufmt")]
+pub struct UfmtBuffer(pub String);
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uWrite for UfmtBuffer {
+    type Error = ();
+
+    fn write_str(&mut self, s: &str) -> Result<(), ()> {
+        self.0.push_str(s);
+        Ok(())
+    }
+}
+
+/// Returns `value` formatted by `ufmt`.
+#[cfg(feature = "ufmt")]
+pub fn ufmt_string<T: ufmt::uDisplay>(value: &T) -> String {
+    let mut buffer = UfmtBuffer(String::new());
+    ufmt::uwrite!(buffer, "{}", value).expect("Writing to a String");
+    buffer.0
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::str::Chars;
+
+use digits::value_at;
+use parse::accumulate;
+use {positional_alphabet, Error, OverflowMode};
+
+
+/// Iterator over values of fixed-width tokens, created by `decode_tokens_iter`.
+///
+/// Yields one value per token, stops after the first error.
+#[derive(Debug, Clone)]
+pub struct DecodeTokens<'a> {
+    glyphs: Chars<'a>,
+    char2val: &'a [char],
+    map: HashMap<char, usize>,
+    token_width: usize,
+    token: usize,
+    position: usize,
+    failed: bool,
+}
+
+impl<'a> DecodeTokens<'a> {
+    fn decode_next(&mut self) -> Result<usize, Error> {
+        let base = self.char2val.len();
+        let mut value: usize = 0;
+        for _ in 0..self.token_width {
+            let glyph = self.glyphs.next().expect("length checked upfront");
+            let digit = value_at(glyph, Some(self.position), &self.map, self.char2val)?;
+            let token = self.token;
+            value = accumulate(value, base, digit, OverflowMode::Error, || {
+                Error::Overflow(format!("Value of token {} exceeds usize", token))
+            })?;
+            self.position += 1;
+        }
+        self.token += 1;
+        Ok(value)
+    }
+}
+
+impl<'a> Iterator for DecodeTokens<'a> {
+    type Item = Result<usize, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.glyphs.as_str().is_empty() {
+            return None;
+        }
+        let result = self.decode_next();
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+
+/// Splits `input` into tokens of `token_width` chars and decodes them lazily.
+///
+/// Nothing is decoded upfront, only the alphabet and the length of `input`
+/// are checked, so big inputs can be streamed.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_tokens_iter;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// let mut tokens = decode_tokens_iter("00FF0100", &hex, 4).unwrap();
+/// assert_eq!(tokens.next(), Some(Ok(255)));
+/// assert_eq!(tokens.next(), Some(Ok(256)));
+/// assert_eq!(tokens.next(), None);
+/// ```
+///
+/// # Errors
+///
+/// Same as `decode_tokens`, but the ones about token contents are yielded
+/// by the iterator.
+pub fn decode_tokens_iter<'a>(input: &'a str, char2val: &'a [char], token_width: usize) -> Result<DecodeTokens<'a>, Error> {
+    let map = positional_alphabet(char2val)?;
+    if token_width == 0 {
+        return Err(Error::OutOfRange("Token width MUST be at least 1, given 0".into()));
+    }
+    let length = input.chars().count();
+    if length % token_width != 0 {
+        return Err(Error::LengthMismatch(format!(
+            "Input length MUST be a multiple of {}, given {}",
+            token_width,
+            length
+        )));
+    }
+    Ok(DecodeTokens {
+        glyphs: input.chars(),
+        char2val,
+        map,
+        token_width,
+        token: 0,
+        position: 0,
+        failed: false,
+    })
+}
+
+
+/// Splits `input` into tokens of `token_width` chars and decodes each of them.
+///
+/// Tokens are split on chars, not bytes, so multi-byte alphabets work.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::decode_tokens;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(decode_tokens("A3F9B200", &hex, 4), Ok(vec![0xA3F9, 0xB200]));
+/// assert_eq!(decode_tokens("", &hex, 4), Ok(vec![]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::OutOfRange` when `token_width` is 0
+///
+/// * Returns `Error::LengthMismatch` when `input` char count isn't a multiple of `token_width`
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char, position is counted in `input`
+///
+/// ```
+/// use numsys::decode_tokens;
+/// use numsys::Error;
+///
+/// let msg = "Char \'2\' at position 4 not found in: [\'0\', \'1\']".to_string();
+/// assert_eq!(decode_tokens("010120", &['0', '1'], 2), Err(Error::MissingChar(msg)));
+/// ```
+///
+/// * Returns `Error::Overflow` when value of a token exceeds `usize`
+pub fn decode_tokens(input: &str, char2val: &[char], token_width: usize) -> Result<Vec<usize>, Error> {
+    decode_tokens_iter(input, char2val, token_width)?.collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use {dec2seq, DIGITS};
+
+    #[test]
+    fn multi_byte_tokens_split_on_chars() {
+        let alphabet = ['★', '☆', 'ż', 'a'];
+        let tokens = ["★★☆", "aaa", "żż★"];
+        assert_eq!(decode_tokens(&tokens.concat(), &alphabet, 3), Ok(vec![1, 63, 40]));
+        let msg = "Input length MUST be a multiple of 3, given 8".to_string();
+        assert_eq!(decode_tokens("★★☆aaaż★", &alphabet, 3), Err(Error::LengthMismatch(msg)));
+    }
+
+    #[test]
+    fn failure_in_middle_token() {
+        let input = "0012x0450099";
+        let msg = format!("Char 'x' at position 4 not found in: {:?}", &DIGITS[..]);
+        assert_eq!(decode_tokens(input, &DIGITS, 4), Err(Error::MissingChar(msg.clone())));
+        let mut tokens = decode_tokens_iter(input, &DIGITS, 4).unwrap();
+        assert_eq!(tokens.next(), Some(Ok(12)));
+        assert_eq!(tokens.next(), Some(Err(Error::MissingChar(msg))));
+        assert_eq!(tokens.next(), None);
+        let wide = format!("{}{}", "0".repeat(20), "9".repeat(20));
+        let msg = "Value of token 1 exceeds usize".to_string();
+        assert_eq!(decode_tokens(&wide, &DIGITS, 20), Err(Error::Overflow(msg)));
+    }
+
+    #[test]
+    fn random_tokens_round_trip() {
+        let mut rng = XorShift::new(2172);
+        let values: Vec<usize> = (0..1000).map(|_| rng.below(10_000)).collect();
+        let input: String = values
+            .iter()
+            .map(|value| format!("{:0>4}", dec2seq(*value, &DIGITS).unwrap()))
+            .collect();
+        assert_eq!(decode_tokens(&input, &DIGITS, 4), Ok(values.clone()));
+        let streamed: Vec<usize> = decode_tokens_iter(&input, &DIGITS, 4).unwrap().map(Result::unwrap).collect();
+        assert_eq!(streamed, values);
+        assert!(decode_tokens(&input, &DIGITS, 0).is_err());
+    }
+}
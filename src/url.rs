@@ -0,0 +1,76 @@
+use {checked_seq2dec, dec2seq, Error, URL_SAFE};
+
+
+/// Converts `decimal` to base 66 of `URL_SAFE` chars, which never need percent-encoding.
+///
+/// `URL_SAFE` is in ASCII order, so sequences of equal length sort like
+/// their values.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_url_safe;
+///
+/// assert_eq!(to_url_safe(0), "-");
+/// assert_eq!(to_url_safe(65), "~");
+/// assert_eq!(to_url_safe(66), ".-");
+/// ```
+pub fn to_url_safe(decimal: usize) -> String {
+    if decimal == 0 {
+        return URL_SAFE[0].to_string();
+    }
+    dec2seq(decimal, &URL_SAFE).expect("URL_SAFE is a valid alphabet")
+}
+
+
+/// Converts `sequence` of `URL_SAFE` chars back to decimal, the inverse of `to_url_safe`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_url_safe;
+///
+/// assert_eq!(from_url_safe(".-"), Ok(66));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::MissingChar` when `sequence` includes a char other than `URL_SAFE` ones
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn from_url_safe(sequence: &str) -> Result<usize, Error> {
+    checked_seq2dec(sequence, &URL_SAFE)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+
+    fn is_unreserved(glyph: char) -> bool {
+        glyph.is_ascii_alphanumeric() || "-._~".contains(glyph)
+    }
+
+    #[test]
+    fn output_is_unreserved_only() {
+        assert_eq!(URL_SAFE.len(), 66);
+        assert!(URL_SAFE.iter().all(|glyph| is_unreserved(*glyph)));
+        let mut rng = XorShift::new(224);
+        let values = (0..10_000).chain((0..10_000).map(|_| rng.next_u64() as usize)).chain(vec![usize::MAX]);
+        for value in values {
+            let seq = to_url_safe(value);
+            assert!(seq.chars().all(is_unreserved), "{}", seq);
+            assert_eq!(from_url_safe(&seq), Ok(value));
+        }
+        assert!(from_url_safe("a%20").is_err());
+    }
+
+    #[test]
+    fn equal_lengths_sort_like_values() {
+        let seqs: Vec<String> = (66..66 * 66).map(to_url_safe).collect();
+        let mut sorted = seqs.clone();
+        sorted.sort();
+        assert_eq!(seqs, sorted);
+    }
+}
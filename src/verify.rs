@@ -0,0 +1,333 @@
+use std::mem;
+
+use std::iter;
+
+use {checked_seq2dec, dec2digits, dec2seq, dec2seq_with, decode_tokens, digits2seq, encode_in, from_str_radix,
+     seq2dec_offsets, seq2dec_with, seq2dec_with_options, switch_dec_base, to_str_radix, try_display, write_all,
+     EncodeArena, Error, InterningEncoder, LazyBased, LiveValue, NumeralSystem, ParseOptions, DIGITS_UPPER_AZ,
+     DIGITS_UPPER_LOWER_AZ};
+
+
+/// Encoding implementation checked by `verify_all_paths`.
+pub struct EncodePath {
+    /// Name of the path, as shown in `PathReport`
+    pub name: &'static str,
+    /// Tells whether the path supports the alphabet (e.g. fixed alphabet paths)
+    pub applies: fn(&[char]) -> bool,
+    pub encode: fn(usize, &[char]) -> Result<String, Error>,
+}
+
+
+/// Decoding implementation checked by `verify_all_decode_paths`.
+pub struct DecodePath {
+    /// Name of the path, as shown in `PathReport`
+    pub name: &'static str,
+    /// Tells whether the path supports the sequence and alphabet
+    pub applies: fn(&str, &[char]) -> bool,
+    pub decode: fn(&str, &[char]) -> Result<usize, Error>,
+}
+
+
+fn any_alphabet(_: &[char]) -> bool {
+    true
+}
+
+fn is_upper_az(alphabet: &[char]) -> bool {
+    alphabet.len() <= DIGITS_UPPER_AZ.len() && alphabet == &DIGITS_UPPER_AZ[..alphabet.len()]
+}
+
+fn is_radix(alphabet: &[char]) -> bool {
+    is_upper_az(alphabet)
+        || alphabet.len() <= DIGITS_UPPER_LOWER_AZ.len() && alphabet == &DIGITS_UPPER_LOWER_AZ[..alphabet.len()]
+}
+
+/// Sequence of `dec2seq` paths, where zero is an empty sequence.
+fn zero_as_digit(sequence: &str, alphabet: &[char]) -> String {
+    if sequence.is_empty() { alphabet[0].to_string() } else { sequence.to_string() }
+}
+
+
+/// Registered encoding paths, the first one is the reference for the others.
+pub static ENCODE_PATHS: &[EncodePath] = &[
+    EncodePath {
+        name: "NumeralSystem::format",
+        applies: any_alphabet,
+        encode: |value, alphabet| NumeralSystem::new(alphabet)?.format(value),
+    },
+    EncodePath {
+        name: "dec2seq",
+        applies: any_alphabet,
+        encode: |value, alphabet| dec2seq(value, alphabet).map(|seq| zero_as_digit(&seq, alphabet)),
+    },
+    EncodePath {
+        name: "digits2seq",
+        applies: any_alphabet,
+        encode: |value, alphabet| digits2seq(&dec2digits(value, alphabet.len())?, alphabet),
+    },
+    EncodePath {
+        name: "dec2seq_with",
+        applies: any_alphabet,
+        encode: |value, alphabet| dec2seq_with(value, alphabet.len(), |digit| alphabet[digit]),
+    },
+    EncodePath {
+        name: "try_display",
+        applies: any_alphabet,
+        encode: |value, alphabet| Ok(try_display(value, &NumeralSystem::new(alphabet)?)?.to_string()),
+    },
+    EncodePath {
+        name: "switch_dec_base",
+        applies: is_upper_az,
+        encode: |value, alphabet| switch_dec_base(value, alphabet.len()),
+    },
+    EncodePath {
+        name: "to_str_radix",
+        applies: is_radix,
+        encode: |value, alphabet| to_str_radix(value, alphabet.len() as u32),
+    },
+    EncodePath {
+        name: "LazyBased",
+        applies: is_upper_az,
+        encode: |value, alphabet| Ok(LazyBased { value, base: alphabet.len() }.to_string()),
+    },
+    EncodePath {
+        name: "encode_in",
+        applies: any_alphabet,
+        encode: |value, alphabet| {
+            let mut arena = EncodeArena::new();
+            encode_in(&mut arena, value, alphabet).map(|seq| zero_as_digit(seq, alphabet))
+        },
+    },
+    EncodePath {
+        name: "InterningEncoder",
+        applies: any_alphabet,
+        encode: |value, alphabet| {
+            let encoder = InterningEncoder::new(alphabet, 1)?;
+            // the second one is a hit, the interned sequence
+            encoder.encode(value);
+            Ok(zero_as_digit(&encoder.encode(value), alphabet))
+        },
+    },
+    EncodePath {
+        name: "write_all",
+        applies: is_upper_az,
+        encode: |value, alphabet| {
+            let mut out = String::new();
+            write_all(iter::once(value), alphabet.len(), ',', &mut out)?;
+            Ok(out)
+        },
+    },
+    EncodePath {
+        name: "LiveValue",
+        applies: is_upper_az,
+        // from a neighbour of the same length, so only the differing digits are rewritten
+        encode: |value, alphabet| Ok(LiveValue::new(value ^ 1, alphabet.len())?.set(value).to_string()),
+    },
+];
+
+
+/// Registered decoding paths, the first one is the reference for the others.
+pub static DECODE_PATHS: &[DecodePath] = &[
+    DecodePath {
+        name: "NumeralSystem::parse",
+        applies: |_, _| true,
+        decode: |sequence, alphabet| NumeralSystem::new(alphabet)?.parse(sequence),
+    },
+    DecodePath {
+        name: "checked_seq2dec",
+        applies: |_, _| true,
+        decode: checked_seq2dec,
+    },
+    DecodePath {
+        name: "seq2dec_with_options",
+        applies: |_, _| true,
+        decode: |sequence, alphabet| seq2dec_with_options(sequence, alphabet, &ParseOptions::default()),
+    },
+    DecodePath {
+        name: "seq2dec_with",
+        applies: |_, _| true,
+        decode: |sequence, alphabet| {
+            seq2dec_with(sequence, alphabet.len(), |glyph| alphabet.iter().position(|c| *c == glyph))
+        },
+    },
+    DecodePath {
+        name: "from_str_radix",
+        // std semantics differ: no empty sequence, a leading `+` and case insensitive up to radix 36
+        applies: |sequence, alphabet| {
+            is_radix(alphabet) && !sequence.is_empty() && !sequence.starts_with('+')
+                && (alphabet.len() > 36 || !sequence.chars().any(|glyph| glyph.is_ascii_lowercase()))
+        },
+        decode: |sequence, alphabet| from_str_radix(sequence, alphabet.len() as u32),
+    },
+    DecodePath {
+        name: "seq2dec_offsets",
+        // an empty row is 0 there
+        applies: |sequence, _| !sequence.is_empty(),
+        decode: |sequence, alphabet| {
+            let mut out = Vec::new();
+            let report = seq2dec_offsets(sequence.as_bytes(), &[0, sequence.len()], alphabet, &mut out)?;
+            match report.errors.into_iter().next() {
+                Some(failed) => Err(failed.error),
+                None => Ok(out[0] as usize),
+            }
+        },
+    },
+    DecodePath {
+        name: "decode_tokens",
+        // the whole sequence is one token, a width of 0 is rejected
+        applies: |sequence, _| !sequence.is_empty(),
+        decode: |sequence, alphabet| Ok(decode_tokens(sequence, alphabet, sequence.chars().count())?[0]),
+    },
+];
+
+
+/// Outcome of every applicable conversion path for one input.
+#[derive(Debug)]
+pub struct PathReport<T> {
+    outcomes: Vec<(&'static str, Result<T, Error>)>,
+}
+
+impl<T: PartialEq> PathReport<T> {
+    /// Returns outcome of each path run, in order of registration.
+    pub fn outcomes(&self) -> &[(&'static str, Result<T, Error>)] {
+        &self.outcomes
+    }
+
+    /// Returns names of paths disagreeing with the reference (first) path.
+    ///
+    /// Paths agree when they give the same value, or fail with the same
+    /// `Error` variant (messages may differ).
+    pub fn divergences(&self) -> Vec<&'static str> {
+        let reference = &self.outcomes[0].1;
+        self.outcomes[1..]
+            .iter()
+            .filter(|(_, outcome)| match (reference, outcome) {
+                (Ok(expected), Ok(given)) => expected != given,
+                (Err(expected), Err(given)) => mem::discriminant(expected) != mem::discriminant(given),
+                _ => true,
+            })
+            .map(|&(name, _)| name)
+            .collect()
+    }
+
+    /// Tells whether all paths agree.
+    pub fn is_consistent(&self) -> bool {
+        self.divergences().is_empty()
+    }
+}
+
+
+/// Encodes `value` with every path of `ENCODE_PATHS` supporting `alphabet`.
+///
+/// Available with the `verify` feature (and in the crate's own tests).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "verify")] {
+/// use numsys::{verify_all_paths, DIGITS_UPPER_LOWER_AZ};
+///
+/// let report = verify_all_paths(255, &DIGITS_UPPER_LOWER_AZ[..16]);
+/// assert!(report.is_consistent(), "{:?}", report);
+/// assert_eq!(report.outcomes().len(), 12);
+/// # }
+/// ```
+pub fn verify_all_paths(value: usize, alphabet: &[char]) -> PathReport<String> {
+    let outcomes = ENCODE_PATHS
+        .iter()
+        .filter(|path| (path.applies)(alphabet))
+        .map(|path| (path.name, (path.encode)(value, alphabet)))
+        .collect();
+    PathReport { outcomes }
+}
+
+
+/// Decodes `sequence` with every path of `DECODE_PATHS` supporting it and `alphabet`.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "verify")] {
+/// use numsys::{verify_all_decode_paths, DIGITS_UPPER_LOWER_AZ};
+///
+/// let report = verify_all_decode_paths("FF", &DIGITS_UPPER_LOWER_AZ[..16]);
+/// assert!(report.is_consistent(), "{:?}", report);
+/// assert_eq!(report.outcomes()[0], ("NumeralSystem::parse", Ok(255)));
+/// # }
+/// ```
+pub fn verify_all_decode_paths(sequence: &str, alphabet: &[char]) -> PathReport<usize> {
+    let outcomes = DECODE_PATHS
+        .iter()
+        .filter(|path| (path.applies)(sequence, alphabet))
+        .map(|path| (path.name, (path.decode)(sequence, alphabet)))
+        .collect();
+    PathReport { outcomes }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digit_count;
+    use testutil::XorShift;
+
+    fn assert_consistent<T: PartialEq + ::std::fmt::Debug>(report: &PathReport<T>) {
+        assert!(report.is_consistent(), "{:?} diverge in {:?}", report.divergences(), report);
+    }
+
+    #[test]
+    fn random_values_every_base() {
+        let mut rng = XorShift::new(2262);
+        for base in 2..63 {
+            let alphabet = &DIGITS_UPPER_LOWER_AZ[..base];
+            for _ in 0..200 {
+                let value = rng.next_u64() as usize >> rng.below(64);
+                let report = verify_all_paths(value, alphabet);
+                assert_consistent(&report);
+                let sequence = report.outcomes()[0].1.as_ref().unwrap().clone();
+                assert_consistent(&verify_all_decode_paths(&sequence, alphabet));
+            }
+            for &value in [0, 1, base - 1, base, usize::MAX].iter() {
+                assert_consistent(&verify_all_paths(value, alphabet));
+            }
+        }
+    }
+
+    #[test]
+    fn adversarial_sequences_every_base() {
+        for base in 2..63 {
+            let alphabet = &DIGITS_UPPER_LOWER_AZ[..base];
+            let top = alphabet[base - 1];
+            let max_width = digit_count(usize::MAX, base).unwrap();
+            let widest: String = iter::repeat(top).take(max_width).collect();
+            let overflowing: String = iter::repeat(top).take(max_width + 1).collect();
+            let sequences = [
+                String::new(),
+                widest,
+                overflowing,
+                format!("1{}", "0".repeat(max_width)),
+                "0".repeat(1000),
+                "1!".to_string(),
+                "+1".to_string(),
+                " 1".to_string(),
+                "Ⅻ".to_string(),
+            ];
+            for sequence in sequences.iter() {
+                assert_consistent(&verify_all_decode_paths(sequence, alphabet));
+            }
+        }
+        // the widest sequence is usize::MAX in every base
+        let report = verify_all_decode_paths(&"1".repeat(64), &['0', '1']);
+        assert_eq!(report.outcomes()[0].1, Ok(usize::MAX));
+    }
+
+    #[test]
+    fn divergence_is_reported() {
+        // `dec2seq` (and `dec2seq_with`, with no alphabet at all) don't check for duplicates
+        let report = verify_all_paths(5, &['A', 'A']);
+        assert_eq!(report.divergences(), vec!["dec2seq", "dec2seq_with"]);
+        // custom alphabets skip the fixed alphabet paths
+        let report = verify_all_paths(5, &['a', 'b', 'c']);
+        assert_eq!(report.outcomes().len(), 7);
+        assert_consistent(&report);
+    }
+}
@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use digits::value_at;
+use parse::{accumulate, echo};
+use {dec2seq, positional_alphabet, Error, OverflowMode};
+
+
+/// How `cmp_fields` treats fields missing in the shorter version.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingFields {
+    /// Missing fields are 0, so `1.2` equals `1.2.0`
+    Zero,
+    /// A version is less than its own extensions, so `1.2` is less than `1.2.0`
+    Less,
+}
+
+
+/// Checks `char2val` is a positional alphabet without `separator`, returning its map.
+fn check_separator(separator: char, char2val: &[char]) -> Result<HashMap<char, usize>, Error> {
+    let map = positional_alphabet(char2val)?;
+    if char2val.contains(&separator) {
+        return Err(Error::InvalidMarker(format!("Separator {:?} MUST NOT be in: {:?}", separator, char2val)));
+    }
+    Ok(map)
+}
+
+
+/// Converts version-like `s` to its fields, sequences using `char2val` translation separated by `separator`.
+///
+/// Fields are separate values, so `1.10` is `[1, 10]` and compares
+/// greater than `1.9` with `cmp_fields`, unlike the strings. Fields may
+/// have leading zeros, like dates do (`2024.06.30`).
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{parse_fields, DIGITS};
+///
+/// assert_eq!(parse_fields("1.12.3", '.', &DIGITS), Ok(vec![1, 12, 3]));
+/// assert_eq!(parse_fields("2024.06.30", '.', &DIGITS), Ok(vec![2024, 6, 30]));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::InvalidMarker` when `separator` is in `char2val`
+///
+/// * Returns `Error::InvalidLayout` when a field is empty
+///
+/// ```
+/// use numsys::{parse_fields, DIGITS};
+/// use numsys::Error;
+///
+/// let msg = "Field 1 at position 2 MUST NOT be empty".to_string();
+/// assert_eq!(parse_fields("1..3", '.', &DIGITS), Err(Error::InvalidLayout(msg)));
+/// ```
+///
+/// * Returns `Error::MissingChar` when a field includes a char out of `char2val`
+///
+/// * Returns `Error::Overflow` when a field exceeds `usize`
+///
+/// ```
+/// use numsys::{parse_fields, DIGITS};
+/// use numsys::Error;
+///
+/// let msg = "Field 1 at position 2 of \"1.99999999999999999999\" exceeds usize".to_string();
+/// assert_eq!(parse_fields("1.99999999999999999999", '.', &DIGITS), Err(Error::Overflow(msg)));
+/// ```
+pub fn parse_fields(s: &str, separator: char, char2val: &[char]) -> Result<Vec<usize>, Error> {
+    let map = check_separator(separator, char2val)?;
+    let base = char2val.len();
+    let mut fields = Vec::new();
+    // position of the current field's first char
+    let mut start = 0;
+    let mut field: Option<usize> = None;
+    for (position, glyph) in s.chars().chain(Some(separator)).enumerate() {
+        if glyph == separator {
+            let value = field.ok_or_else(|| {
+                Error::InvalidLayout(format!("Field {} at position {} MUST NOT be empty", fields.len(), start))
+            })?;
+            fields.push(value);
+            start = position + 1;
+            field = None;
+            continue;
+        }
+        let digit = value_at(glyph, Some(position), &map, char2val)?;
+        let value = accumulate(field.unwrap_or(0), base, digit, OverflowMode::Error, || {
+            Error::Overflow(format!("Field {} at position {} of {} exceeds usize", fields.len(), start, echo(s)))
+        })?;
+        field = Some(value);
+    }
+    Ok(fields)
+}
+
+
+/// Converts `fields` to a version-like string, the inverse of `parse_fields`.
+///
+/// Fields get no leading zeros, so `2024.06.30` comes back as `2024.6.30`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::{format_fields, DIGITS};
+///
+/// assert_eq!(format_fields(&[1, 12, 3], '.', &DIGITS), Ok("1.12.3".to_string()));
+/// assert_eq!(format_fields(&[2024, 6, 30], '-', &DIGITS), Ok("2024-6-30".to_string()));
+/// assert_eq!(format_fields(&[0, 1], '.', &DIGITS), Ok("0.1".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns errors of `parse_fields` for `separator` and `char2val`
+pub fn format_fields(fields: &[usize], separator: char, char2val: &[char]) -> Result<String, Error> {
+    check_separator(separator, char2val)?;
+    let mut s = String::new();
+    for (index, field) in fields.iter().enumerate() {
+        if index != 0 {
+            s.push(separator);
+        }
+        if *field == 0 {
+            s.push(char2val[0]);
+        } else {
+            s.push_str(&dec2seq(*field, char2val)?);
+        }
+    }
+    Ok(s)
+}
+
+
+/// Compares versions `a` and `b` field by field, in numeric order.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use numsys::{cmp_fields, MissingFields};
+///
+/// assert_eq!(cmp_fields(&[1, 10], &[1, 9], MissingFields::Zero), Ordering::Greater);
+/// assert_eq!(cmp_fields(&[1, 2], &[1, 2, 0], MissingFields::Zero), Ordering::Equal);
+/// assert_eq!(cmp_fields(&[1, 2], &[1, 2, 0], MissingFields::Less), Ordering::Less);
+/// ```
+pub fn cmp_fields(a: &[usize], b: &[usize], missing: MissingFields) -> Ordering {
+    if missing == MissingFields::Less {
+        return a.cmp(b);
+    }
+    let count = a.len().max(b.len());
+    let padded = |fields: &[usize], index: usize| fields.get(index).cloned().unwrap_or(0);
+    (0..count)
+        .map(|index| padded(a, index).cmp(&padded(b, index)))
+        .find(|order| *order != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {DIGITS, DIGITS_UPPER_AZ};
+
+    fn cmp(a: &str, b: &str, missing: MissingFields) -> Ordering {
+        cmp_fields(&parse_fields(a, '.', &DIGITS).unwrap(), &parse_fields(b, '.', &DIGITS).unwrap(), missing)
+    }
+
+    #[test]
+    fn numeric_order_of_fields() {
+        assert_eq!(cmp("1.10", "1.9", MissingFields::Zero), Ordering::Greater);
+        assert!("1.10" < "1.9");
+        assert_eq!(cmp("1.2", "1.2.0", MissingFields::Zero), Ordering::Equal);
+        assert_eq!(cmp("1.2", "1.2.0.0", MissingFields::Zero), Ordering::Equal);
+        assert_eq!(cmp("1.2", "1.2.0", MissingFields::Less), Ordering::Less);
+        assert_eq!(cmp("1.2.1", "1.2", MissingFields::Zero), Ordering::Greater);
+        assert_eq!(cmp("1.2.1", "1.3", MissingFields::Less), Ordering::Less);
+        assert_eq!(cmp("2024.06.30", "2024.6.30", MissingFields::Zero), Ordering::Equal);
+        assert_eq!(cmp("0", "0.0", MissingFields::Zero), Ordering::Equal);
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        let hex = &DIGITS_UPPER_AZ[..16];
+        for fields in [vec![0], vec![1, 12, 3], vec![usize::MAX, 0, 255]].iter() {
+            let s = format_fields(fields, '.', hex).unwrap();
+            assert_eq!(parse_fields(&s, '.', hex).as_ref(), Ok(fields));
+        }
+        assert_eq!(format_fields(&[], '.', &DIGITS), Ok("".to_string()));
+        assert_eq!(format_fields(&[255, 1], '-', hex), Ok("FF-1".to_string()));
+        assert_eq!(parse_fields("FF-1", '-', hex), Ok(vec![255, 1]));
+    }
+
+    #[test]
+    fn invalid_versions() {
+        let overflow = "1.2.18446744073709551616";
+        let msg = format!("Field 2 at position 4 of {:?} exceeds usize", overflow);
+        assert_eq!(parse_fields(overflow, '.', &DIGITS), Err(Error::Overflow(msg)));
+        assert_eq!(parse_fields("1.2.18446744073709551615", '.', &DIGITS), Ok(vec![1, 2, usize::MAX]));
+        for (s, position) in [("", 0), (".1", 0), ("1.", 2), ("1..2", 2)].iter() {
+            let msg = format!("at position {} MUST NOT be empty", position);
+            match parse_fields(s, '.', &DIGITS) {
+                Err(Error::InvalidLayout(ref m)) if m.ends_with(&msg) => {}
+                other => panic!("{:?} gave {:?}", s, other),
+            }
+        }
+        let msg = "Char \'x\' at position 2 not found in: ['0', '1']".to_string();
+        assert_eq!(parse_fields("1.x", '.', &['0', '1']), Err(Error::MissingChar(msg)));
+        let msg = "Separator '1' MUST NOT be in: ['0', '1']".to_string();
+        assert_eq!(parse_fields("0", '1', &['0', '1']), Err(Error::InvalidMarker(msg.clone())));
+        assert_eq!(format_fields(&[0], '1', &['0', '1']), Err(Error::InvalidMarker(msg)));
+    }
+}
@@ -0,0 +1,119 @@
+use {checked_seq2dec, Error, NumeralSystem};
+
+
+/// Maps `value` to unsigned like protobuf's zigzag: `0, -1, 1, -2, ...` become `0, 1, 2, 3, ...`.
+///
+/// Small magnitudes stay small, whatever the sign.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_zigzag;
+///
+/// assert_eq!(to_zigzag(0), 0);
+/// assert_eq!(to_zigzag(-1), 1);
+/// assert_eq!(to_zigzag(1), 2);
+/// assert_eq!(to_zigzag(isize::MIN), usize::MAX);
+/// ```
+pub fn to_zigzag(value: isize) -> usize {
+    ((value << 1) ^ (value >> (isize::BITS - 1))) as usize
+}
+
+
+/// Maps zigzag `n` back to signed, the inverse of `to_zigzag`.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_zigzag;
+///
+/// assert_eq!(from_zigzag(3), -2);
+/// assert_eq!(from_zigzag(4), 2);
+/// assert_eq!(from_zigzag(usize::MAX), isize::MIN);
+/// ```
+pub fn from_zigzag(n: usize) -> isize {
+    ((n >> 1) as isize) ^ -((n & 1) as isize)
+}
+
+
+/// Converts `value` zigzag mapped with `to_zigzag` using `char2val` translation.
+///
+/// Zero is a single zero glyph.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::to_zigzag_seq;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(to_zigzag_seq(-1, &hex), Ok("1".to_string()));
+/// assert_eq!(to_zigzag_seq(-128, &hex), Ok("FF".to_string()));
+/// assert_eq!(to_zigzag_seq(0, &hex), Ok("0".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+pub fn to_zigzag_seq(value: isize, char2val: &[char]) -> Result<String, Error> {
+    NumeralSystem::new(char2val)?.format(to_zigzag(value))
+}
+
+
+/// Converts zigzag `sequence` of `to_zigzag_seq` back to signed using `char2val` translation.
+///
+/// # Examples
+///
+/// ```
+/// use numsys::from_zigzag_seq;
+///
+/// let hex: Vec<char> = "0123456789ABCDEF".chars().collect();
+/// assert_eq!(from_zigzag_seq("FF", &hex), Ok(-128));
+/// assert_eq!(from_zigzag_seq("100", &hex), Ok(128));
+/// ```
+///
+/// # Errors
+///
+/// * Returns `Error::DictEmpty` when `char2val` length is 0
+///
+/// * Returns `Error::BaseTooSmall` when `char2val` length is 1
+///
+/// * Returns `Error::MultipleChar` when `char2val` includes duplicated chars
+///
+/// * Returns `Error::MissingChar` when `char2val` missing a char or more
+///
+/// * Returns `Error::Overflow` when value exceeds `usize`
+pub fn from_zigzag_seq(sequence: &str, char2val: &[char]) -> Result<isize, Error> {
+    checked_seq2dec(sequence, char2val).map(from_zigzag)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testutil::XorShift;
+    use DIGITS_UPPER_AZ;
+
+    #[test]
+    fn small_magnitudes_stay_small() {
+        let pairs = [(0, 0), (-1, 1), (1, 2), (-2, 3), (2, 4), (isize::MAX, usize::MAX - 1), (isize::MIN, usize::MAX)];
+        for &(signed, unsigned) in pairs.iter() {
+            assert_eq!(to_zigzag(signed), unsigned);
+            assert_eq!(from_zigzag(unsigned), signed);
+        }
+        let mut rng = XorShift::new(242);
+        for _ in 0..10_000 {
+            let value = rng.next_u64() as isize >> rng.below(64);
+            assert_eq!(from_zigzag(to_zigzag(value)), value);
+            assert_eq!(to_zigzag(value), 2 * value.unsigned_abs() - (value < 0) as usize);
+            let base = 2 + rng.below(35);
+            let sequence = to_zigzag_seq(value, &DIGITS_UPPER_AZ[..base]).unwrap();
+            assert_eq!(from_zigzag_seq(&sequence, &DIGITS_UPPER_AZ[..base]), Ok(value));
+        }
+        assert!(from_zigzag_seq("10000000000000000", &DIGITS_UPPER_AZ[..16]).is_err());
+        assert!(to_zigzag_seq(1, &['a']).is_err());
+    }
+}